@@ -1,149 +1,25 @@
+# tutel computes the candidates itself, this way task indices are completed
+# along with their descriptions, and part of a description completes to the
+# index of the task
 _tutel() {
-    local i cur prev opts cmds
+    local IFS=$'\n'
+    local lines=($("$1" --bpaf-complete-rev=1 "${COMP_WORDS[@]:1:COMP_CWORD}"))
+
     COMPREPLY=()
-    cur="${COMP_WORDS[COMP_CWORD]}"
-    prev="${COMP_WORDS[COMP_CWORD-1]}"
-    cmd=""
-    opts=""
+    # a single candidate replaces the word, it comes without a description
+    if [[ ${#lines[@]} -eq 1 ]]; then
+        COMPREPLY=("${lines[0]}")
+        return
+    fi
 
-    for i in ${COMP_WORDS[@]}
-    do
-        case "${i}" in
-            "$1")
-                cmd="tutel"
-                ;;
-            add)
-                cmd+="__add"
-                ;;
-            completions)
-                cmd+="__completions"
-                ;;
-            done)
-                cmd+="__done"
-                ;;
-            edit)
-                cmd+="__edit"
-                ;;
-            new)
-                cmd+="__new"
-                ;;
-            rm)
-                cmd+="__rm"
-                ;;
-            *)
-                ;;
-        esac
+    local line
+    for line in "${lines[@]}"; do
+        if [[ $line == *$'\t'* ]]; then
+            COMPREPLY+=("$(printf '%-19s %s' "${line%%$'\t'*}" "${line#*$'\t'}")")
+        else
+            COMPREPLY+=("$line")
+        fi
     done
-
-    case "${cmd}" in
-        tutel)
-            opts="-h -V --help --version new add done rm edit completions help"
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__add)
-            opts="-c -h --completed --help <description>..."
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__completions)
-            opts="-h --help bash zsh fish elvish"
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__done)
-            opts="-! -a -h --not --all --help <indices>..."
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__edit)
-            opts="-e -h --editor --help <index>"
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                --editor)
-                    COMPREPLY=($(compgen -f "${cur}"))
-                    return 0
-                    ;;
-                -e)
-                    COMPREPLY=($(compgen -f "${cur}"))
-                    return 0
-                    ;;
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__new)
-            opts="-f -h --force --help <name>"
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-        tutel__rm)
-            opts="-a -c -h --all --cleanup --help --project <indices>..."
-            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
-                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-                return 0
-            fi
-            case "${prev}" in
-                *)
-                    COMPREPLY=()
-                    ;;
-            esac
-            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
-            return 0
-            ;;
-    esac
 }
 
-complete -F _tutel -o bashdefault -o default tutel
-
+complete -F _tutel tutel
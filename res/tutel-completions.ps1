@@ -0,0 +1,25 @@
+# tutel computes the candidates itself, this way task indices are completed
+# along with their descriptions. Needs PowerShell 7.3 or later, older
+# versions drop the empty argument that is being completed.
+Register-ArgumentCompleter -Native -CommandName tutel -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $arguments = @(
+        $commandAst.CommandElements |
+            Select-Object -Skip 1 |
+            Where-Object { $_.Extent.StartOffset -lt $cursorPosition } |
+            ForEach-Object { $_.ToString() }
+    )
+    # the cursor is after a space, an empty argument is being completed
+    if ($wordToComplete -eq '') {
+        $arguments += ''
+    }
+
+    tutel --bpaf-complete-rev=1 @arguments | ForEach-Object {
+        $value, $description = $_ -split "`t", 2
+        if (-not $description) {
+            $description = $value
+        }
+        [System.Management.Automation.CompletionResult]::new($value, $value, 'ParameterValue', $description)
+    }
+}
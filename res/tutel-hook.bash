@@ -0,0 +1,12 @@
+# Shows the pending tasks when entering a project. Add to ~/.bashrc:
+#   eval "$(tutel hook bash)"
+__tutel_hook() {
+    if [ "$PWD" != "${__tutel_dir-}" ]; then
+        tutel hook --summary ${__tutel_dir:+--previous "$__tutel_dir"}
+        __tutel_dir="$PWD"
+    fi
+}
+case ";${PROMPT_COMMAND-};" in
+    *";__tutel_hook;"*) ;;
+    *) PROMPT_COMMAND="__tutel_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
@@ -0,0 +1,8 @@
+# Shows the pending tasks when entering a project. Add to
+# ~/.config/fish/config.fish:
+#   tutel hook fish | source
+function __tutel_hook --on-variable PWD
+    tutel hook --summary --previous "$__tutel_dir"
+    set -g __tutel_dir $PWD
+end
+__tutel_hook
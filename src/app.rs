@@ -1,58 +1,671 @@
-use bpaf::{construct, env, long, positional, short, OptionParser, Parser};
+use bpaf::{construct, env, long, positional, short, Args, OptionParser, ParseFailure, Parser};
+use std::path::PathBuf;
+use tutel::{tr, Boundary, Column, CommentAction, Format, GroupBy, Priority, Query, Status};
+
+use crate::prompt::{PromptShell, StatusFormat};
 
 /// Indicates what Tasks(s) to select
 #[derive(Debug, Clone)]
-pub enum TaskSelector {
-    Indexed(Vec<usize>),
+pub enum TaskSelector<T = usize> {
+    Indexed(Vec<T>),
     All,
-    Completed,
+    /// Completed before the given time, or at all
+    Completed(Option<u64>),
+    /// Not completed yet
+    Pending,
+    /// With this tag in the description
+    Tagged(String),
+    /// Matching the query
+    Matching(Query),
+}
+
+impl<T> TaskSelector<T> {
+    /// Turns the given tasks into others, leaving the other selectors as they are
+    pub fn try_map<U, E>(self, f: impl FnMut(T) -> Result<U, E>) -> Result<TaskSelector<U>, E> {
+        Ok(match self {
+            Self::Indexed(tasks) => {
+                TaskSelector::Indexed(tasks.into_iter().map(f).collect::<Result<_, _>>()?)
+            }
+            Self::All => TaskSelector::All,
+            Self::Completed(before) => TaskSelector::Completed(before),
+            Self::Pending => TaskSelector::Pending,
+            Self::Tagged(tag) => TaskSelector::Tagged(tag),
+            Self::Matching(query) => TaskSelector::Matching(query),
+        })
+    }
+}
+
+/// A task as given on the command line. Ids and aliases are only looked up
+/// once the project and list the command works on are known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskRef {
+    Index(usize),
+    /// An id or alias
+    Name(String),
+}
+
+/// When to use colored output
+#[derive(Debug, Clone, Copy)]
+pub enum ColorChoice {
+    /// Only if stdout is a terminal and `NO_COLOR` is not set
+    Auto,
+    Always,
+    Never,
+}
+
+/// A task that another one depends on
+#[derive(Debug, Clone)]
+pub enum Dependency<T = usize> {
+    Index(T),
+    /// A task of another project, as `project:id`
+    External(String),
+}
+
+/// The language the graph command prints
+#[derive(Debug, Clone, Copy)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+/// What the timesheet sums up the tracked time by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimesheetBy {
+    Day,
+    Task,
+    /// The `#tags` in the descriptions
+    Tag,
+}
+
+/// Options that apply to every command
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub color: ColorChoice,
+    pub ascii: bool,
+    /// Output for screen readers
+    pub plain: bool,
+    /// Output for scripts
+    pub porcelain: bool,
+    /// Report which project was found and how long things took
+    pub verbose: bool,
+    /// Where to stop searching for the project, instead of what the config says
+    pub stop_at: Option<Boundary>,
+    /// Work on this named list of the project instead of the default one
+    pub list: Option<String>,
+    /// Work on the project in this directory or file, or at this ssh url,
+    /// instead of the one in the current directory
+    pub project: Option<String>,
+    pub command: Command,
+}
+
+/// How to show the todo list
+#[derive(Debug, Clone)]
+pub struct ShowOptions {
+    /// Wrap descriptions at this many columns instead of the terminal width
+    pub width: Option<usize>,
+    pub no_pager: bool,
+    /// Lay the tasks out as a table with these columns
+    pub columns: Option<Vec<Column>>,
+    /// List snoozed tasks too
+    pub snoozed: bool,
+    /// List hidden tasks too
+    pub hidden: bool,
+    /// List at most this many tasks
+    pub limit: Option<usize>,
+    /// Only list tasks waiting on something
+    pub waiting: bool,
+    /// Only list stale tasks
+    pub stale: bool,
+    /// Only list the tasks assigned to this person
+    pub assignee: Option<String>,
+    /// Only list the tasks mentioning this person
+    pub mentions: Option<String>,
+    /// Only list the tasks with this tag
+    pub tag: Option<String>,
+    /// Show every project below the current directory
+    pub recursive: bool,
+    pub group_by: Option<GroupBy>,
+    /// The view from the config these options add to, or else a query the
+    /// tasks have to match
+    pub view: Option<String>,
+}
+
+/// The fields `edit` sets with flags, the ones that are none stay as they are
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    pub desc: Option<String>,
+    pub estimate: Option<u64>,
+    /// Empty to unassign the task
+    pub assignee: Option<String>,
+    /// Some(None) removes the due date
+    pub due: Option<Option<u64>>,
+    /// Some(None) removes the priority
+    pub priority: Option<Option<Priority>>,
+    pub pinned: Option<bool>,
+    pub add_tag: Option<String>,
+    pub remove_tag: Option<String>,
+}
+
+impl TaskChanges {
+    /// Without any changes the description is edited in the editor instead
+    pub const fn is_empty(&self) -> bool {
+        self.desc.is_none()
+            && self.estimate.is_none()
+            && self.assignee.is_none()
+            && self.due.is_none()
+            && self.priority.is_none()
+            && self.pinned.is_none()
+            && self.add_tag.is_none()
+            && self.remove_tag.is_none()
+    }
 }
 
 /// The command to execute
 #[derive(Debug, Clone)]
 pub enum Command {
-    Show,
-    NewProject { name: Option<String>, force: bool },
-    AddTask { desc: String, completed: bool },
-    MarkCompletion(bool, TaskSelector),
-    RemoveTask(TaskSelector),
-    EditTask(String, usize),
-    PrintCompletion(String),
+    Show(ShowOptions),
+    ShowTask(TaskRef),
+    /// Show this many of the most urgent tasks
+    Focus(usize),
+    /// Sums up every registered project, with this many of its most urgent
+    /// tasks
+    Dashboard(usize),
+    /// Finds the tasks with these words, archived ones included
+    Search {
+        words: Vec<String>,
+        /// Accept typos, closest matches first
+        fuzzy: bool,
+    },
+    /// Fit the board into this many columns instead of the terminal width
+    Board(Option<usize>),
+    NewProject {
+        name: Option<String>,
+        force: bool,
+        /// How to store the project, instead of what the config says
+        format: Option<Format>,
+    },
+    AddTask {
+        desc: String,
+        completed: bool,
+        estimate: Option<u64>,
+        assignee: Option<String>,
+        /// Even if a similar task exists already
+        force: bool,
+    },
+    /// Captures an idea in the global inbox
+    Capture(String),
+    /// Moves inbox items into projects
+    Triage,
+    /// Adds a task that was completed at the given time, or now
+    Log {
+        desc: String,
+        at: Option<u64>,
+    },
+    /// Makes the task depend on another one, or not anymore if false
+    Depend {
+        depends: bool,
+        index: TaskRef,
+        on: Dependency<TaskRef>,
+    },
+    /// Prints the dependencies of the tasks as a graph
+    Graph(GraphFormat),
+    /// Adds the tasks of the template with this name
+    AddTemplate(String),
+    /// Adds a task, asking for each of its fields
+    AddInteractive,
+    MarkCompletion {
+        completed: bool,
+        /// How the tasks were resolved
+        note: Option<String>,
+        /// What to do with the TODO comments the tasks refer to
+        comment: Option<CommentAction>,
+        selector: TaskSelector<TaskRef>,
+    },
+    RemoveTask(TaskSelector<TaskRef>),
+    EditTask {
+        editor: String,
+        /// Don't ask before saving what came back from the editor
+        yes: bool,
+        selector: TaskSelector<TaskRef>,
+        changes: TaskChanges,
+    },
+    SetTask {
+        index: TaskRef,
+        status: Status,
+        force: bool,
+    },
+    /// Pins the task, or unpins it if false
+    Pin(bool, TaskRef),
+    /// Hides the task, or shows it again if false
+    Hide(bool, TaskRef),
+    Open {
+        index: TaskRef,
+        editor: String,
+    },
+    /// Gives a task a name that works wherever an index does, or removes it
+    Alias {
+        index: TaskRef,
+        alias: Option<String>,
+    },
+    /// Adds a file or url to a task, or removes it again
+    Attach {
+        index: TaskRef,
+        target: String,
+        remove: bool,
+    },
+    Start {
+        index: TaskRef,
+        force: bool,
+    },
+    Stop,
+    Snooze {
+        index: TaskRef,
+        until: u64,
+    },
+    Wait {
+        index: TaskRef,
+        /// Not set if the task isn't waiting anymore
+        on: Option<String>,
+    },
+    Pomodoro {
+        index: TaskRef,
+        work: Option<u64>,
+        rest: Option<u64>,
+    },
+    Timesheet {
+        since: Option<u64>,
+        by: TimesheetBy,
+        csv: bool,
+        query: Option<Query>,
+    },
+    Yank {
+        index: TaskRef,
+        markdown: bool,
+        osc52: bool,
+    },
+    Prompt(Option<PromptShell>),
+    /// Prints the snippet for a shell
+    Hook(String),
+    /// Prints the state of the project as variable assignments
+    Env,
+    /// Lists the named lists of the project
+    Lists,
+    /// Restores the project as it was before the last change
+    Undo,
+    /// Restores the change that was undone last
+    Redo,
+    /// Fails if open tasks have any of these tags, or any task is open at all
+    Guard(Vec<String>),
+    /// Prints what the hook shows when entering the current directory from
+    /// the previous one
+    HookSummary(Option<PathBuf>),
+    /// Prints where the project in effect lives
+    Path,
+    /// Prints the project file as it is stored
+    Cat,
+    /// Runs the commands in this file, or stdin, one per line
+    Batch(Option<PathBuf>),
+    /// Runs these commands, given as their arguments, saving the project once
+    Transaction(Vec<Vec<String>>),
+    /// Stores the project in another format
+    Convert(Format),
+    /// Merges their version of a project file into ours, like a git merge
+    /// driver
+    Merge {
+        base: Option<PathBuf>,
+        ours: PathBuf,
+        theirs: PathBuf,
+        /// Where the project file lives, tells its format
+        name: Option<PathBuf>,
+        /// Resolve conflicts the same way on every side instead of keeping ours
+        auto: bool,
+    },
+    /// Opens the raw project file in an editor
+    EditProject {
+        editor: String,
+        yes: bool,
+    },
+    Status {
+        format: StatusFormat,
+        max_age: u64,
+    },
+    PrintCompletion {
+        shell: Option<String>,
+        install: bool,
+    },
+    Man {
+        output: Option<PathBuf>,
+        command: Option<String>,
+    },
+    /// Only archive what the config says instead of every completed task
+    Archive(bool),
     RemoveProject,
 }
 
-fn options() -> OptionParser<Command> {
+fn options() -> OptionParser<Options> {
+    let show_cmd = show_command()
+        .command("show")
+        .help(tr("show the todo list or a single task"));
+
+    // showing the list is the default, so its options work without the subcommand as well
+    let show = show_list_command().hide();
+
+    let focus_cmd = focus_command()
+        .command("focus")
+        .help(tr("show only the most urgent tasks"));
+
+    let dashboard_cmd = dashboard_command()
+        .command("dashboard")
+        .help(tr("overview of all projects"));
+
+    let search_cmd = search_command()
+        .command("search")
+        .help(tr("find tasks by words, archived ones too"));
+
+    let board_cmd = board_command()
+        .command("board")
+        .help(tr("show the tasks in columns by status"));
+
     let new_cmd = new_project_command()
         .command("new")
-        .help("create a new project");
+        .help(tr("create a new project"));
 
     let add_cmd = add_task_command()
         .command("add")
         .short('a')
-        .help("add a new task");
+        .help(tr("add a new task"));
+
+    let in_cmd = capture_command()
+        .command("in")
+        .help(tr("capture a task in the global inbox"));
+
+    let triage_cmd = triage_command()
+        .command("triage")
+        .help(tr("move tasks from the inbox into projects"));
+
+    let log_cmd = log_command()
+        .command("log")
+        .help(tr("record already completed work"));
 
     let done_cmd = task_completed_command()
         .command("done")
         .short('d')
-        .help("mark a task as being completed");
+        .help(tr("mark a task as being completed"));
+
+    let rm_cmd = remove_task_command()
+        .command("rm")
+        .help(tr("remove a task"));
 
-    let rm_cmd = remove_task_command().command("rm").help("remove a task");
+    let archive_cmd = archive_command()
+        .command("archive")
+        .help(tr("move completed tasks to the archive"));
 
     let edit_cmd = edit_task_command()
         .command("edit")
         .short('e')
-        .help("edit an existing task");
+        .help(tr("edit an existing task"));
+
+    let set_cmd = set_task_command()
+        .command("set")
+        .help(tr("change the status of a task"));
+
+    let pin_cmd = pin_command().command("pin").help(tr("list a task first"));
+
+    let hide_cmd = hide_command()
+        .command("hide")
+        .help(tr("keep a task out of the list"));
+
+    let depend_cmd = depend_command()
+        .command("depend")
+        .help(tr("make a task wait for another one"));
+
+    let graph_cmd = graph_command()
+        .command("graph")
+        .help(tr("print the dependencies between tasks as a graph"));
+
+    let open_cmd = open_command()
+        .command("open")
+        .help(tr("open the urls of a task"));
+
+    let alias_cmd = alias_command()
+        .command("alias")
+        .help(tr("give a task a name to use instead of its index"));
+
+    let attach_cmd = attach_command()
+        .command("attach")
+        .help(tr("attach a file or url to a task"));
+
+    let start_cmd = start_command()
+        .command("start")
+        .help(tr("start working on a task"));
+
+    let stop_cmd = stop_command()
+        .command("stop")
+        .help(tr("stop working on the active task"));
+
+    let snooze_cmd = snooze_command()
+        .command("snooze")
+        .help(tr("hide a task from the list for a while"));
+
+    let wait_cmd = wait_command()
+        .command("wait")
+        .help(tr("mark a task as waiting on something"));
+
+    let pomo_cmd = pomodoro_command()
+        .command("pomo")
+        .help(tr("work on a task for one pomodoro"));
+
+    let timesheet_cmd = timesheet_command()
+        .command("timesheet")
+        .help(tr("summarize the tracked time"));
+
+    let yank_cmd = yank_command()
+        .command("yank")
+        .help(tr("copy a task to the clipboard"));
+
+    let prompt_cmd = prompt_command()
+        .command("prompt")
+        .help(tr("print a summary for the shell prompt"));
+
+    let hook_cmd = hook_command()
+        .command("hook")
+        .help(tr("print a shell hook showing pending tasks on cd"));
+
+    let env_cmd = bpaf::pure(Command::Env)
+        .to_options()
+        .descr(tr(
+            "print the state of the project as shell variables, e.g. TUTEL_PENDING=3",
+        ))
+        .footer(tr("use it in an .envrc with: eval \"$(tutel env)\""))
+        .command("env")
+        .help(tr("print the state of the project as shell variables"));
+
+    let undo_cmd = bpaf::pure(Command::Undo)
+        .to_options()
+        .descr(tr("restore the project as it was before the last change"))
+        .footer(tr(
+            "how many changes can be undone is set by history in the config",
+        ))
+        .command("undo")
+        .help(tr("undo the last change"));
+
+    let redo_cmd = bpaf::pure(Command::Redo)
+        .to_options()
+        .descr(tr("restore the change that was undone last"))
+        .command("redo")
+        .help(tr("redo the last undone change"));
+
+    let lists_cmd = bpaf::pure(Command::Lists)
+        .to_options()
+        .descr(tr(
+            "show the lists of the project and how many of their tasks are pending",
+        ))
+        .footer(tr(
+            "pick one with --list <name>, a list is created when its first task is added",
+        ))
+        .command("lists")
+        .help(tr("show the lists of the project"));
+
+    let guard_cmd = guard_command()
+        .command("guard")
+        .help(tr("fail if tasks are still open, for git hooks"));
+
+    let status_cmd = status_command()
+        .command("status")
+        .help(tr("print a summary for status bars"));
+
+    let path_cmd = bpaf::pure(Command::Path)
+        .to_options()
+        .descr(tr(
+            "print the absolute path of the project file in effect for this directory",
+        ))
+        .command("path")
+        .help(tr("print the path of the project file"));
+
+    let cat_cmd = bpaf::pure(Command::Cat)
+        .to_options()
+        .descr(tr(
+            "print the project file in effect for this directory as it is stored",
+        ))
+        .command("cat")
+        .help(tr("print the project file"));
+
+    let batch_cmd = batch_command()
+        .command("batch")
+        .help(tr("run commands from a file, all or nothing"));
+
+    let tx_cmd = transaction_command()
+        .command("tx")
+        .help(tr("run several commands, saving once"));
+
+    let convert_cmd = convert_command()
+        .command("convert")
+        .help(tr("store the project in another format"));
+
+    let merge_cmd = merge_command()
+        .command("merge")
+        .help(tr("merge two versions of a project file"));
+
+    let project_cmd = project_command()
+        .command("project")
+        .help(tr("work with the project file itself"));
 
     let completion_cmd = print_completions_command()
         .command("completions")
-        .help("print shell completions");
+        .help(tr("print shell completions"));
 
-    construct!([new_cmd, add_cmd, done_cmd, rm_cmd, edit_cmd, completion_cmd])
-        .fallback(Command::Show)
-        .to_options()
-        .version(concat!("tutel v", env!("CARGO_PKG_VERSION")))
-        .descr("tutel\na minimalistic todo app for terminal enthusiasts")
-        .footer("run without a subcommand to show the todo list")
+    let man_cmd = man_command().command("man").help(tr("print the man page"));
+
+    let command = construct!([
+        show_cmd,
+        focus_cmd,
+        dashboard_cmd,
+        search_cmd,
+        board_cmd,
+        new_cmd,
+        add_cmd,
+        in_cmd,
+        triage_cmd,
+        log_cmd,
+        done_cmd,
+        rm_cmd,
+        archive_cmd,
+        edit_cmd,
+        set_cmd,
+        pin_cmd,
+        hide_cmd,
+        depend_cmd,
+        graph_cmd,
+        open_cmd,
+        alias_cmd,
+        attach_cmd,
+        start_cmd,
+        stop_cmd,
+        snooze_cmd,
+        wait_cmd,
+        pomo_cmd,
+        timesheet_cmd,
+        yank_cmd,
+        prompt_cmd,
+        hook_cmd,
+        env_cmd,
+        lists_cmd,
+        undo_cmd,
+        redo_cmd,
+        guard_cmd,
+        status_cmd,
+        path_cmd,
+        cat_cmd,
+        batch_cmd,
+        tx_cmd,
+        convert_cmd,
+        merge_cmd,
+        project_cmd,
+        completion_cmd,
+        man_cmd,
+        show
+    ]);
+
+    let ascii = long("ascii")
+        .help(tr("only use ascii characters in the output"))
+        .switch();
+
+    let plain = long("plain")
+        .help(tr(
+            "screen reader friendly output: words instead of symbols, colors and alignment",
+        ))
+        .switch();
+
+    let porcelain = long("porcelain")
+        .help(tr("stable tab separated output for scripts"))
+        .switch();
+
+    let verbose = short('v')
+        .long("verbose")
+        .help(tr(
+            "report which project file is used and how long it took to find",
+        ))
+        .switch();
+
+    let stop_at = long("stop-at")
+        .help(tr("don't look for the project above the git root or the home directory: git, home or none"))
+        .argument::<String>("where")
+        .complete(|_| vec![("git", None), ("home", None), ("none", None)])
+        .parse::<_, _, String>(|s| s.parse::<Boundary>().map_err(|e| e.to_string()))
+        .optional();
+
+    let list = long("list")
+        .help(tr(
+            "work on this list of the project instead of the default one",
+        ))
+        .argument::<String>("name")
+        .optional();
+
+    // without a long name, rm --project removes the project file
+    let project = short('p')
+        .help(tr(
+            "use the project in this directory instead, or on another machine: ssh://host/path",
+        ))
+        .argument::<String>("location")
+        .optional();
+
+    construct!(Options {
+        color(),
+        ascii,
+        plain,
+        porcelain,
+        verbose,
+        stop_at,
+        list,
+        project,
+        command
+    })
+    .to_options()
+    .version(concat!("tutel v", env!("CARGO_PKG_VERSION")))
+    .descr(tr(
+        "tutel\na minimalistic todo app for terminal enthusiasts",
+    ))
+    .footer(tr("run without a subcommand to show the todo list"))
 }
 
 #[test]
@@ -61,26 +674,380 @@ fn check_bpaf_invariants() {
 }
 
 /// Parse the command line and return the command to be executed
-pub fn parse_cli() -> Command {
+pub fn parse_cli() -> Options {
     options().run()
 }
 
+/// Parses one line of a batch file into the command it stands for
+pub fn parse_line(line: &str) -> Result<Command, String> {
+    parse_words(&split_words(line)?)
+}
+
+/// Parses the arguments of a single command, without the program name.
+/// Options that apply to the whole run, like `--list` or `--ascii`, are an
+/// error: they are given once, before `batch` or `tx`.
+pub fn parse_words(words: &[String]) -> Result<Command, String> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    match options().run_inner(Args::from(words.as_slice())) {
+        Ok(Options {
+            color: ColorChoice::Auto,
+            ascii: false,
+            plain: false,
+            porcelain: false,
+            verbose: false,
+            stop_at: None,
+            list: None,
+            project: None,
+            command,
+        }) => Ok(command),
+        Ok(_) => Err(tr(
+            "options like --list and -p apply to the whole run, give them before batch or tx",
+        )
+        .to_string()),
+        Err(ParseFailure::Stdout(msg) | ParseFailure::Stderr(msg)) => Err(msg),
+    }
+}
+
+#[test]
+fn reject_global_options_per_command() {
+    assert!(parse_line("add x").is_ok());
+    // not -p of the whole run
+    assert!(matches!(
+        parse_line("rm --project"),
+        Ok(Command::RemoveProject)
+    ));
+    for line in ["--list work add x", "-p /tmp add y", "--ascii show"] {
+        assert!(parse_line(line).is_err(), "{}", line);
+    }
+}
+
+/// Splits a line into words like a shell would, minus all the expansions:
+/// quotes group words and a backslash escapes the next character
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some('\'')) => word.get_or_insert_with(String::new).push(c),
+            ('\\', _) => {
+                let next = chars
+                    .next()
+                    .ok_or_else(|| tr!("line ends with a backslash"))?;
+                word.get_or_insert_with(String::new).push(next);
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(tr!("unterminated quote"));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[test]
+fn split_batch_lines() {
+    assert_eq!(
+        split_words(r#"done --note "shipped in v1.2" 3"#).unwrap(),
+        ["done", "--note", "shipped in v1.2", "3"]
+    );
+    assert_eq!(
+        split_words(r#"add it\'s 'a \ b' """#).unwrap(),
+        ["add", "it's", "a \\ b", ""]
+    );
+    assert!(split_words("add \"oops").is_err());
+}
+
+/// The `--help` output of tutel or one of its subcommands
+pub fn help(command: Option<&str>) -> Option<String> {
+    let args: Vec<&str> = command.into_iter().chain(["--help"]).collect();
+    match options().run_inner(Args::from(args.as_slice())) {
+        Err(ParseFailure::Stdout(help)) => Some(help),
+        _ => None,
+    }
+}
+
+fn color() -> impl Parser<ColorChoice> {
+    long("color")
+        .help(tr(
+            "when to use colors: auto, always or never (default: auto)",
+        ))
+        .argument::<String>("when")
+        .complete(|_| vec![("auto", None), ("always", None), ("never", None)])
+        .parse::<_, _, String>(|s| match s.as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(tr!("invalid color choice: {s}", s)),
+        })
+        .fallback(ColorChoice::Auto)
+}
+
+fn show_command() -> OptionParser<Command> {
+    let options = show_options();
+    let target = positional::<String>("index|view")
+        .help(tr(
+            "a task to show everything about, a view from the config or a query",
+        ))
+        .complete(complete_index)
+        .optional();
+
+    construct!(options, target)
+        .map(|(mut options, target)| {
+            // anything that isn't an index may still be a view, ids and
+            // aliases are told apart from those once the project is loaded
+            match target.map(|target| target.parse::<usize>().map_err(|_| target)) {
+                Some(Ok(index)) => Command::ShowTask(TaskRef::Index(index)),
+                Some(Err(view)) => {
+                    options.view = Some(view);
+                    Command::Show(options)
+                }
+                None => Command::Show(options),
+            }
+        })
+        .to_options()
+        .descr(tr(
+            "show the todo list, or everything about a single task if an index is given",
+        ))
+        .footer(tr("views are named show options in the config, like\n\
+            [view]\n\
+            work = \"tag:work sort:urgency group:status\"\n\
+            queries combine terms with and, or, not and parentheses, like\n\
+            tutel show 'tag:home and (age>1w or status:blocked)'"))
+}
+
+fn show_list_command() -> impl Parser<Command> {
+    show_options().map(Command::Show)
+}
+
+fn show_options() -> impl Parser<ShowOptions> {
+    let width = long("width")
+        .help(tr(
+            "wrap descriptions at this many columns (default: terminal width)",
+        ))
+        .argument::<usize>("cols")
+        .optional();
+
+    let no_pager = long("no-pager")
+        .help(tr("don't pipe long lists into $PAGER"))
+        .switch();
+
+    let table = long("table")
+        .help(tr("show the tasks as a table"))
+        .req_flag(Column::ALL.to_vec());
+
+    let columns = long("columns")
+        .help(tr(
+            "the columns of the table, comma separated (implies --table)",
+        ))
+        .argument::<String>("columns")
+        .parse::<_, _, String>(|s| {
+            s.split(',')
+                .map(|c| c.trim().parse::<Column>().map_err(|e| e.to_string()))
+                .collect()
+        });
+
+    let columns = construct!([columns, table]).optional();
+
+    let snoozed = long("snoozed")
+        .help(tr("list snoozed tasks as well"))
+        .switch();
+
+    let hidden = long("hidden")
+        .help(tr("list hidden tasks as well"))
+        .switch();
+
+    let limit = limit("list at most this many tasks").optional();
+
+    let stale = long("stale")
+        .help(tr("only list tasks that are open for too long"))
+        .switch();
+
+    let waiting = long("waiting")
+        .help(tr("only list the tasks waiting on something"))
+        .switch();
+
+    let assignee = assignee("only list the tasks assigned to this person");
+
+    let mentions = long("mentions")
+        .help(tr(
+            "only list the tasks mentioning @name in their description",
+        ))
+        .argument::<String>("name")
+        .optional();
+
+    let tag = tag("only list the tasks with #tag in their description").optional();
+
+    let recursive = short('r')
+        .long("recursive")
+        .help(tr("show the projects in all directories below this one"))
+        .switch();
+
+    let group_by = long("group-by")
+        .help(tr(
+            "list the tasks under a heading per tag, status, priority or due date, with counts",
+        ))
+        .argument::<String>("tag|status|priority|due")
+        .complete(|_| GroupBy::ALL.iter().map(|g| (g.name(), None)).collect())
+        .parse::<_, _, String>(|s| s.parse::<GroupBy>().map_err(|e| e.to_string()))
+        .optional();
+
+    // set from the positional of `show`
+    let view = bpaf::pure(None);
+
+    construct!(ShowOptions {
+        width,
+        no_pager,
+        columns,
+        snoozed,
+        hidden,
+        limit,
+        waiting,
+        stale,
+        assignee,
+        mentions,
+        tag,
+        recursive,
+        group_by,
+        view
+    })
+}
+
+fn matching(help: &'static str) -> impl Parser<TaskSelector<TaskRef>> {
+    query(help).map(TaskSelector::Matching)
+}
+
+fn query(help: &'static str) -> impl Parser<Query> {
+    long("where")
+        .help(tr(help))
+        .argument::<String>("query")
+        .parse::<_, _, String>(|s| s.parse::<Query>().map_err(|e| e.to_string()))
+}
+
+fn tag(help: &'static str) -> impl Parser<String> {
+    long("tag").help(tr(help)).argument::<String>("tag")
+}
+
+fn assignee(help: &'static str) -> impl Parser<Option<String>> {
+    long("assignee")
+        .help(tr(help))
+        .argument::<String>("name")
+        .optional()
+}
+
+fn limit(help: &'static str) -> impl Parser<usize> {
+    short('n')
+        .long("limit")
+        .help(tr(help))
+        .argument::<usize>("count")
+}
+
+fn focus_command() -> OptionParser<Command> {
+    let limit = limit("how many tasks to show (default: 3)").fallback(3);
+
+    construct!(Command::Focus(limit))
+        .to_options()
+        .descr(tr(
+            "show only the few most urgent tasks that are not done yet",
+        ))
+        .footer(tr("pinned tasks always come first"))
+}
+
+fn dashboard_command() -> OptionParser<Command> {
+    let limit = limit("how many tasks to show per project (default: 3)").fallback(3);
+
+    construct!(Command::Dashboard(limit))
+        .to_options()
+        .descr(tr("sum up every project tutel knows about: what is left, what is stale and the most urgent tasks"))
+        .footer(
+            tr("projects are known once a command changed them. Tasks are stale when open for \
+            longer than stale in the config, over estimate when more time was tracked than \
+            estimated."),
+        )
+}
+
+fn search_command() -> OptionParser<Command> {
+    let words = positional::<String>("words")
+        .help(tr("the words to look for, the start of a word is enough"))
+        .some(tr("what to search for is required"));
+
+    let fuzzy = short('f')
+        .long("fuzzy")
+        .help(tr(
+            "match words with typos or abbreviated ones too, closest matches first",
+        ))
+        .switch();
+
+    construct!(Command::Search { fuzzy, words })
+        .to_options()
+        .descr(tr(
+            "find the tasks of all lists and the archive having every one of the words",
+        ))
+        .footer(tr(
+            "with search-index = true in the config the archive is kept in an index for this",
+        ))
+}
+
+fn board_command() -> OptionParser<Command> {
+    let width = long("width")
+        .help(tr(
+            "fit the board into this many columns (default: terminal width)",
+        ))
+        .argument::<usize>("cols")
+        .optional();
+
+    construct!(Command::Board(width))
+        .to_options()
+        .descr(tr(
+            "show the tasks side by side in one column per status, like a kanban board",
+        ))
+        .footer(tr(
+            "descriptions that don't fit into their column are cut off",
+        ))
+}
+
 fn new_project_command() -> OptionParser<Command> {
-    let name = positional::<String>("name").optional();
+    let name = positional::<String>("name")
+        .complete(complete_project_name)
+        .optional();
     let force = short('f')
         .long("force")
-        .help("force project creation")
+        .help(tr("force project creation"))
         .switch();
+    let format = long("format")
+        .help(tr(
+            "store the project as toml, yaml, a markdown checklist(TODO.md) or binary",
+        ))
+        .argument::<String>("format")
+        .complete(|_| Format::ALL.iter().map(|f| (f.name(), None)).collect())
+        .parse::<_, _, String>(|s| s.parse::<Format>().map_err(|e| e.to_string()))
+        .optional();
 
-    construct!(Command::NewProject { force, name })
-        .to_options()
-        .descr("create a new project in the current directory")
+    construct!(Command::NewProject {
+        force,
+        format,
+        name
+    })
+    .to_options()
+    .descr(tr("create a new project in the current directory"))
 }
 
-fn add_task_command() -> OptionParser<Command> {
-    let desc = positional::<String>("description")
+/// Everything after the subcommand, merged into a single description
+fn description() -> impl Parser<String> {
+    positional::<String>("description")
         .many()
-        .guard(|v| !v.is_empty(), "the task description is required")
+        .guard(|v| !v.is_empty(), tr("the task description is required"))
         .map(|v| {
             let mut desc = String::new();
             let vlen = v.len();
@@ -92,115 +1059,1071 @@ fn add_task_command() -> OptionParser<Command> {
                 }
             }
             desc
-        });
+        })
+}
+
+fn add_task_command() -> OptionParser<Command> {
+    let desc = description();
 
     let completed = short('c')
         .long("completed")
-        .help("mark the task as already completed")
+        .help(tr("mark the task as already completed"))
         .switch();
 
-    construct!(Command::AddTask { completed, desc })
+    let estimate = estimate();
+
+    let force = short('f')
+        .long("force")
+        .help(tr("add the task even if a similar one is open already"))
+        .switch();
+
+    let assignee = assignee("who takes care of the task");
+
+    let add = construct!(Command::AddTask {
+        completed,
+        estimate,
+        assignee,
+        force,
+        desc
+    });
+
+    let template = long("template")
+        .help(tr("add the tasks of a template from the config instead"))
+        .argument::<String>("name")
+        .complete(complete_template)
+        .map(Command::AddTemplate);
+
+    let interactive = short('i')
+        .long("interactive")
+        .help(tr(
+            "ask for the description and the other fields one by one",
+        ))
+        .req_flag(Command::AddInteractive);
+
+    construct!([template, interactive, add])
+        .to_options()
+        .descr(tr("add a new task. aliases: a"))
+        .footer(tr(
+            "the values of placeholders like {version} in a template are asked for",
+        ))
+}
+
+fn capture_command() -> OptionParser<Command> {
+    construct!(Command::Capture(description()))
+        .to_options()
+        .descr(tr(
+            "add a task to the global inbox, no matter which directory you are in",
+        ))
+        .footer(tr("tutel triage moves it into a project later on"))
+}
+
+fn triage_command() -> OptionParser<Command> {
+    bpaf::pure(Command::Triage)
+        .to_options()
+        .descr(tr(
+            "go through the inbox, moving every task into a project, deleting or skipping it",
+        ))
+        .footer(tr(
+            "a task is moved into the project in effect for the directory you enter",
+        ))
+}
+
+fn log_command() -> OptionParser<Command> {
+    let at = long("at")
+        .help(tr(
+            "when the work was done: today, yesterday, 2024-03-01 or 3h(ago) (default: now)",
+        ))
+        .argument::<String>("date")
+        .parse::<_, _, String>(|s| {
+            tutel::parse_date(&s, tutel::now()).ok_or_else(|| tr!("invalid date: {s}", s))
+        })
+        .optional();
+
+    let desc = description();
+
+    construct!(Command::Log { at, desc })
         .to_options()
-        .descr("add a new task. aliases: a")
+        .descr(tr("record work that is already done as a completed task"))
 }
 
 fn task_completed_command() -> OptionParser<Command> {
     let completed = short('!')
         .long("not")
-        .help("mark the task as not being done")
+        .help(tr("mark the task as not being done"))
         .flag(false, true);
 
     // can unconditionally be mapped to TaskSelector::All since its value is only used if it is
     // present
     let all = short('a')
         .long("all")
-        .help("select all tasks")
+        .help(tr("select all tasks"))
         .req_flag(TaskSelector::All);
 
-    let selector = construct!([parse_indices(), all]);
-    construct!(Command::MarkCompletion(completed, selector))
-        .to_options()
-        .descr("mark a task as being done. aliases: d")
+    let pending = long("pending")
+        .help(tr("select all tasks that are not completed"))
+        .req_flag(TaskSelector::Pending);
+
+    let done = long("done")
+        .help(tr(
+            "select all completed tasks, e.g. --not --done reopens them",
+        ))
+        .req_flag(TaskSelector::Completed(None));
+
+    let note = long("note")
+        .help(tr("how the task was resolved, e.g. \"shipped in v1.2\""))
+        .argument::<String>("note")
+        .optional();
+
+    let comment = long("comment")
+        .help(tr(
+            "delete or annotate the TODO comment at the file:line in the description",
+        ))
+        .argument::<String>("action")
+        .complete(|_| vec![("delete", None), ("annotate", None)])
+        .parse::<_, _, String>(|s| s.parse::<CommentAction>().map_err(|e| e.to_string()))
+        .optional();
+
+    let matching = matching("select all tasks matching the query");
+
+    let selector = construct!([parse_indices(), all, pending, done, matching]);
+    construct!(Command::MarkCompletion {
+        completed,
+        note,
+        comment,
+        selector
+    })
+    .guard(
+        |cmd| {
+            !matches!(
+                cmd,
+                Command::MarkCompletion {
+                    completed: false,
+                    note: Some(_),
+                    ..
+                }
+            )
+        },
+        tr("--note only works when completing a task"),
+    )
+    .to_options()
+    .descr(tr("mark a task as being done. aliases: d"))
+    .footer(tr(
+        "the note is shown by show <index> and timesheet --by-task",
+    ))
 }
 
 fn remove_task_command() -> OptionParser<Command> {
     let all = short('a')
         .long("all")
-        .help("remove all tasks")
+        .help(tr("remove all tasks"))
         .req_flag(TaskSelector::All);
 
     let cleanup = short('c')
         .long("cleanup")
-        .help("remove all completed tasks")
-        .req_flag(TaskSelector::Completed);
+        .help(tr("remove all completed tasks"))
+        .req_flag(TaskSelector::Completed(None));
+
+    let pending = long("pending")
+        .help(tr("remove all tasks that are not completed"))
+        .req_flag(TaskSelector::Pending);
+
+    let older_than = long("older-than")
+        .help(tr(
+            "with --cleanup, only remove tasks completed this long ago, e.g. 2w",
+        ))
+        .argument::<String>("duration")
+        .parse::<_, _, String>(|s| {
+            let age = tutel::parse_duration(&s).ok_or_else(|| tr!("invalid duration: {s}", s))?;
+            Ok(tutel::now().saturating_sub(age))
+        })
+        .optional();
 
     let project = long("project")
-        .help("remove the whole project file")
+        .help(tr("remove the whole project file"))
         .req_flag(Command::RemoveProject);
 
-    let remove_task = construct!([parse_indices(), all, cleanup]).map(Command::RemoveTask);
+    let matching = matching("remove all tasks matching the query");
+
+    let selector = construct!([all, cleanup, pending, matching, parse_indices()]);
+    let remove_task = construct!(older_than, selector)
+        .guard(
+            |(before, selector)| before.is_none() || matches!(selector, TaskSelector::Completed(_)),
+            tr("--older-than only works with --cleanup"),
+        )
+        .map(|(before, selector)| match selector {
+            TaskSelector::Completed(_) => TaskSelector::Completed(before),
+            selector => selector,
+        })
+        .map(Command::RemoveTask);
 
     construct!([remove_task, project])
         .to_options()
-        .descr("remove a task from a project")
+        .descr(tr("remove a task from a project"))
+}
+
+/// Suggests the name of the current directory, the default for new projects
+#[allow(clippy::ptr_arg)]
+fn complete_project_name(input: &String) -> Vec<(String, Option<String>)> {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| Some(dir.file_name()?.to_string_lossy().to_string()))
+        .filter(|name| name.starts_with(input.as_str()))
+        .map(|name| (name, Some(tr("the current directory").to_string())))
+        .into_iter()
+        .collect()
+}
+
+/// Suggests the templates defined in the config
+#[allow(clippy::ptr_arg)]
+fn complete_template(input: &String) -> Vec<(String, Option<String>)> {
+    let Ok(config) = tutel::Config::load() else {
+        return Vec::new();
+    };
+
+    config
+        .templates
+        .into_iter()
+        .filter(|t| t.name.starts_with(input.as_str()))
+        .map(|t| {
+            let hint = t.tasks.join(", ");
+            (t.name, Some(hint))
+        })
+        .collect()
 }
 
+// bpaf hands completers a reference to the parsed value
+#[allow(clippy::ptr_arg)]
 fn complete_indices(input: &Vec<String>) -> Vec<(String, Option<String>)> {
-    let p = tutel::load_project_rec(&*std::env::current_dir().unwrap()).unwrap();
+    // there is nothing to complete outside of a project
+    let Ok(p) = std::env::current_dir()
+        .map_err(anyhow::Error::from)
+        .and_then(|dir| tutel::load_project_rec(&dir))
+    else {
+        return Vec::new();
+    };
     let mut res = Vec::new();
 
     let full = &input[..input.len() - 1];
     let active = input.last().unwrap();
 
-    for task in p.data.tasks {
-        let tid = task.index.to_string();
-        if full.contains(&tid) {
-            continue;
-        }
-        if tid.starts_with(active) {
-            res.push((format!("{}", task.index), Some(task.desc.clone())));
+    let tasks = p
+        .data
+        .tasks
+        .iter()
+        .filter(|task| !full.contains(&task.index.to_string()));
+
+    let matching: Vec<_> = if active.chars().all(|c| c.is_ascii_digit()) {
+        tasks
+            .filter(|task| task.index.to_string().starts_with(active.as_str()))
+            .collect()
+    } else {
+        // anything but an index is looked up in the descriptions, loosely
+        // only if nothing contains it as is
+        let active = active.to_lowercase();
+        let tasks: Vec<_> = tasks.collect();
+        let exact: Vec<_> = tasks
+            .iter()
+            .copied()
+            .filter(|task| task.desc.to_lowercase().contains(&active))
+            .collect();
+        if exact.is_empty() {
+            tasks
+                .into_iter()
+                .filter(|task| fuzzy_match(&task.desc, &active))
+                .collect()
+        } else {
+            exact
         }
+    };
+
+    for task in matching {
+        res.push((format!("{}", task.index), Some(task.desc.clone())));
     }
 
     res
 }
 
-fn parse_indices() -> impl Parser<TaskSelector> {
+/// Whether all characters of the pattern appear in the text in the same
+/// order, ignoring case
+fn fuzzy_match(text: &str, pattern: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|p| text.any(|c| c == p))
+}
+
+#[test]
+fn fuzzy_match_descriptions() {
+    assert!(fuzzy_match("Buy groceries", "gro"));
+    assert!(fuzzy_match("Buy groceries", "bgr"));
+    assert!(!fuzzy_match("Buy groceries", "rgb"));
+}
+
+/// Completes a single index, showing the descriptions of the tasks as hints
+#[allow(clippy::ptr_arg)]
+fn complete_index(input: &String) -> Vec<(String, Option<String>)> {
+    complete_indices(&vec![input.clone()])
+}
+
+/// Takes an index, or something that may be the id or alias of a task
+fn task_ref(x: &str) -> Result<TaskRef, String> {
+    if let Ok(index) = x.parse::<usize>() {
+        return Ok(TaskRef::Index(index));
+    }
+    if !x.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Err(tr!("not a valid index or id: {x}", x));
+    }
+    Ok(TaskRef::Name(x.to_string()))
+}
+
+fn parse_index() -> impl Parser<TaskRef> {
+    // parsed from a string, a partially typed index wouldn't reach the completer otherwise
+    positional::<String>("index")
+        .complete(complete_index)
+        .parse::<_, _, String>(|x| task_ref(&x))
+}
+
+fn parse_indices() -> impl Parser<TaskSelector<TaskRef>> {
     positional::<String>("indices")
-        .some("one or more task indices are required")
+        .help(tr(
+            "task indices or ids, - reads them from stdin, one per line",
+        ))
+        .some(tr("one or more task indices are required"))
         .complete(complete_indices)
         .parse::<_, _, String>(|v| {
             let mut indices = Vec::with_capacity(v.len());
 
             for x in v {
-                indices.push(
-                    x.parse::<usize>()
-                        .map_err(|_| format!("not a valid index: {x}"))?,
-                )
+                if x == "-" {
+                    indices.extend(read_indices(std::io::stdin().lock())?);
+                } else {
+                    indices.push(task_ref(&x)?);
+                }
             }
 
             Ok(TaskSelector::Indexed(indices))
         })
 }
 
+/// Reads indices or ids, one per line. Only the first word of a line counts,
+/// so the output of `--porcelain show` can be piped in directly.
+fn read_indices(input: impl std::io::BufRead) -> Result<Vec<TaskRef>, String> {
+    let mut indices = Vec::new();
+    for line in input.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(x) = line.split_whitespace().next() {
+            indices.push(task_ref(x)?);
+        }
+    }
+    Ok(indices)
+}
+
+#[test]
+fn read_porcelain_indices() {
+    let input = "3\tpending\tk3x9qa\t\tbuy milk\n\n 12 \n";
+    assert_eq!(
+        read_indices(input.as_bytes()),
+        Ok(vec![TaskRef::Index(3), TaskRef::Index(12)])
+    );
+    assert!(read_indices(&b"!!"[..]).is_err());
+}
+
+fn estimate() -> impl Parser<Option<u64>> {
+    long("estimate")
+        .help(tr(
+            "how long the task is expected to take, e.g. 30m, 2h or 1d",
+        ))
+        .argument::<String>("duration")
+        .parse::<_, _, String>(|s| {
+            tutel::parse_duration(&s).ok_or_else(|| tr!("invalid duration: {s}", s))
+        })
+        .optional()
+}
+
+fn archive_command() -> OptionParser<Command> {
+    let auto = long("auto")
+        .help(tr(
+            "only archive tasks completed longer ago than [archive] after in the config",
+        ))
+        .switch();
+
+    construct!(Command::Archive(auto))
+        .to_options()
+        .descr(tr(
+            "move completed tasks from the list into the archive file next to it",
+        ))
+        .footer(tr(
+            "with [archive] after set in the config this happens on every change to the list",
+        ))
+}
+
 fn edit_task_command() -> OptionParser<Command> {
-    let index = positional::<usize>("index");
+    let all = short('a')
+        .long("all")
+        .help(tr("edit all tasks"))
+        .req_flag(TaskSelector::All);
+    let tagged = tag("edit all tasks with #tag in their description").map(TaskSelector::Tagged);
+    let matching = matching("edit all tasks matching the query");
+    let selector = construct!([all, tagged, matching, parse_indices()]);
+
+    let editor = editor();
+    let yes = yes();
+
+    let desc = long("desc")
+        .help(tr("the new description"))
+        .argument::<String>("text")
+        .optional();
+    let estimate = estimate();
+    let assignee = assignee("who takes care of the task, empty to unassign it");
+    let due = long("due")
+        .help(tr(
+            "when the task has to be done: tomorrow, friday, 2024-03-01 or 3d(from now), empty to remove it",
+        ))
+        .argument::<String>("date")
+        .parse::<_, _, String>(|s| {
+            if s.is_empty() {
+                return Ok(None);
+            }
+            tutel::parse_until(&s, tutel::now())
+                .map(Some)
+                .ok_or_else(|| tr!("invalid date: {s}", s))
+        })
+        .optional();
+    let priority = long("priority")
+        .help(tr("low, medium or high, empty to remove it"))
+        .argument::<String>("priority")
+        .parse::<_, _, String>(|s| {
+            if s.is_empty() {
+                return Ok(None);
+            }
+            s.parse::<Priority>().map(Some).map_err(|e| e.to_string())
+        })
+        .optional();
+    let pin = long("pin").help(tr("pin the task")).req_flag(true);
+    let unpin = long("unpin").help(tr("unpin the task")).req_flag(false);
+    let pinned = construct!([pin, unpin]).optional();
+    let add_tag = long("add-tag")
+        .help(tr("add #tag to the description"))
+        .argument::<String>("tag")
+        .optional();
+    let remove_tag = long("remove-tag")
+        .help(tr("remove #tag from the description"))
+        .argument::<String>("tag")
+        .optional();
+    let changes = construct!(TaskChanges {
+        desc,
+        estimate,
+        assignee,
+        due,
+        priority,
+        pinned,
+        add_tag,
+        remove_tag
+    });
+
+    construct!(Command::EditTask {
+        editor,
+        yes,
+        changes,
+        selector
+    })
+    .to_options()
+    .descr(tr("edit an existing task. aliases: e"))
+    .footer(tr(
+        "the editor is only launched if no field is set with a flag. \
+        What changed is shown before it is saved.",
+    ))
+}
+
+#[test]
+fn edit_fields_with_flags() {
+    let Ok(Command::EditTask { changes, .. }) =
+        parse_line("edit 3 --desc 'new text' --due friday --priority high")
+    else {
+        panic!("edit with flags doesn't parse");
+    };
+    assert_eq!(changes.desc.as_deref(), Some("new text"));
+    assert!(matches!(changes.due, Some(Some(_))));
+    assert_eq!(changes.priority, Some(Some(Priority::High)));
+
+    let Ok(Command::EditTask { changes, .. }) = parse_line("edit 3 --due '' --priority ''") else {
+        panic!("edit clearing fields doesn't parse");
+    };
+    assert_eq!((changes.due, changes.priority), (Some(None), Some(None)));
+    assert!(parse_line("edit 3 --priority urgent").is_err());
+}
 
-    let editor = env("EDITOR")
+fn yes() -> impl Parser<bool> {
+    short('y')
+        .long("yes")
+        .help(tr("save the changes from the editor without asking"))
+        .switch()
+}
+
+fn editor() -> impl Parser<String> {
+    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
+    env("EDITOR")
         .short('e')
         .long("editor")
-        .help("the editor to use (default: $EDITOR)")
-        .argument::<String>("editor");
+        .help(tr(
+            "the editor to use (default: $EDITOR, then vi or notepad on Windows)",
+        ))
+        .argument::<String>("editor")
+        .fallback(default_editor.to_string())
+}
+
+fn convert_command() -> OptionParser<Command> {
+    let format = positional::<String>("format")
+        .help(tr("toml, yaml, markdown or binary"))
+        .complete(|_| Format::ALL.iter().map(|f| (f.name(), None)).collect())
+        .parse::<_, _, String>(|s| s.parse::<Format>().map_err(|e| e.to_string()));
 
-    construct!(Command::EditTask(editor, index))
+    construct!(Command::Convert(format))
         .to_options()
-        .descr("edit an existing task. aliases: e")
+        .descr(tr(
+            "store the project in another format, replacing the project file",
+        ))
+        .footer(tr(
+            "binary files load a lot faster than text when a list has grown to tens of \
+            thousands of tasks. A markdown checklist only keeps whether tasks are done.",
+        ))
+}
+
+fn merge_command() -> OptionParser<Command> {
+    let base = long("base")
+        .help(tr(
+            "the version both started from. Without it, tasks only one side has are kept",
+        ))
+        .argument::<PathBuf>("file")
+        .optional();
+    let name = long("name")
+        .help(tr(
+            "the name of the project file, to tell its format (default: the name of ours)",
+        ))
+        .argument::<PathBuf>("file")
+        .optional();
+    let auto = long("auto")
+        .help(tr(
+            "never fail: resolve conflicts the same way whichever side merges",
+        ))
+        .switch();
+    let ours = positional::<PathBuf>("ours").help(tr("our version, the result is written here"));
+    let theirs = positional::<PathBuf>("theirs").help(tr("their version"));
+
+    construct!(Command::Merge {
+        base,
+        name,
+        auto,
+        ours,
+        theirs
+    })
+    .to_options()
+    .descr(tr("merge two versions of a project file, task by task"))
+    .footer(tr(
+        "tasks are matched by id and merged field by field. Where both sides changed a field, \
+        ours is kept and the conflict is reported, and tutel exits with an error. \
+        With --auto, devices syncing the file through git converge instead: changed tasks \
+        win over removed ones, done over open and the greater value otherwise, and \
+        tasks added on both sides get indices that don't depend on who merges. \
+        To let git merge project files this way:\n\
+        git config merge.tutel.driver \"tutel merge --base %O --name %P %A %B\"\n\
+        echo \".tutel.* merge=tutel\" >> .gitattributes",
+    ))
+}
+
+fn project_command() -> OptionParser<Command> {
+    let editor = editor();
+    let yes = yes();
+    let edit = construct!(Command::EditProject { editor, yes })
+        .to_options()
+        .descr(tr("edit the project file in your editor"))
+        .footer(tr(
+            "the file is only saved if it still is a valid project, \
+            after showing the tasks that changed",
+        ))
+        .command("edit")
+        .help(tr("edit the project file in your editor"));
+
+    construct!([edit])
+        .to_options()
+        .descr(tr("work with the project file itself"))
+}
+
+fn transaction_command() -> OptionParser<Command> {
+    let words = bpaf::any::<String>("command")
+        .help(tr("the commands with their arguments, separated by --and"))
+        .guard(|w| w != "--help" && w != "-h", "")
+        .many()
+        .guard(|words| !words.is_empty(), tr("no commands given"))
+        .map(|words| {
+            words
+                .split(|w| w == "--and")
+                .map(<[String]>::to_vec)
+                .collect::<Vec<_>>()
+        })
+        .guard(
+            |commands| commands.iter().all(|c| !c.is_empty()),
+            tr("every --and needs a command on both sides"),
+        );
+
+    construct!(Command::Transaction(words))
+        .to_options()
+        .descr(tr(
+            "run several commands, writing the project file once at the end",
+        ))
+        .footer(tr("tutel tx done 3 --and add \"write the follow-up\"\n\
+            if any of the commands fails, the project file stays unchanged. Until then \
+            the changes are made to a copy, which replaces the project file in one step. \
+            options like --list apply to every command of the transaction."))
+}
+
+fn batch_command() -> OptionParser<Command> {
+    let file = positional::<PathBuf>("file")
+        .help(tr(
+            "the commands to run, one per line. Reads stdin if missing or -",
+        ))
+        .optional()
+        .map(|file| file.filter(|f| f.as_os_str() != "-"));
+
+    construct!(Command::Batch(file))
+        .to_options()
+        .descr(tr(
+            "run a sequence of tutel commands, one per line, all or nothing",
+        ))
+        .footer(tr(
+            "empty lines and lines starting with # are skipped. Every line is checked before \
+            the first one runs, and if any command fails, the project is restored to what \
+            it was before the batch. options like --list go before batch, not on a line.",
+        ))
+}
+
+fn set_task_command() -> OptionParser<Command> {
+    let status = long("status")
+        .help(tr("pending, in-progress, blocked, done or cancelled"))
+        .argument::<String>("status")
+        .complete(|_| Status::ALL.iter().map(|s| (s.name(), None)).collect())
+        .parse::<_, _, String>(|s| s.parse::<Status>().map_err(|e| e.to_string()));
+    let force = exceed_wip_limit();
+    let index = parse_index();
+
+    construct!(Command::SetTask {
+        status,
+        force,
+        index
+    })
+    .to_options()
+    .descr(tr("change the status of a task"))
+    .footer(tr("done and cancelled tasks count as completed"))
+}
+
+fn pin_command() -> OptionParser<Command> {
+    let pinned = short('!')
+        .long("not")
+        .help(tr("unpin the task"))
+        .flag(false, true);
+    let index = parse_index();
+
+    construct!(Command::Pin(pinned, index))
+        .to_options()
+        .descr(tr("pin a task, so it is always listed first"))
+}
+
+fn hide_command() -> OptionParser<Command> {
+    let hidden = short('!')
+        .long("not")
+        .help(tr("list the task again"))
+        .flag(false, true);
+    let index = parse_index();
+
+    construct!(Command::Hide(hidden, index))
+        .to_options()
+        .descr(tr("hide a task from the list, for someday/maybe items"))
+        .footer(tr("show --hidden lists hidden tasks anyway"))
+}
+
+fn depend_command() -> OptionParser<Command> {
+    let depends = short('!')
+        .long("not")
+        .help(tr("remove the dependency"))
+        .flag(false, true);
+    let on = long("on")
+        .help(tr(
+            "the task that has to be done first, project:id for a task of another project",
+        ))
+        .argument::<String>("index")
+        .complete(complete_index)
+        .parse::<_, _, String>(|x| {
+            if tutel::is_reference(&x) {
+                Ok(Dependency::External(x))
+            } else {
+                task_ref(&x).map(Dependency::Index)
+            }
+        });
+    let index = parse_index();
+
+    construct!(Command::Depend { depends, on, index })
+        .to_options()
+        .descr(tr("make a task depend on another one that has to be done first"))
+        .footer(tr("tasks of other projects are found by the project name, once tutel has changed that project"))
+}
+
+fn graph_command() -> OptionParser<Command> {
+    let dot = long("dot")
+        .help(tr("print the graph in Graphviz' DOT language (default)"))
+        .req_flag(GraphFormat::Dot);
+
+    let mermaid = long("mermaid")
+        .help(tr(
+            "print a mermaid flowchart, e.g. for markdown on GitHub or GitLab",
+        ))
+        .req_flag(GraphFormat::Mermaid);
+
+    let format = construct!([dot, mermaid]).fallback(GraphFormat::Dot);
+
+    construct!(Command::Graph(format))
+        .to_options()
+        .descr(tr(
+            "print the tasks and the dependencies between them as a graph",
+        ))
+        .footer(tr("e.g. tutel graph --dot | dot -Tsvg > tasks.svg"))
+}
+
+fn open_command() -> OptionParser<Command> {
+    let editor = editor();
+    let index = parse_index();
+
+    construct!(Command::Open { editor, index })
+        .to_options()
+        .descr(tr(
+            "open the urls in a task description with the default application",
+        ))
+        .footer(tr(
+            "a place in a file like src/main.rs:42, relative to the project, is opened \
+            in the editor at that line instead",
+        ))
+}
+
+fn alias_command() -> OptionParser<Command> {
+    let clear = long("clear")
+        .help(tr("remove the alias of the task"))
+        .switch();
+    let index = parse_index();
+    let alias = positional::<String>("alias").optional();
+
+    construct!(clear, index, alias)
+        .parse::<_, _, &str>(|(clear, index, alias)| match (clear, alias) {
+            (true, Some(_)) => Err(tr("either give an alias or --clear")),
+            (false, None) => Err(tr("an alias is required, or --clear to remove it")),
+            (_, alias) => Ok(Command::Alias { index, alias }),
+        })
+        .to_options()
+        .descr(tr(
+            "give a task a name that can be used wherever an index can, like tutel done deploy",
+        ))
+        .footer(tr(
+            "aliases are lowercase words starting with a letter, unique within the project",
+        ))
+}
+
+fn attach_command() -> OptionParser<Command> {
+    let remove = short('r')
+        .long("remove")
+        .help(tr("remove the attachment instead"))
+        .switch();
+    let index = parse_index();
+    let target = positional::<String>("path|url");
+
+    construct!(Command::Attach {
+        remove,
+        index,
+        target
+    })
+    .to_options()
+    .descr(tr("attach a file or url to a task, tutel open opens it"))
+    .footer(tr("files inside of the project are stored relative to it"))
+}
+
+/// Overrides a strict limit of tasks in progress
+fn exceed_wip_limit() -> impl Parser<bool> {
+    short('f')
+        .long("force")
+        .help(tr("even if that exceeds the limit of tasks in progress"))
+        .switch()
+}
+
+fn start_command() -> OptionParser<Command> {
+    let force = exceed_wip_limit();
+    let index = parse_index();
+
+    construct!(Command::Start { force, index })
+        .to_options()
+        .descr(tr("start tracking the time spent on a task"))
+        .footer(tr(
+            "only one task is active at a time, starting a task stops the active one",
+        ))
+}
+
+fn stop_command() -> OptionParser<Command> {
+    bpaf::pure(Command::Stop)
+        .to_options()
+        .descr(tr("stop tracking time for the active task"))
+}
+
+fn snooze_command() -> OptionParser<Command> {
+    let until = long("until")
+        .help(tr(
+            "when to show the task again: tomorrow, friday, 2024-03-01 or 3d(from now)",
+        ))
+        .argument::<String>("date")
+        .parse::<_, _, String>(|s| {
+            tutel::parse_until(&s, tutel::now()).ok_or_else(|| tr!("invalid date: {s}", s))
+        });
+    let index = parse_index();
+
+    construct!(Command::Snooze { until, index })
+        .to_options()
+        .descr(tr("hide a task from the list until the given date"))
+        .footer(tr("snooze a task until today to show it again right away"))
+}
+
+fn wait_command() -> OptionParser<Command> {
+    let on = long("on")
+        .help(tr("what the task is waiting on, e.g. \"reply from Bob\""))
+        .argument::<String>("reason")
+        .map(Some);
+    let clear = long("clear")
+        .help(tr("the task isn't waiting anymore"))
+        .req_flag(None);
+    let on = construct!([on, clear]).fallback(Some(String::new()));
+    let index = parse_index();
+
+    construct!(Command::Wait { on, index })
+        .to_options()
+        .descr(tr(
+            "mark a task as waiting on something else, like someone it was delegated to",
+        ))
+        .footer(tr(
+            "waiting tasks are not urgent, show --waiting lists all of them",
+        ))
+}
+
+fn pomodoro_command() -> OptionParser<Command> {
+    let duration = |name: &'static str, help: &'static str| {
+        long(name)
+            .help(tr(help))
+            .argument::<String>("duration")
+            .parse::<_, _, String>(|s| {
+                tutel::parse_duration(&s).ok_or_else(|| tr!("invalid duration: {s}", s))
+            })
+            .optional()
+    };
+
+    let work = duration("length", "how long to work, e.g. 50m (default: 25m)");
+    let rest = duration(
+        "break",
+        "how long to rest afterwards, 0m to skip (default: 5m)",
+    );
+    let index = parse_index();
+
+    construct!(Command::Pomodoro { work, rest, index })
+        .to_options()
+        .descr(tr(
+            "work on a task until the timer runs out, then take a break",
+        ))
+        .footer(tr(
+            "finished pomodoros are tracked like with start and stop, interrupting one discards it",
+        ))
+}
+
+fn timesheet_command() -> OptionParser<Command> {
+    let since = long("since")
+        .help(tr(
+            "only count time after this date: today, yesterday, 2024-03-01 or 2w(ago)",
+        ))
+        .argument::<String>("date")
+        .parse::<_, _, String>(|s| {
+            tutel::parse_date(&s, tutel::now()).ok_or_else(|| tr!("invalid date: {s}", s))
+        })
+        .optional();
+
+    let by_task = long("by-task")
+        .help(tr("sum up per task instead of per day"))
+        .req_flag(TimesheetBy::Task);
+    let by_tag = long("by-tag")
+        .help(tr("sum up per #tag instead of per day"))
+        .req_flag(TimesheetBy::Tag);
+    let by = construct!([by_task, by_tag]).fallback(TimesheetBy::Day);
+
+    let csv = long("csv")
+        .help(tr(
+            "print one comma separated line per row, e.g. for invoicing",
+        ))
+        .switch();
+
+    let query = query("only count the tasks matching the query").optional();
+
+    construct!(Command::Timesheet {
+        since,
+        by,
+        csv,
+        query
+    })
+    .to_options()
+    .descr(tr("summarize the time tracked with start and stop"))
+}
+
+fn yank_command() -> OptionParser<Command> {
+    let markdown = short('m')
+        .long("markdown")
+        .help(tr("copy the task as a markdown checklist item"))
+        .switch();
+
+    let osc52 = long("osc52")
+        .help(tr("always let the terminal copy the text using OSC 52"))
+        .switch();
+
+    let index = parse_index();
+
+    construct!(Command::Yank {
+        markdown,
+        osc52,
+        index
+    })
+    .to_options()
+    .descr(tr("copy the description of a task to the system clipboard"))
+}
+
+fn prompt_command() -> OptionParser<Command> {
+    let shell = long("shell")
+        .help(tr(
+            "color the output, using the zero width markers of bash or zsh",
+        ))
+        .argument::<String>("shell")
+        .complete(|_| vec![("bash", None), ("zsh", None)])
+        .parse::<_, _, String>(|s| match s.as_str() {
+            "bash" => Ok(PromptShell::Bash),
+            "zsh" => Ok(PromptShell::Zsh),
+            _ => Err(tr!("unsupported shell: {s}", s)),
+        })
+        .optional();
+
+    construct!(Command::Prompt(shell))
+        .to_options()
+        .descr(tr(
+            "print a compact summary of the todo list, meant to be embedded into PS1",
+        ))
+        .footer(tr("prints nothing if there is no project"))
+}
+
+fn hook_command() -> OptionParser<Command> {
+    let shell = positional::<String>("shell")
+        .help(tr("bash, zsh or fish"))
+        .complete(|_| {
+            crate::hook::SHELLS
+                .iter()
+                .map(|shell| (*shell, None))
+                .collect()
+        })
+        .map(Command::Hook);
+
+    let summary = long("summary")
+        .help(tr(
+            "print the summary the hook shows, used by the hook itself",
+        ))
+        .req_flag(());
+    let previous = long("previous")
+        .help(tr("the directory the shell was in before, nothing is printed if it is in the same project"))
+        .argument::<PathBuf>("dir")
+        .optional();
+    let summary =
+        construct!(summary, previous).map(|((), previous)| Command::HookSummary(previous));
+
+    construct!([summary, shell])
+        .to_options()
+        .descr(tr(
+            "print a snippet that shows the pending tasks whenever you cd into a project",
+        ))
+        .footer(tr("bash: eval \"$(tutel hook bash)\"\n\
+            zsh: eval \"$(tutel hook zsh)\"\n\
+            fish: tutel hook fish | source"))
+}
+
+fn guard_command() -> OptionParser<Command> {
+    let tags =
+        tag("only tasks with #tag in their description count, can be given several times").many();
+
+    construct!(Command::Guard(tags))
+        .to_options()
+        .descr(tr(
+            "exit with 1 and list the tasks if any of them is still open",
+        ))
+        .footer(tr("meant for git hooks, e.g. .git/hooks/pre-push:\n\
+            #!/bin/sh\n\
+            exec tutel guard --tag release-blocker"))
+}
+
+fn status_command() -> OptionParser<Command> {
+    let starship = long("starship")
+        .help(tr("print output for a starship custom module"))
+        .req_flag(StatusFormat::Starship);
+
+    let tmux = long("tmux")
+        .help(tr(
+            "print output for tmux's status-right, colored with tmux markup",
+        ))
+        .req_flag(StatusFormat::Tmux);
+
+    let format = construct!([starship, tmux]).fallback(StatusFormat::Plain);
+
+    let max_age = long("max-age")
+        .help(tr(
+            "reuse tmux output computed less than SECS seconds ago, 0 disables the cache",
+        ))
+        .argument::<u64>("SECS")
+        .fallback(10);
+
+    construct!(Command::Status { format, max_age })
+        .to_options()
+        .descr(tr(
+            "print a summary of the todo list in a format for status bars",
+        ))
+        .footer(tr("prints nothing and exits with 1 if there is no project"))
 }
 
 fn print_completions_command() -> OptionParser<Command> {
-    let shell = positional::<String>("shell");
+    let install = long("install")
+        .help(tr(
+            "write the script to where the shell looks for completions",
+        ))
+        .switch();
+
+    let shell = positional::<String>("shell")
+        .complete(|_| {
+            crate::completions::SHELLS
+                .iter()
+                .map(|shell| (*shell, None))
+                .collect()
+        })
+        .optional();
+
+    construct!(Command::PrintCompletion { install, shell })
+        .to_options()
+        .descr(tr("print shell completions for the given shell"))
+        .footer(tr("with --install the shell defaults to the one in $SHELL"))
+}
+
+fn man_command() -> OptionParser<Command> {
+    let output = long("output")
+        .help(tr(
+            "write tutel.1 and a page for every subcommand(tutel-add.1, ...) into this directory",
+        ))
+        .argument::<PathBuf>("dir")
+        .optional();
+
+    let command = positional::<String>("command").optional();
 
-    construct!(Command::PrintCompletion(shell))
+    construct!(Command::Man { output, command })
         .to_options()
-        .descr("print shell completions for the given shell")
+        .descr(tr("print the man page of tutel or one of its subcommands"))
 }
@@ -0,0 +1,159 @@
+use std::fmt::Display;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{date, tr, DisplayOptions, Project, Status, Task};
+
+/// Renders the tasks of a project side by side in one column per status,
+/// like a kanban board
+pub struct Board<'a> {
+    project: &'a Project,
+    options: &'a DisplayOptions,
+}
+
+impl Project {
+    /// Returns a wrapper that renders this project as a board. Descriptions
+    /// are cut off to fit the columns into `options.width`.
+    pub const fn board<'a>(&'a self, options: &'a DisplayOptions) -> Board<'a> {
+        Board {
+            project: self,
+            options,
+        }
+    }
+}
+
+/// Used if the width of the terminal is unknown
+const DEFAULT_WIDTH: usize = 80;
+
+impl Display for Board<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let options = self.options;
+        let theme = &options.theme;
+        let now = date::now();
+
+        // only statuses some task has get a column
+        let columns: Vec<_> = Status::ALL
+            .iter()
+            .map(|status| {
+                let mut tasks: Vec<&Task> = self
+                    .project
+                    .data
+                    .tasks
+                    .iter()
+                    .filter(|t| t.status == *status)
+                    .filter(|t| options.lists(t, now))
+                    .collect();
+                tasks.sort_by_key(|t| !t.pinned);
+                (*status, tasks)
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect();
+
+        write!(f, "{}", theme.name.paint(&self.project.data.name))?;
+        if columns.is_empty() {
+            return write!(f, "\n{}", tr("[empty]"));
+        }
+
+        // columns side by side don't work with a screen reader, one after
+        // the other do
+        if options.plain {
+            for (status, tasks) in &columns {
+                write!(f, "\n{}: {}", status.label(), tasks.len())?;
+                for task in tasks {
+                    write!(f, "\n{} {}: {}", tr("task"), task.index, task.desc)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let width = options.width.unwrap_or(DEFAULT_WIDTH);
+        let gaps = (columns.len() - 1) * (options.separator().width() + 2);
+        let column_width = (width.saturating_sub(gaps) / columns.len()).max(8);
+        let ellipsis = if options.ascii { "~" } else { "…" };
+
+        // every cell is kept along with its width, the styles would skew it
+        let mut lines = vec![Vec::new(), Vec::new()];
+        let rule = if options.ascii { "-" } else { "─" }.repeat(column_width);
+        for (status, tasks) in &columns {
+            let header = format!("{} ({})", status.label(), tasks.len());
+            let header = truncate(&header, column_width, ellipsis);
+            let style = options.style_of(*status);
+            lines[0].push((header.width(), style.paint(&header).to_string()));
+            lines[1].push((column_width, theme.separator.paint(&rule).to_string()));
+        }
+
+        let rows = columns
+            .iter()
+            .map(|(_, tasks)| tasks.len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..rows {
+            let cells = columns
+                .iter()
+                .map(|(_, tasks)| {
+                    let Some(task) = tasks.get(row) else {
+                        return (0, String::new());
+                    };
+                    let index = format!("{:03} ", task.index);
+                    let desc = truncate(&task.desc, column_width - index.width(), ellipsis);
+                    let index_style = if task.is_active() {
+                        &theme.active
+                    } else {
+                        &theme.index
+                    };
+                    let cell = format!(
+                        "{}{}",
+                        index_style.paint(&index),
+                        options.description(&desc, task)
+                    );
+                    (index.width() + desc.width(), cell)
+                })
+                .collect();
+            lines.push(cells);
+        }
+
+        let separator = theme.separator.paint(options.separator());
+        for line in lines {
+            let last = line.len() - 1;
+            let mut text = String::new();
+            for (i, (cell_width, cell)) in line.into_iter().enumerate() {
+                text.push_str(&cell);
+                if i < last {
+                    let padding = column_width.saturating_sub(cell_width);
+                    text.push_str(&format!("{:padding$} {separator} ", ""));
+                }
+            }
+            // columns that ran out of tasks leave trailing whitespace
+            write!(f, "\n{}", text.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Cuts text off so it is at most `width` columns wide, marking the cut
+/// with the ellipsis
+fn truncate(text: &str, width: usize, ellipsis: &str) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut truncated_width = ellipsis.width();
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if truncated_width + char_width > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += char_width;
+    }
+    truncated.push_str(ellipsis);
+    truncated
+}
+
+#[test]
+fn truncate_to_width() {
+    assert_eq!(truncate("short", 10, "…"), "short");
+    assert_eq!(truncate("a longer description", 10, "…"), "a longer …");
+    assert_eq!(truncate("日本語のタスク", 7, "~"), "日本語~");
+}
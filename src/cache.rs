@@ -0,0 +1,93 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// `$XDG_CACHE_HOME/tutel`, falling back to `~/.cache/tutel` and
+/// `%LOCALAPPDATA%\tutel` on Windows
+fn cache_dir() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+
+    Some(dir.join("tutel"))
+}
+
+fn cache_file(namespace: &str, key: &str) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    Some(cache_dir()?.join(format!("{namespace}-{:016x}", hasher.finish())))
+}
+
+/// Returns the value cached for the key if it is younger than `max_age`,
+/// otherwise computes and caches it. `None` (e.g. "there is no project")
+/// is cached as well. Failing to read or write the cache is not an error,
+/// the value is just computed every time then.
+pub fn cached(
+    namespace: &str,
+    key: &str,
+    max_age: Duration,
+    compute: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    let Some(file) = cache_file(namespace, key) else {
+        return compute();
+    };
+
+    let fresh = modified(&file)
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < max_age);
+
+    reuse_or_compute(&file, fresh, compute)
+}
+
+/// Like [`cached`], but the value stays fresh until `source` changes
+pub fn cached_while_unchanged(
+    namespace: &str,
+    source: &Path,
+    compute: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    let Some(file) = cache_file(namespace, &source.to_string_lossy()) else {
+        return compute();
+    };
+
+    let fresh = match (modified(&file), modified(source)) {
+        (Some(cached), Some(changed)) => cached > changed,
+        _ => false,
+    };
+
+    reuse_or_compute(&file, fresh, compute)
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+fn reuse_or_compute(
+    file: &Path,
+    fresh: bool,
+    compute: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    if fresh {
+        if let Ok(content) = fs::read_to_string(file) {
+            // the first character tells whether there is a value at all
+            return content.strip_prefix('+').map(str::to_string);
+        }
+    }
+
+    let value = compute();
+
+    let content = value
+        .as_ref()
+        .map_or_else(|| "-".to_string(), |value| format!("+{value}"));
+    if let Some(dir) = file.parent() {
+        let _ = fs::create_dir_all(dir).and_then(|_| fs::write(file, content));
+    }
+
+    value
+}
@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::data::ProjectData;
+use crate::Task;
+
+/// A task list kept as a markdown checklist, like GitHub renders it
+pub const CHECKLIST_FILE_NAME: &str = "TODO.md";
+
+/// Written into the checklists tutel creates. A `TODO.md` without it is
+/// somebody's own file and not a project.
+pub const MARKER: &str = "<!-- tutel -->";
+
+/// Whether the checklist at `path` is a project, that is tutel created it
+pub fn is_project(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|text| text.lines().any(|line| line.trim() == MARKER))
+}
+
+/// A `- [ ] description` line, split into its parts
+struct Item<'a> {
+    /// Indentation and bullet, up to the box
+    prefix: &'a str,
+    done: bool,
+    desc: &'a str,
+}
+
+fn item(line: &str) -> Option<Item<'_>> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let rest = trimmed.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let desc = &rest[3..];
+    let desc = desc.strip_prefix(' ').unwrap_or(desc);
+    Some(Item {
+        prefix: &line[..indent + 2],
+        done,
+        desc: desc.trim_end(),
+    })
+}
+
+/// Reads the items of the checklist as tasks, numbered in order. The first
+/// `# heading` names the project, `default_name` is used without one. The
+/// tasks are left without ids, see [`ProjectData::assign_ids`].
+pub fn parse(text: &str, default_name: &str) -> ProjectData {
+    let name = text
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .unwrap_or(default_name)
+        .trim()
+        .to_string();
+
+    let tasks = text
+        .lines()
+        .filter_map(item)
+        .enumerate()
+        // the id is derived from the position and text when the project is
+        // loaded, so it stays the same until the item changes
+        .map(|(index, item)| Task {
+            id: String::new(),
+            created: None,
+            ..Task::new(item.desc, item.done, index)
+        })
+        .collect();
+
+    ProjectData {
+        name,
+        tasks,
+        lists: BTreeMap::new(),
+    }
+}
+
+/// Writes the tasks back into the checklist they were read from. The item
+/// with position N is the task with index N, items without a task are
+/// dropped and new tasks follow the last item. Everything else stays as it
+/// is. Only done or not survives, other statuses and details are lost.
+pub fn render(original: &str, data: &ProjectData) -> String {
+    let line = |prefix: &str, task: &Task| {
+        let mark = if task.is_completed() { 'x' } else { ' ' };
+        format!("{prefix}[{mark}] {}", task.desc)
+    };
+
+    let mut out = Vec::new();
+    let mut position = 0;
+    let mut last_item = None;
+    for text in original.lines() {
+        let Some(item) = item(text) else {
+            out.push(text.to_string());
+            continue;
+        };
+        if let Some(task) = data.tasks.iter().find(|t| t.index == position) {
+            out.push(line(item.prefix, task));
+            last_item = Some(out.len());
+        }
+        position += 1;
+    }
+
+    let new = data
+        .tasks
+        .iter()
+        .filter(|t| t.index >= position)
+        .map(|task| line("- ", task));
+    match last_item {
+        Some(at) => {
+            let new: Vec<_> = new.collect();
+            out.splice(at..at, new);
+        }
+        None if original.trim().is_empty() => {
+            out = vec![
+                format!("# {}", data.name),
+                MARKER.to_string(),
+                String::new(),
+            ];
+            out.extend(new);
+        }
+        None => {
+            if out.last().is_some_and(|line| !line.trim().is_empty()) {
+                out.push(String::new());
+            }
+            out.extend(new);
+        }
+    }
+
+    let mut text = out.join("\n");
+    text.push('\n');
+    text
+}
+
+#[test]
+fn checklist_round_trip() {
+    let original = "# Release\n\nBefore tagging:\n\n- [ ] bump the version\n  * [x] write the changelog\n- [ ] tag it\n\nThat's it.\n";
+    let mut data = parse(original, "dir");
+    assert_eq!(data.name, "Release");
+    assert_eq!(data.tasks.len(), 3);
+    assert!(data.tasks[1].is_completed());
+    assert_eq!(render(original, &data), original);
+
+    // every load gives the items the same ids
+    data.assign_ids();
+    let mut again = parse(original, "dir");
+    again.assign_ids();
+    let ids = |data: &ProjectData| data.tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&data), ids(&again));
+
+    data.tasks[0].set_status(crate::Status::Done);
+    data.tasks.retain(|t| t.index != 2);
+    data.tasks.push(Task::new("publish", false, 3));
+    assert_eq!(
+        render(original, &data),
+        "# Release\n\nBefore tagging:\n\n- [x] bump the version\n  * [x] write the changelog\n- [ ] publish\n\nThat's it.\n"
+    );
+    // a new checklist is marked as a project
+    assert_eq!(
+        render("", &data),
+        "# Release\n<!-- tutel -->\n\n- [x] bump the version\n- [x] write the changelog\n- [ ] publish\n"
+    );
+}
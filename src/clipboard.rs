@@ -0,0 +1,100 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    process::{Command, Stdio},
+};
+use tutel::tr;
+
+/// Clipboard tools that read the content to copy from stdin
+const TOOLS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["pbcopy"],
+    &["clip.exe"],
+];
+
+/// Copies the text to the system clipboard. Uses the first available
+/// clipboard tool, falling back to the OSC 52 escape sequence which asks the
+/// terminal to do it. Over ssh OSC 52 is always used, a tool would only
+/// copy to the clipboard of the remote machine.
+pub fn copy(text: &str, force_osc52: bool) -> Result<()> {
+    let remote = std::env::var_os("SSH_TTY").is_some();
+
+    if !force_osc52 && !remote {
+        for tool in TOOLS {
+            if copy_with(tool, text).is_ok() {
+                return Ok(());
+            }
+        }
+    }
+
+    copy_osc52(text)
+}
+
+fn copy_with(tool: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(tool[0])
+        .args(&tool[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    if !child.wait()?.success() {
+        bail!(tr!("{tool} failed", tool = tool[0]));
+    }
+
+    Ok(())
+}
+
+fn copy_osc52(text: &str) -> Result<()> {
+    let mut sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+
+    // tmux only passes the sequence on to the terminal when asked to
+    if std::env::var_os("TMUX").is_some() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+
+    let mut tty = OpenOptions::new().write(true).open("/dev/tty").context(tr(
+        "no clipboard tool found and no terminal to send OSC 52 to",
+    ))?;
+    tty.write_all(sequence.as_bytes())?;
+
+    Ok(())
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+#[test]
+fn base64_padding() {
+    assert_eq!(base64(b"tutel"), "dHV0ZWw=");
+    assert_eq!(base64(b"tu"), "dHU=");
+    assert_eq!(base64(b"tut"), "dHV0");
+}
@@ -0,0 +1,145 @@
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path, str::FromStr};
+
+use crate::tr;
+
+/// What marks a comment as something left to do
+const MARKERS: &[&str] = &["TODO", "FIXME"];
+
+/// How comments start in most languages. `<!--` comes before `--`, the
+/// longest match wins.
+const OPENERS: &[&str] = &["<!--", "//", "/*", "--", "#", ";"];
+
+/// What happens to the comment of a task once it is done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentAction {
+    /// Removes the comment, and the line with it if nothing else is on it
+    Delete,
+    /// Replaces `TODO` or `FIXME` with `DONE`
+    Annotate,
+}
+
+impl FromStr for CommentAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delete" => Ok(Self::Delete),
+            "annotate" => Ok(Self::Annotate),
+            _ => bail!(tr!("expected delete or annotate, got {s}", s)),
+        }
+    }
+}
+
+/// Deletes or annotates the TODO comment in the line(counted from 1) of the
+/// file. Returns false and leaves the file alone if there is no such
+/// comment in that line, e.g. because the code moved since.
+///
+/// # Errors
+/// This function will return an error if the file can't be read or written.
+pub fn resolve_comment(file: &Path, line: usize, action: CommentAction) -> Result<bool> {
+    let content = fs::read_to_string(file)
+        .with_context(|| tr!("unable to read {file}", file = file.display()))?;
+
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let Some(text) = line.checked_sub(1).and_then(|n| lines.get(n).copied()) else {
+        return Ok(false);
+    };
+    let Some(resolved) = resolve_line(text, action) else {
+        return Ok(false);
+    };
+
+    let resolved = resolved.as_deref();
+    match resolved {
+        Some(resolved) => lines[line - 1] = resolved,
+        None => {
+            lines.remove(line - 1);
+        }
+    }
+    fs::write(file, lines.concat())
+        .with_context(|| tr!("unable to write {file}", file = file.display()))?;
+    Ok(true)
+}
+
+/// The line with the comment resolved, `Some(None)` if it goes away
+/// completely and `None` if it has no TODO comment
+fn resolve_line(text: &str, action: CommentAction) -> Option<Option<String>> {
+    let (marker_at, marker) = MARKERS
+        .iter()
+        .filter_map(|m| Some((text.find(m)?, *m)))
+        .min()?;
+    let opener_at = last_opener(&text[..marker_at])?;
+
+    Some(match action {
+        CommentAction::Annotate => Some(format!(
+            "{}DONE{}",
+            &text[..marker_at],
+            &text[marker_at + marker.len()..]
+        )),
+        CommentAction::Delete => {
+            let code = text[..opener_at].trim_end();
+            if code.is_empty() {
+                None
+            } else {
+                let ending = &text[text.trim_end_matches(['\r', '\n']).len()..];
+                Some(format!("{code}{ending}"))
+            }
+        }
+    })
+}
+
+/// Where the last comment opener in the text is. Openers within quotes, like
+/// the `#` of `"#fff"`, are part of a string and don't count.
+fn last_opener(text: &str) -> Option<usize> {
+    let mut found = None;
+    let mut quote = None;
+    let mut escaped = false;
+    // the rest of an opener, the `--` of `<!--` doesn't start another one
+    let mut skip = 0;
+    for (i, c) in text.char_indices() {
+        match quote {
+            _ if i < skip => {}
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None => {
+                if let Some(opener) = OPENERS.iter().find(|o| text[i..].starts_with(*o)) {
+                    found = Some(i);
+                    skip = i + opener.len();
+                }
+            }
+        }
+    }
+    found
+}
+
+#[test]
+fn resolve_todo_lines() {
+    use CommentAction::*;
+    assert_eq!(resolve_line("    // TODO: fix\n", Delete), Some(None));
+    assert_eq!(
+        resolve_line("x = 1 # FIXME later\r\n", Delete),
+        Some(Some("x = 1\r\n".to_string()))
+    );
+    assert_eq!(
+        resolve_line("    // TODO: fix\n", Annotate),
+        Some(Some("    // DONE: fix\n".to_string()))
+    );
+    assert_eq!(resolve_line("let todo = TODO;\n", Delete), None);
+    assert_eq!(resolve_line("// nothing to do\n", Delete), None);
+    assert_eq!(
+        resolve_line("color = \"#fff\" # TODO: don't hardcode\n", Delete),
+        Some(Some("color = \"#fff\"\n".to_string()))
+    );
+    assert_eq!(
+        resolve_line("s = 'a--b\\'--' -- FIXME\n", Delete),
+        Some(Some("s = 'a--b\\'--'\n".to_string()))
+    );
+    assert_eq!(resolve_line("s = \"// TODO\"\n", Delete), None);
+    assert_eq!(
+        resolve_line("<p>x</p> <!-- TODO: y -->\n", Delete),
+        Some(Some("<p>x</p>\n".to_string()))
+    );
+}
@@ -0,0 +1,126 @@
+use anyhow::{bail, Context, Result};
+use std::{fs, path::PathBuf};
+use tutel::tr;
+
+const BASH_COMPLETIONS: &str = include_str!("../res/tutel-completions.bash");
+const ZSH_COMPLETIONS: &str = include_str!("../res/tutel-completions.zsh");
+const FISH_COMPLETIONS: &str = include_str!("../res/tutel-completions.fish");
+const ELVISH_COMPLETIONS: &str = include_str!("../res/tutel-completions.elv");
+const NUSHELL_COMPLETIONS: &str = include_str!("../res/tutel-completions.nu");
+const POWERSHELL_COMPLETIONS: &str = include_str!("../res/tutel-completions.ps1");
+
+/// The shells completion scripts exist for
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "elvish", "nushell", "powershell"];
+
+/// Returns the completion script for the given shell
+pub fn script(shell: &str) -> Result<&'static str> {
+    Ok(match shell {
+        "bash" => BASH_COMPLETIONS,
+        "zsh" => ZSH_COMPLETIONS,
+        "fish" => FISH_COMPLETIONS,
+        "elvish" => ELVISH_COMPLETIONS,
+        "nushell" => NUSHELL_COMPLETIONS,
+        "powershell" => POWERSHELL_COMPLETIONS,
+        _ => bail!(tr!("no such shell: {shell}", shell)),
+    })
+}
+
+/// Guesses the shell of the user from `$SHELL`. Windows doesn't set it,
+/// PowerShell is assumed there.
+pub fn detect_shell() -> Result<String> {
+    let shell = match std::env::var_os("SHELL") {
+        Some(shell) => shell,
+        None if cfg!(windows) => return Ok("powershell".to_string()),
+        None => bail!(tr("no shell given and $SHELL is not set")),
+    };
+    let name = match PathBuf::from(shell).file_name() {
+        Some(name) if name == "nu" => "nushell".to_string(),
+        Some(name) if name == "pwsh" => "powershell".to_string(),
+        Some(name) => name.to_string_lossy().to_string(),
+        None => String::new(),
+    };
+
+    if !SHELLS.contains(&name.as_str()) {
+        bail!(tr!(
+            "unsupported shell {name}, pass one of {shells}",
+            name = format!("{name:?}"),
+            shells = SHELLS.join(", ")
+        ));
+    }
+
+    Ok(name)
+}
+
+/// Writes the completion script to the place the shell loads completions
+/// from, replacing an older version. Returns where it was written and what
+/// else the user has to do, if anything.
+pub fn install(shell: &str) -> Result<(PathBuf, Option<&'static str>)> {
+    let script = script(shell)?;
+
+    let (path, hint) = match shell {
+        "bash" => (data_dir()?.join("bash-completion/completions/tutel"), None),
+        // zsh has no per user completion directory
+        "zsh" => (
+            zdot_dir()?.join(".zfunc/_tutel"),
+            Some("add `fpath=(~/.zfunc $fpath)` before `compinit` in your .zshrc"),
+        ),
+        "fish" => (config_dir()?.join("fish/completions/tutel.fish"), None),
+        "elvish" => (
+            config_dir()?.join("elvish/lib/tutel.elv"),
+            Some("add `use tutel` to your rc.elv"),
+        ),
+        "nushell" => (
+            config_dir()?.join("nushell/autoload/tutel.nu"),
+            Some("nushell before 0.101 doesn't autoload it, `source` it in your config.nu"),
+        ),
+        // the profile is a script of the user, it can't just be replaced
+        "powershell" => (
+            powershell_dir()?.join("tutel-completions.ps1"),
+            Some("dot source the script in your $PROFILE"),
+        ),
+        _ => bail!(tr!("no such shell: {shell}", shell)),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| tr!("couldn't create {dir}", dir = dir.display()))?;
+    }
+    fs::write(&path, script)
+        .with_context(|| tr!("couldn't write {path}", path = path.display()))?;
+
+    Ok((path, hint))
+}
+
+fn home_dir() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .context(tr("$HOME is not set"))
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf> {
+    match std::env::var_os(var).filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(home_dir()?.join(fallback)),
+    }
+}
+
+fn data_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+fn config_dir() -> Result<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+fn zdot_dir() -> Result<PathBuf> {
+    xdg_dir("ZDOTDIR", "")
+}
+
+fn powershell_dir() -> Result<PathBuf> {
+    if cfg!(windows) {
+        Ok(home_dir()?.join("Documents").join("PowerShell"))
+    } else {
+        Ok(config_dir()?.join("powershell"))
+    }
+}
@@ -0,0 +1,567 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    date::{self, DateFormat},
+    display::Symbols,
+    format::Format,
+    rules::Rule,
+    template::Template,
+    theme::Theme,
+    tr,
+    urgency::Urgency,
+    view::View,
+};
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Whether urls in descriptions are rendered as clickable hyperlinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hyperlinks {
+    /// Only on terminals known to support them
+    Auto,
+    Always,
+    Never,
+}
+
+/// User configuration. Everything in here has a sensible default, so
+/// a missing config file is not an error.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub theme: Theme,
+    pub symbols: Symbols,
+    /// Only use ascii characters in the output
+    pub ascii: bool,
+    /// Output for screen readers
+    pub plain: bool,
+    /// Output for scripts, only ever set from the command line so a config
+    /// file can't break them
+    pub porcelain: bool,
+    /// The project is a temporary copy of one on another machine, only ever
+    /// set from the command line. Nothing should remember the copy's path.
+    pub remote: bool,
+    /// Page the list if it doesn't fit on the screen
+    pub pager: bool,
+    pub hyperlinks: Hyperlinks,
+    pub urgency: Urgency,
+    /// Coloring rules for task descriptions
+    pub rules: Vec<Rule>,
+    pub pomodoro: Pomodoro,
+    pub wip: WipLimit,
+    /// Open tasks older than this many seconds are marked as stale
+    pub stale: Option<u64>,
+    /// Completed tasks are archived this many seconds after completion
+    pub archive_after: Option<u64>,
+    pub templates: Vec<Template>,
+    /// Named `show` options, used as `tutel show <name>`
+    pub views: Vec<View>,
+    /// How dates are shown
+    pub date_format: DateFormat,
+    /// Expand shortcodes like `:rocket:` in descriptions
+    pub emoji: bool,
+    /// Render light markdown in descriptions
+    pub markdown: bool,
+    /// How new projects are stored
+    pub format: Format,
+    pub discovery: Discovery,
+    /// How many changes can be undone
+    pub history: usize,
+    /// Keep an index of the archive for `search`
+    pub search_index: bool,
+}
+
+/// How projects are searched for
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    /// Directories matching these patterns are never searched, on top of
+    /// what `.gitignore` and `.tutelignore` files say
+    pub ignore: Vec<String>,
+    /// Where the search for the project file upwards ends
+    pub stop_at: Boundary,
+    /// How many parent directories are searched for the project file at most
+    pub max_depth: Option<usize>,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            ignore: vec!["node_modules".to_string(), "target".to_string()],
+            stop_at: Boundary::None,
+            max_depth: None,
+        }
+    }
+}
+
+/// The last directory searched for a project file when walking upwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Search up to the root of the filesystem
+    None,
+    /// The root of the git repository, if inside of one
+    Git,
+    /// The home directory, if inside of it
+    Home,
+}
+
+impl Boundary {
+    /// Whether the search ends with this directory
+    pub fn is_reached(self, dir: &Path) -> bool {
+        match self {
+            Self::None => false,
+            Self::Git => dir.join(".git").exists(),
+            Self::Home => std::env::var_os("HOME").is_some_and(|home| dir == Path::new(&home)),
+        }
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "git" => Ok(Self::Git),
+            "home" => Ok(Self::Home),
+            _ => bail!(tr!("expected git, home or none, got {s}", s)),
+        }
+    }
+}
+
+/// How many tasks may be in progress at the same time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WipLimit {
+    /// No limit if not set
+    pub limit: Option<usize>,
+    /// Refuse to exceed the limit instead of only warning about it
+    pub strict: bool,
+}
+
+/// Lengths of the pomodoro timer, in seconds
+#[derive(Debug, Clone, Copy)]
+pub struct Pomodoro {
+    pub work: u64,
+    /// The break following a pomodoro, none if zero
+    pub rest: u64,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self {
+            work: 25 * 60,
+            rest: 5 * 60,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            symbols: Symbols::default(),
+            ascii: false,
+            plain: false,
+            porcelain: false,
+            remote: false,
+            pager: true,
+            hyperlinks: Hyperlinks::Auto,
+            urgency: Urgency::default(),
+            rules: Rule::defaults(),
+            pomodoro: Pomodoro::default(),
+            wip: WipLimit::default(),
+            stale: Some(30 * 24 * 60 * 60),
+            archive_after: None,
+            templates: Vec::new(),
+            views: Vec::new(),
+            date_format: DateFormat::default(),
+            emoji: false,
+            markdown: true,
+            format: Format::default(),
+            discovery: Discovery::default(),
+            history: 20,
+            search_index: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file if there is one, otherwise returns the default config.
+    ///
+    /// # Errors
+    /// This function will return an error if the config file exists but
+    /// cannot be read or is invalid.
+    pub fn load() -> Result<Self> {
+        match config_path() {
+            Some(path) if path.is_file() => {
+                let content =
+                    fs::read_to_string(&path).context(tr("unable to read config file"))?;
+                Self::parse(&content).with_context(|| {
+                    tr!("invalid config file {path}", path = path.to_string_lossy())
+                })
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Parses the content of a config file.
+    ///
+    /// # Errors
+    /// This function will return an error if the content is not valid toml or
+    /// contains invalid values.
+    pub fn parse(content: &str) -> Result<Self> {
+        let table: toml::value::Table = toml::from_str(content).context(tr("invalid syntax"))?;
+        let mut config = Self::default();
+
+        if let Some(name) = table.get("theme") {
+            let name = match name.as_str() {
+                Some(name) => name,
+                None => bail!(tr("'theme' must be the name of a theme")),
+            };
+            config.theme = load_theme(name, &table)?;
+        }
+
+        if let Some(symbols) = table.get("symbols") {
+            match symbols.as_table() {
+                Some(symbols) => load_symbols(&mut config.symbols, symbols)?,
+                None => bail!(tr("'symbols' must be a table")),
+            }
+        }
+
+        if let Some(ascii) = get_bool(&table, "ascii")? {
+            config.ascii = ascii;
+        }
+
+        if let Some(plain) = get_bool(&table, "plain")? {
+            config.plain = plain;
+        }
+
+        if let Some(pager) = get_bool(&table, "pager")? {
+            config.pager = pager;
+        }
+
+        if let Some(emoji) = get_bool(&table, "emoji")? {
+            config.emoji = emoji;
+        }
+
+        if let Some(markdown) = get_bool(&table, "markdown")? {
+            config.markdown = markdown;
+        }
+
+        if let Some(search_index) = get_bool(&table, "search-index")? {
+            config.search_index = search_index;
+        }
+
+        if let Some(hyperlinks) = table.get("hyperlinks") {
+            config.hyperlinks = match hyperlinks.as_str() {
+                Some("auto") => Hyperlinks::Auto,
+                Some("always") => Hyperlinks::Always,
+                Some("never") => Hyperlinks::Never,
+                _ => bail!(tr("'hyperlinks' must be one of auto, always or never")),
+            };
+        }
+
+        if let Some(urgency) = table.get("urgency") {
+            match urgency.as_table() {
+                Some(urgency) => config
+                    .urgency
+                    .apply(urgency)
+                    .context(tr("invalid urgency settings"))?,
+                None => bail!(tr("'urgency' must be a table")),
+            }
+        }
+
+        if let Some(rules) = table.get("rules") {
+            match rules.as_array() {
+                Some(rules) => config.rules = load_rules(rules)?,
+                None => bail!(tr("'rules' must be an array of tables")),
+            }
+        }
+
+        if let Some(pomodoro) = table.get("pomodoro") {
+            match pomodoro.as_table() {
+                Some(pomodoro) => load_pomodoro(&mut config.pomodoro, pomodoro)?,
+                None => bail!(tr("'pomodoro' must be a table")),
+            }
+        }
+
+        if let Some(stale) = table.get("stale") {
+            config.stale = match stale {
+                toml::Value::Boolean(false) => None,
+                toml::Value::String(after) => Some(
+                    date::parse_duration(after)
+                        .context(tr("'stale' must be a duration like \"30d\" or false"))?,
+                ),
+                _ => bail!(tr("'stale' must be a duration like \"30d\" or false")),
+            };
+        }
+
+        if let Some(format) = table.get("date-format") {
+            match format.as_str() {
+                Some(format) => config.date_format = DateFormat::parse(format)?,
+                None => bail!(tr(
+                    "'date-format' must be iso, relative or a strftime format"
+                )),
+            }
+        }
+
+        if let Some(format) = table.get("format") {
+            match format.as_str() {
+                Some(format) => config.format = format.parse()?,
+                None => bail!(tr("'format' must be toml, yaml, markdown or binary")),
+            }
+        }
+
+        if let Some(archive) = table.get("archive") {
+            match archive.as_table() {
+                Some(archive) => config.archive_after = load_archive(archive)?,
+                None => bail!(tr("'archive' must be a table")),
+            }
+        }
+
+        if let Some(templates) = table.get("templates") {
+            match templates.as_table() {
+                Some(templates) => config.templates = load_templates(templates)?,
+                None => bail!(tr("'templates' must be a table")),
+            }
+        }
+
+        if let Some(views) = table.get("view") {
+            match views.as_table() {
+                Some(views) => config.views = load_views(views)?,
+                None => bail!(tr("'view' must be a table")),
+            }
+        }
+
+        if let Some(history) = table.get("history") {
+            config.history = history
+                .as_integer()
+                .and_then(|depth| usize::try_from(depth).ok())
+                .context(tr(
+                    "'history' must be the number of changes that can be undone",
+                ))?;
+        }
+
+        if let Some(discovery) = table.get("discovery") {
+            match discovery.as_table() {
+                Some(discovery) => load_discovery(&mut config.discovery, discovery)?,
+                None => bail!(tr("'discovery' must be a table")),
+            }
+        }
+
+        if let Some(wip) = table.get("wip") {
+            match wip.as_table() {
+                Some(wip) => load_wip_limit(&mut config.wip, wip)?,
+                None => bail!(tr("'wip' must be a table")),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn get_bool(table: &toml::value::Table, key: &str) -> Result<Option<bool>> {
+    match table.get(key) {
+        Some(toml::Value::Boolean(b)) => Ok(Some(*b)),
+        Some(_) => bail!(tr!("'{key}' must be true or false", key)),
+        None => Ok(None),
+    }
+}
+
+/// Looks up a theme by name. User defined themes in the `[themes]` table
+/// are based on the default theme and take precedence over builtin ones.
+fn load_theme(name: &str, config: &toml::value::Table) -> Result<Theme> {
+    let user_theme = config
+        .get("themes")
+        .and_then(toml::Value::as_table)
+        .and_then(|themes| themes.get(name));
+
+    match user_theme {
+        Some(toml::Value::Table(overrides)) => {
+            let mut theme = Theme::default();
+            theme
+                .apply(overrides)
+                .with_context(|| tr!("invalid theme '{name}'", name))?;
+            Ok(theme)
+        }
+        Some(_) => bail!(tr!("theme '{name}' must be a table", name)),
+        None => match Theme::builtin(name) {
+            Some(theme) => Ok(theme),
+            None => bail!(tr!("no such theme: {name}", name)),
+        },
+    }
+}
+
+fn load_rules(rules: &[toml::Value]) -> Result<Vec<Rule>> {
+    let mut loaded = Vec::with_capacity(rules.len());
+
+    for (i, rule) in rules.iter().enumerate() {
+        let get = |key| rule.get(key).and_then(toml::Value::as_str);
+        let (Some(when), Some(style)) = (get("when"), get("style")) else {
+            bail!(tr!(
+                "rule {number} needs a 'when' and a 'style'",
+                number = i + 1
+            ));
+        };
+        loaded.push(
+            Rule::parse(when, style)
+                .with_context(|| tr!("invalid rule {number}", number = i + 1))?,
+        );
+    }
+
+    Ok(loaded)
+}
+
+fn load_pomodoro(pomodoro: &mut Pomodoro, table: &toml::value::Table) -> Result<()> {
+    for (key, value) in table {
+        let length = value
+            .as_str()
+            .and_then(date::parse_duration)
+            .with_context(|| tr!("'{key}' must be a duration like \"25m\"", key))?;
+
+        match key.as_str() {
+            "work" => pomodoro.work = length,
+            "break" => pomodoro.rest = length,
+            _ => bail!(tr!("unknown pomodoro key: {key}", key)),
+        }
+    }
+
+    Ok(())
+}
+
+fn load_archive(table: &toml::value::Table) -> Result<Option<u64>> {
+    let mut after = None;
+    for (key, value) in table {
+        match key.as_str() {
+            "after" => {
+                after = value.as_str().and_then(date::parse_duration);
+                if after.is_none() {
+                    bail!(tr("'after' must be a duration like \"30d\""));
+                }
+            }
+            _ => bail!(tr!("unknown archive key: {key}", key)),
+        }
+    }
+
+    Ok(after)
+}
+
+fn load_templates(table: &toml::value::Table) -> Result<Vec<Template>> {
+    let mut templates = Vec::with_capacity(table.len());
+
+    for (name, tasks) in table {
+        let tasks = tasks
+            .as_array()
+            .and_then(|tasks| {
+                tasks
+                    .iter()
+                    .map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            })
+            .with_context(|| {
+                tr!(
+                    "template '{name}' must be a list of task descriptions",
+                    name
+                )
+            })?;
+        templates.push(Template {
+            name: name.clone(),
+            tasks,
+        });
+    }
+
+    Ok(templates)
+}
+
+fn load_views(table: &toml::value::Table) -> Result<Vec<View>> {
+    table
+        .iter()
+        .map(|(name, query)| {
+            let query = query
+                .as_str()
+                .with_context(|| tr!("view '{name}' must be a string like \"tag:work\"", name))?;
+            View::parse(name, query).with_context(|| tr!("invalid view '{name}'", name))
+        })
+        .collect()
+}
+
+fn load_wip_limit(wip: &mut WipLimit, table: &toml::value::Table) -> Result<()> {
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("limit", toml::Value::Integer(limit)) if *limit > 0 => {
+                wip.limit = usize::try_from(*limit).ok();
+            }
+            ("limit", _) => bail!(tr("'limit' must be a positive number")),
+            ("strict", toml::Value::Boolean(strict)) => wip.strict = *strict,
+            ("strict", _) => bail!(tr("'strict' must be true or false")),
+            _ => bail!(tr!("unknown wip key: {key}", key)),
+        }
+    }
+
+    Ok(())
+}
+
+fn load_discovery(discovery: &mut Discovery, table: &toml::value::Table) -> Result<()> {
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("ignore", toml::Value::Array(patterns)) => {
+                discovery.ignore = patterns
+                    .iter()
+                    .map(|p| p.as_str().map(str::to_string))
+                    .collect::<Option<_>>()
+                    .context(tr("'ignore' must be a list of patterns"))?;
+            }
+            ("ignore", _) => bail!(tr("'ignore' must be a list of patterns")),
+            ("stop-at", toml::Value::String(boundary)) => discovery.stop_at = boundary.parse()?,
+            ("stop-at", _) => bail!(tr("'stop-at' must be git, home or none")),
+            ("max-depth", toml::Value::Integer(depth)) if *depth >= 0 => {
+                discovery.max_depth = usize::try_from(*depth).ok();
+            }
+            ("max-depth", _) => bail!(tr("'max-depth' must be a number")),
+            _ => bail!(tr!("unknown discovery key: {key}", key)),
+        }
+    }
+
+    Ok(())
+}
+
+fn load_symbols(symbols: &mut Symbols, table: &toml::value::Table) -> Result<()> {
+    for (key, value) in table {
+        let symbol = match value.as_str() {
+            Some(symbol) => symbol.to_string(),
+            None => bail!(tr!("symbol '{key}' must be a string", key)),
+        };
+
+        match key.as_str() {
+            "done" => symbols.done = symbol,
+            "pending" => symbols.pending = symbol,
+            "in-progress" => symbols.in_progress = symbol,
+            "blocked" => symbols.blocked = symbol,
+            "cancelled" => symbols.cancelled = symbol,
+            "active" => symbols.active = symbol,
+            "waiting" => symbols.waiting = symbol,
+            "pinned" => symbols.pinned = symbol,
+            "prompt-done" => symbols.prompt_done = symbol,
+            "prompt-pending" => symbols.prompt_pending = symbol,
+            _ => bail!(tr!("unknown symbol: {key}", key)),
+        }
+    }
+
+    Ok(())
+}
+
+/// The location of the config file: `$XDG_CONFIG_HOME/tutel/config.toml`,
+/// falling back to `~/.config/tutel/config.toml` and `%APPDATA%\tutel\config.toml`
+/// on Windows, where `HOME` usually isn't set
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(config_dir.join("tutel").join(CONFIG_FILE_NAME))
+}
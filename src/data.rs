@@ -1,11 +1,12 @@
 use anyhow::Context;
 use anyhow::{bail, Result};
-use colored::Colorize;
+use std::collections::{hash_map::RandomState, BTreeMap};
 use std::fs;
-use std::{
-    fmt::{Display, Write},
-    path::PathBuf,
-};
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{date, history, links, search, tr, Error, Format, ARCHIVE_FILE_NAME, DEFAULT_LIST};
 
 /// A Project hold multiple tasks. It also holds the location of
 /// the file these tasks were loaded from and how many
@@ -15,6 +16,15 @@ pub struct Project {
     pub path: PathBuf,
     pub steps: usize,
     pub data: ProjectData,
+    /// Tasks completed more than this many seconds ago are moved to the
+    /// archive whenever the project is saved
+    pub archive_after: Option<u64>,
+    /// The named list that is in `data.tasks`, none for the default one
+    pub list: Option<String>,
+    /// How many changes can be undone, none are recorded if zero
+    pub history: usize,
+    /// Keep an index of the archive up to date for `search`
+    pub search_index: bool,
 }
 
 impl Project {
@@ -25,8 +35,13 @@ impl Project {
             data: ProjectData {
                 name,
                 tasks: Vec::new(),
+                lists: BTreeMap::new(),
             },
             steps,
+            archive_after: None,
+            list: None,
+            history: 0,
+            search_index: false,
         }
     }
 
@@ -37,18 +52,87 @@ impl Project {
     /// a Project could not be loaded from it.
     pub fn load(project_file: PathBuf, steps: usize) -> Result<Self> {
         let file_content =
-            fs::read_to_string(project_file.as_path()).context("unable to read project file")?;
+            fs::read(project_file.as_path()).context(tr("unable to read project file"))?;
 
-        let data: ProjectData =
-            toml::from_str(file_content.as_str()).context("invalid project file syntax")?;
+        let mut data = Format::of(&project_file).parse(&file_content, &project_file)?;
+        data.assign_ids();
 
         Ok(Self {
             path: project_file,
             data,
             steps,
+            archive_after: None,
+            list: None,
+            history: 0,
+            search_index: false,
         })
     }
 
+    /// Makes the named list the one all commands work on. It doesn't need
+    /// to exist yet, it is created once it has tasks.
+    pub fn select_list(&mut self, name: &str) {
+        let current = self.list.as_deref().unwrap_or(DEFAULT_LIST).to_string();
+        if current == name {
+            return;
+        }
+        self.data.switch_list(&current, name);
+        self.list = (name != DEFAULT_LIST).then(|| name.to_string());
+    }
+
+    /// Fails if the project is a TODO.md checklist, which would lose what
+    /// is about to be set the next time it is saved
+    pub(crate) fn not_a_checklist(&self, what: &str) -> Result<()> {
+        if Format::of(&self.path) == Format::Checklist {
+            bail!(tr!(
+                "a checklist can't keep {what}, convert the project first",
+                what
+            ));
+        }
+        Ok(())
+    }
+
+    /// Starts tracking time for a task, moving it to in-progress unless it
+    /// is completed. Only one task is worked on at a time, a task that is
+    /// still active is stopped.
+    ///
+    /// # Errors
+    /// This function will return an error if there is no task with the given
+    /// index or time is already tracked for it.
+    pub fn start(&mut self, index: usize) -> Result<()> {
+        if self.get_task(index)?.is_active() {
+            bail!(tr!("task {index} is already started", index));
+        }
+
+        let now = date::now();
+        self.stop_at(now);
+        let task = self.get_task_mut(index)?;
+        task.intervals.push(Interval {
+            start: now,
+            end: None,
+        });
+        if !task.is_completed() {
+            task.status = Status::InProgress;
+        }
+
+        Ok(())
+    }
+
+    /// Stops tracking time, returning the indices of the tasks that were active
+    pub fn stop(&mut self) -> Vec<usize> {
+        self.stop_at(date::now())
+    }
+
+    fn stop_at(&mut self, now: u64) -> Vec<usize> {
+        let mut stopped = Vec::new();
+        for task in &mut self.data.tasks {
+            if let Some(interval) = task.intervals.last_mut().filter(|i| i.end.is_none()) {
+                interval.end = Some(now);
+                stopped.push(task.index);
+            }
+        }
+        stopped
+    }
+
     /// Save the project to where it was loaded from.
     ///
     /// # Errors
@@ -56,8 +140,172 @@ impl Project {
     /// loaded from cant be written(doesnt exist, permission denied) or the
     /// project could not be serialized. Both of these are not very likely to occur
     pub fn save(&mut self) -> Result<()> {
-        let serialized = toml::to_string_pretty(&self.data)?;
-        fs::write(self.path.as_path(), serialized).context("unable to write project file")?;
+        crate::writable()?;
+        history::record(&self.path, self.history)?;
+        if let Some(after) = self.archive_after {
+            self.archive(Some(date::now().saturating_sub(after)))?;
+        }
+
+        // the default list is the one at the top of the file
+        let list = self.list.clone();
+        if let Some(list) = &list {
+            self.data.switch_list(list, DEFAULT_LIST);
+        }
+        let serialized = Format::of(&self.path).render(&self.data, &self.path);
+        if let Some(list) = &list {
+            self.data.switch_list(DEFAULT_LIST, list);
+        }
+        fs::write(self.path.as_path(), serialized?).context(tr("unable to write project file"))?;
+        Ok(())
+    }
+
+    /// Replaces the project file with `content` as it is, for changes made
+    /// to the file itself rather than to the tasks. Can be undone like
+    /// [`Project::save`].
+    ///
+    /// # Errors
+    /// This function will return an error if the project is read-only or the
+    /// file can't be written.
+    pub fn write(&self, content: &[u8]) -> Result<()> {
+        crate::writable()?;
+        history::record(&self.path, self.history)?;
+        fs::write(&self.path, content).context(tr("unable to write project file"))
+    }
+
+    /// Deletes the project file, the archive stays.
+    ///
+    /// # Errors
+    /// This function will return an error if the project is read-only or the
+    /// file can't be deleted.
+    pub fn delete(self) -> Result<()> {
+        crate::writable()?;
+        fs::remove_file(self.path).context(tr("could not delete project file"))
+    }
+
+    /// Moves completed tasks into the archive file next to the project file,
+    /// returning how many were moved. If `before` is given, only tasks
+    /// completed before that time are moved.
+    ///
+    /// # Errors
+    /// This function will return an error if the archive file can't be read
+    /// or written.
+    pub fn archive(&mut self, before: Option<u64>) -> Result<usize> {
+        crate::writable()?;
+        history::record(&self.path, self.history)?;
+        let (archived, kept): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.data.tasks)
+            .into_iter()
+            .partition(|t| t.is_completed_before(before));
+        self.data.tasks = kept;
+        self.forget(&archived.iter().map(|t| t.index).collect::<Vec<_>>());
+
+        if archived.is_empty() {
+            return Ok(0);
+        }
+
+        let mut archive = self.read_archive()?.unwrap_or_else(|| ProjectData {
+            name: self.data.name.clone(),
+            tasks: Vec::new(),
+            lists: BTreeMap::new(),
+        });
+
+        let count = archived.len();
+        archive.tasks.extend(archived);
+        let serialized = toml::to_string_pretty(&archive)?;
+        fs::write(self.path.with_file_name(ARCHIVE_FILE_NAME), serialized)
+            .context(tr("unable to write archive file"))?;
+        if self.search_index {
+            // the archive is written already, search rebuilds a stale index
+            let _ = search::update_index(&self.path, &archive.tasks);
+        }
+
+        Ok(count)
+    }
+
+    /// The tasks in the archive file next to the project file, if there is one.
+    ///
+    /// # Errors
+    /// This function will return an error if the archive file can't be read.
+    pub fn read_archive(&self) -> Result<Option<ProjectData>> {
+        let path = self.path.with_file_name(ARCHIVE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).context(tr("unable to read archive file"))?;
+        let archive =
+            toml::from_str(&content).context(Error::Invalid("invalid archive file syntax"))?;
+        Ok(Some(archive))
+    }
+
+    /// Returns a reference to a contained Task.
+    ///
+    /// # Errors
+    /// This function will return an error if a Task with the given index
+    /// could not be found.
+    pub fn get_task(&self, index: usize) -> Result<&Task> {
+        match self.data.tasks.iter().find(|t| t.index == index) {
+            Some(t) => Ok(t),
+            None => bail!(Error::NoTask(tr!("index {index}", index))),
+        }
+    }
+
+    /// Returns the task with the given id.
+    ///
+    /// # Errors
+    /// This function will return an error if no task has this id.
+    pub fn find_id(&self, id: &str) -> Result<&Task> {
+        match self.data.tasks.iter().find(|t| t.id == id) {
+            Some(t) => Ok(t),
+            None => bail!(Error::NoTask(tr!("id {id}", id))),
+        }
+    }
+
+    /// Returns the task with the given alias.
+    ///
+    /// # Errors
+    /// This function will return an error if no task has this alias.
+    pub fn find_alias(&self, alias: &str) -> Result<&Task> {
+        match self
+            .data
+            .tasks
+            .iter()
+            .find(|t| t.alias.as_deref() == Some(alias))
+        {
+            Some(t) => Ok(t),
+            None => bail!(Error::NoTask(tr!("alias {alias}", alias))),
+        }
+    }
+
+    /// Gives the task an alias that can be used instead of its index, or
+    /// removes it if none is given.
+    ///
+    /// # Errors
+    /// This function will return an error if there is no task with the given
+    /// index, the alias is not a word starting with a letter or is already
+    /// taken by another task, or the project is a checklist.
+    pub fn set_alias(&mut self, index: usize, alias: Option<String>) -> Result<()> {
+        if let Some(alias) = &alias {
+            self.not_a_checklist(tr("aliases"))?;
+            let valid = alias.starts_with(|c: char| c.is_ascii_lowercase())
+                && alias
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+            if !valid {
+                bail!(tr("an alias is made of lowercase letters, digits, - and _ and starts with a letter"));
+            }
+            let taken =
+                self.data.tasks.iter().find(|t| {
+                    t.index != index && (t.id == *alias || t.alias.as_ref() == Some(alias))
+                });
+            if let Some(other) = taken {
+                bail!(tr!(
+                    "{alias} already refers to task {index}",
+                    alias,
+                    index = other.index
+                ));
+            }
+        }
+
+        self.get_task_mut(index)?.alias = alias;
         Ok(())
     }
 
@@ -72,30 +320,150 @@ impl Project {
                 return Ok(t);
             }
         }
-        bail!("no task with index {}", &index)
+        bail!(Error::NoTask(tr!("index {index}", index)))
+    }
+
+    /// Adds a new task, returning it so further fields can be set
+    pub fn add(&mut self, name: String, completed: bool) -> &mut Task {
+        let index = self.next_index();
+        let mut task = Task::new(name, completed, index);
+        task.id = self.data.unused_id();
+        self.data.tasks.push(task);
+        self.data.tasks.last_mut().unwrap()
     }
 
-    pub fn add(&mut self, name: String, completed: bool) {
+    /// Finds an open task that describes the same thing, ignoring case,
+    /// punctuation, word order and small typos
+    pub fn find_duplicate(&self, desc: &str) -> Option<&Task> {
         self.data
             .tasks
-            .push(Task::new(name, completed, self.next_index()))
+            .iter()
+            .filter(|t| !t.is_completed())
+            .find(|t| is_similar(&t.desc, desc))
     }
 
     pub fn remove(&mut self, index: usize) {
-        self.data.tasks.retain(|t| t.index != index);
+        self.remove_where(|t| t.index == index);
+    }
+
+    /// Removes the tasks matching `f`, and the dependencies on them
+    pub fn remove_where(&mut self, f: impl Fn(&Task) -> bool) {
+        let removed: Vec<usize> = self
+            .data
+            .tasks
+            .iter()
+            .filter(|t| f(t))
+            .map(|t| t.index)
+            .collect();
+        self.data.tasks.retain(|t| !f(t));
+        self.forget(&removed);
+    }
+
+    /// Drops the dependencies on tasks that are gone. Their indices are
+    /// handed out again, so a new task would take their place otherwise.
+    /// Tasks that were only blocked by them aren't anymore.
+    fn forget(&mut self, removed: &[usize]) {
+        for t in &mut self.data.tasks {
+            if t.depends.iter().any(|d| removed.contains(d)) {
+                t.depends.retain(|d| !removed.contains(d));
+                if t.depends.is_empty() && t.status == Status::Blocked {
+                    t.set_status(Status::Pending);
+                }
+            }
+        }
+    }
+
+    /// Makes the task depend on another one, or not anymore if `depends` is
+    /// false. A pending task depending on one that isn't completed is blocked.
+    ///
+    /// # Errors
+    /// This function will return an error if either task doesn't exist, the
+    /// dependency would be circular or the project is a checklist.
+    pub fn depend(&mut self, index: usize, on: usize, depends: bool) -> Result<()> {
+        if depends {
+            self.not_a_checklist(tr("dependencies"))?;
+        }
+        self.get_task(on)?;
+        if depends && (index == on || self.depends_on(on, index)) {
+            bail!(tr!("task {on} already depends on task {index}", on, index));
+        }
+
+        let on_completed = self.get_task(on)?.is_completed();
+        let task = self.get_task_mut(index)?;
+        task.depends.retain(|d| *d != on);
+        if depends {
+            task.depends.push(on);
+            if !on_completed && task.status == Status::Pending {
+                task.set_status(Status::Blocked);
+            }
+        }
+        self.unblock();
+        Ok(())
+    }
+
+    /// Moves blocked tasks whose dependencies are all completed back to
+    /// pending, returning their indices. Tasks blocked without depending on
+    /// anything are left alone.
+    pub fn unblock(&mut self) -> Vec<usize> {
+        let ready: Vec<usize> = self
+            .data
+            .tasks
+            .iter()
+            .filter(|t| t.status == Status::Blocked && !t.depends.is_empty())
+            .filter(|t| {
+                t.depends
+                    .iter()
+                    .all(|d| self.get_task(*d).map_or(true, Task::is_completed))
+            })
+            .map(|t| t.index)
+            .collect();
+
+        for t in &mut self.data.tasks {
+            if ready.contains(&t.index) {
+                t.set_status(Status::Pending);
+            }
+        }
+        ready
+    }
+
+    /// Whether the task depends on the other one, directly or through other tasks
+    pub fn depends_on(&self, index: usize, on: usize) -> bool {
+        let mut todo = vec![index];
+        let mut seen = Vec::new();
+        while let Some(index) = todo.pop() {
+            if seen.contains(&index) {
+                continue;
+            }
+            seen.push(index);
+            let Ok(task) = self.get_task(index) else {
+                continue;
+            };
+            if task.depends.contains(&on) {
+                return true;
+            }
+            todo.extend(&task.depends);
+        }
+        false
     }
 
     pub fn remove_all(&mut self) {
         self.data.tasks.clear();
     }
 
-    pub fn remove_completed(&mut self) {
-        self.data.tasks.retain(|t| !t.completed);
+    /// Removes completed tasks. If `before` is given, only tasks completed
+    /// before that time are removed.
+    pub fn remove_completed(&mut self, before: Option<u64>) {
+        self.remove_where(|t| t.is_completed_before(before));
+    }
+
+    /// Removes the tasks that are not completed
+    pub fn remove_pending(&mut self) {
+        self.remove_where(|t| !t.is_completed());
     }
 
     pub fn mark_completion_all(&mut self, completed: bool) {
-        for mut t in &mut self.data.tasks {
-            t.completed = completed;
+        for t in &mut self.data.tasks {
+            t.set_status(Status::from_completion(completed));
         }
     }
 
@@ -105,8 +473,29 @@ impl Project {
     /// This function will return an error if a Task with the given index
     /// could not be found.
     pub fn mark_completion(&mut self, index: usize, completed: bool) -> Result<()> {
+        self.set_status(index, Status::from_completion(completed))
+    }
+
+    /// Marks the Task with the given Index as completed, noting how it was
+    /// resolved.
+    ///
+    /// # Errors
+    /// This function will return an error if a Task with the given index
+    /// could not be found.
+    pub fn complete_with_note(&mut self, index: usize, note: impl Into<String>) -> Result<()> {
         let task = self.get_task_mut(index)?;
-        task.completed = completed;
+        task.set_status(Status::Done);
+        task.note = Some(note.into());
+        Ok(())
+    }
+
+    /// Changes the status of the Task with the given index.
+    ///
+    /// # Errors
+    /// This function will return an error if a Task with the given index
+    /// could not be found.
+    pub fn set_status(&mut self, index: usize, status: Status) -> Result<()> {
+        self.get_task_mut(index)?.set_status(status);
         Ok(())
     }
 
@@ -134,84 +523,533 @@ impl Project {
     }
 }
 
-impl Display for Project {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Gather all tasks and their completion state
-        let mut tasks = String::new();
-        let mut completed = true;
+/// The part of a Project that needs to be saved/loaded
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct ProjectData {
+    pub name: String,
+    pub tasks: Vec<Task>,
+    /// The named lists besides the default one in `tasks`
+    pub lists: BTreeMap<String, Vec<Task>>,
+}
+
+impl ProjectData {
+    /// Puts the tasks away as the list `current` and takes out the list
+    /// `name` in their place. Lists without tasks are dropped.
+    fn switch_list(&mut self, current: &str, name: &str) {
+        let tasks = self.lists.remove(name).unwrap_or_default();
+        let previous = std::mem::replace(&mut self.tasks, tasks);
+        if !previous.is_empty() {
+            self.lists.insert(current.to_string(), previous);
+        }
+    }
 
-        for t in &self.data.tasks {
-            write!(&mut tasks, "\n{}", t)?;
-            if !t.completed {
-                completed = false;
+    /// Gives the tasks created by older versions an id. It is derived from
+    /// the task, so it stays the same until the project is saved.
+    pub fn assign_ids(&mut self) {
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].id.is_empty() {
+                let task = &self.tasks[i];
+                let id = id_from(fnv(&format!(
+                    "{}{}{:?}",
+                    task.index, task.desc, task.created
+                )));
+                self.tasks[i].id = if self.tasks.iter().any(|t| t.id == id) {
+                    self.unused_id()
+                } else {
+                    id
+                };
             }
         }
+    }
 
-        let steps_counter = if self.steps == 0 {
-            String::new()
-        } else {
-            format!(" [-{}]", self.steps).blue().to_string()
-        };
-
-        let marker = if completed { "✓".green() } else { "X".red() };
-
-        let headline_marker = format!("[{marker}]{steps_counter}");
+    /// Generates an id no task has yet
+    fn unused_id(&self) -> String {
+        loop {
+            let id = generate_id();
+            if self.tasks.iter().all(|t| t.id != id) {
+                return id;
+            }
+        }
+    }
+}
 
-        let headline = format!(
-            "{} {}",
-            headline_marker.yellow().bold(),
-            self.data.name.bold()
-        );
-        write!(f, "{}", headline)?;
+/// Generates a random id like `k3x9qa`
+fn generate_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(date::now());
+    id_from(hasher.finish())
+}
 
-        if !self.data.tasks.is_empty() {
-            write!(f, "{}", tasks)?;
-        } else {
-            write!(f, "\n[empty]")?;
-        }
+/// Turns a number into an id. It always starts with a letter, so it can't be
+/// confused with an index.
+fn id_from(mut n: u64) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
 
-        Ok(())
+    let mut id = String::with_capacity(6);
+    id.push(char::from(DIGITS[10 + (n % 26) as usize]));
+    n /= 26;
+    for _ in 0..5 {
+        id.push(char::from(DIGITS[(n % 36) as usize]));
+        n /= 36;
     }
+    id
 }
 
-/// The part of a Project that needs to be saved/loaded
-#[allow(clippy::module_name_repetitions)]
-#[derive(Debug)]
-pub struct ProjectData {
-    pub(crate) name: String,
-    pub tasks: Vec<Task>,
+/// The FNV-1a hash of the text, which unlike the std hashers is stable
+pub fn fnv(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 /// A completable Task within a Project
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Task {
     pub desc: String,
     pub index: usize,
-    pub completed: bool,
+    /// Unlike the index this never changes, not even in the archive
+    pub id: String,
+    /// A name given to the task that can be used instead of its index
+    pub alias: Option<String>,
+    pub status: Status,
+    /// When the task was done or cancelled. Not known for tasks completed
+    /// with older versions.
+    pub closed: Option<u64>,
+    /// How the task was resolved, given when completing it
+    pub note: Option<String>,
+    /// When the task was added, in seconds since the unix epoch. Tasks
+    /// created by older versions don't have this.
+    pub created: Option<u64>,
+    /// The time spent working on this task, oldest first
+    pub intervals: Vec<Interval>,
+    /// How long the task is expected to take, in seconds
+    pub estimate: Option<u64>,
+    /// How many pomodoros were finished working on this task
+    pub pomodoros: u32,
+    /// The task is hidden from the list until this point in time
+    pub snoozed: Option<u64>,
+    /// What the task is waiting on, e.g. someone it was delegated to. Empty
+    /// if no reason was given.
+    pub waiting: Option<String>,
+    /// Who takes care of the task, for lists shared by a team
+    pub assignee: Option<String>,
+    pub priority: Option<Priority>,
+    /// When the task has to be done by, in seconds since the unix epoch
+    pub due: Option<u64>,
+    /// Pinned tasks are listed first
+    pub pinned: bool,
+    /// Hidden tasks are kept, but not listed by default
+    pub hidden: bool,
+    /// The indices of the tasks that have to be done before this one
+    pub depends: Vec<usize>,
+    /// Tasks of other projects that have to be done before this one, as
+    /// `project:id`
+    pub external: Vec<String>,
+    /// Files and urls that belong to the task. Files inside of the project
+    /// are relative to the project directory.
+    pub attachments: Vec<String>,
+}
+
+/// The state a task is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Pending,
+    InProgress,
+    Blocked,
+    Done,
+    /// Closed without being done
+    Cancelled,
+}
+
+impl Status {
+    pub const ALL: &'static [Self] = &[
+        Self::Pending,
+        Self::InProgress,
+        Self::Blocked,
+        Self::Done,
+        Self::Cancelled,
+    ];
+
+    /// The name used on the command line and in project files
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in-progress",
+            Self::Blocked => "blocked",
+            Self::Done => "done",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// The name in the language of the user, for display
+    pub fn label(self) -> &'static str {
+        crate::tr(self.name())
+    }
+
+    /// Whether no more work is needed, because the task is done or cancelled
+    pub const fn is_closed(self) -> bool {
+        matches!(self, Self::Done | Self::Cancelled)
+    }
+
+    /// The status matching the completion state older versions stored
+    pub const fn from_completion(completed: bool) -> Self {
+        if completed {
+            Self::Done
+        } else {
+            Self::Pending
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.iter().find(|status| status.name() == s) {
+            Some(status) => Ok(*status),
+            None => bail!(
+                tr!("unknown status: {s}. available are pending, in-progress, blocked, done and cancelled", s)
+            ),
+        }
+    }
+}
+
+/// How important a task is, tasks without one are in between low and medium
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub const ALL: &'static [Self] = &[Self::Low, Self::Medium, Self::High];
+
+    /// The name used on the command line and in project files
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
+    /// The name in the language of the user, for display
+    pub fn label(self) -> &'static str {
+        crate::tr(self.name())
+    }
+}
+
+impl FromStr for Priority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.iter().find(|priority| priority.name() == s) {
+            Some(priority) => Ok(*priority),
+            None => bail!(tr!(
+                "unknown priority: {s}. available are low, medium and high",
+                s
+            )),
+        }
+    }
+}
+
+/// A span of time spent working on a task, in seconds since the unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub start: u64,
+    /// Not set as long as the work is still going on
+    pub end: Option<u64>,
+}
+
+impl Interval {
+    /// How long this interval lasted, or has lasted until now if it is still running
+    pub fn duration(&self, now: u64) -> u64 {
+        self.end.unwrap_or(now).saturating_sub(self.start)
+    }
 }
 
 impl Task {
     pub fn new(name: impl Into<String>, completed: bool, index: usize) -> Self {
         Self {
             desc: name.into(),
-            completed,
+            status: Status::from_completion(completed),
+            closed: completed.then(date::now),
+            note: None,
             index,
+            id: generate_id(),
+            created: Some(date::now()),
+            intervals: Vec::new(),
+            estimate: None,
+            pomodoros: 0,
+            snoozed: None,
+            waiting: None,
+            assignee: None,
+            priority: None,
+            due: None,
+            pinned: false,
+            hidden: false,
+            depends: Vec::new(),
+            external: Vec::new(),
+            attachments: Vec::new(),
+            alias: None,
         }
     }
+
+    /// Changes the status, keeping track of when the task was completed.
+    /// Reopening the task drops its note.
+    pub fn set_status(&mut self, status: Status) {
+        if status.is_closed() != self.status.is_closed() {
+            self.closed = status.is_closed().then(date::now);
+        }
+        if !status.is_closed() {
+            self.note = None;
+        }
+        self.status = status;
+    }
+
+    /// Whether the task is done or cancelled
+    pub const fn is_completed(&self) -> bool {
+        self.status.is_closed()
+    }
+
+    /// Whether the task was completed before the given time, or at all if
+    /// none is given. Tasks without a completion time only count in the latter case.
+    pub fn is_completed_before(&self, time: Option<u64>) -> bool {
+        self.is_completed() && time.is_none_or(|time| self.closed.is_some_and(|c| c < time))
+    }
+
+    /// Whether time is currently being tracked for this task
+    pub fn is_active(&self) -> bool {
+        self.intervals.last().is_some_and(|i| i.end.is_none())
+    }
+
+    /// The total time spent on this task in seconds, including a running interval
+    pub fn tracked(&self, now: u64) -> u64 {
+        self.intervals.iter().map(|i| i.duration(now)).sum()
+    }
+
+    /// The part of the estimate not yet spent on this task. Completed tasks
+    /// have nothing left.
+    pub fn remaining(&self, now: u64) -> Option<u64> {
+        if self.is_completed() {
+            return Some(0);
+        }
+        Some(self.estimate?.saturating_sub(self.tracked(now)))
+    }
+
+    /// Whether the task can't be worked on until something else happens
+    pub const fn is_waiting(&self) -> bool {
+        !self.is_completed() && self.waiting.is_some()
+    }
+
+    /// The people mentioned in the description like `@bob`
+    pub fn mentions(&self) -> Vec<&str> {
+        links::find_mentions(&self.desc)
+    }
+
+    /// Whether the task mentions the person, ignoring case
+    pub fn mentions_person(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('@');
+        self.mentions().iter().any(|m| m.eq_ignore_ascii_case(name))
+    }
+
+    /// The tags in the description like `#release-blocker`
+    pub fn tags(&self) -> Vec<&str> {
+        links::find_tags(&self.desc)
+    }
+
+    /// Whether the task has the tag, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags().iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Appends `#tag` to the description, unless the task has the tag already
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim_start_matches('#');
+        if !self.has_tag(tag) {
+            self.desc = format!("{} #{tag}", self.desc);
+        }
+    }
+
+    /// Drops the words with the tag from the description
+    pub fn remove_tag(&mut self, tag: &str) {
+        let tag = tag.trim_start_matches('#');
+        self.desc = self
+            .desc
+            .split_whitespace()
+            .filter(|word| {
+                !links::find_tags(word)
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(tag))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    /// Whether the task is still open after its due date
+    pub fn is_overdue(&self, now: u64) -> bool {
+        !self.is_completed() && self.due.is_some_and(|due| due <= now)
+    }
+
+    /// Whether the task is still snoozed at the given time
+    pub fn is_snoozed(&self, now: u64) -> bool {
+        self.snoozed.is_some_and(|until| until > now)
+    }
+
+    /// Whether the task is still open more than `after` seconds after it was created
+    pub fn is_stale(&self, now: u64, after: u64) -> bool {
+        !self.is_completed()
+            && self
+                .created
+                .is_some_and(|created| now.saturating_sub(created) > after)
+    }
+
+    /// How long ago this task was created, in seconds
+    pub fn age(&self) -> Option<u64> {
+        self.created
+            .map(|created| date::now().saturating_sub(created))
+    }
 }
 
-impl Display for Task {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let marker = if self.completed {
-            "[✓]".green()
-        } else {
-            "[X]".red()
-        };
-        f.write_fmt(format_args!(
-            "{:03} {} {marker}{}",
-            self.index,
-            "│".bold(),
-            self.desc
-        ))
+/// The lowercase words of a description, without punctuation
+pub fn words(desc: &str) -> Vec<String> {
+    desc.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether two descriptions are the same apart from case, punctuation, word
+/// order or a typo or two
+fn is_similar(a: &str, b: &str) -> bool {
+    let (mut a, mut b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    let (joined_a, joined_b) = (a.join(" "), b.join(" "));
+    // short descriptions differing by a typo are different words more often than not
+    let allowed = joined_a.chars().count().min(joined_b.chars().count()) / 10;
+    if edit_distance(&joined_a, &joined_b) <= allowed {
+        return true;
+    }
+
+    a.sort_unstable();
+    b.sort_unstable();
+    a == b
+}
+
+/// The number of characters to insert, remove or replace to turn a into b
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let replaced = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = replaced.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[test]
+fn similar_descriptions() {
+    assert!(is_similar("Water the plants", "water the plants!"));
+    assert!(is_similar(
+        "update deps, run tests",
+        "run tests update deps"
+    ));
+    assert!(is_similar("renew the passport", "renew the pasport"));
+    assert!(!is_similar("fix bug", "fix bus"));
+    assert!(!is_similar("water the plants", "water the garden"));
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn named_lists() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut project = Project::new(dir.path().join(".tutel.toml"), 0, "demo".to_string());
+    project.add("buy milk".to_string(), false);
+    project.select_list("work");
+    assert!(project.data.tasks.is_empty());
+    project.add("send invoice".to_string(), false);
+    project.save().unwrap();
+
+    let mut project = Project::load(project.path, 0).unwrap();
+    project.select_list(DEFAULT_LIST);
+    assert_eq!(project.data.tasks[0].desc, "buy milk");
+    project.select_list("work");
+    assert_eq!(project.data.tasks[0].desc, "send invoice");
+    project.select_list(DEFAULT_LIST);
+    assert_eq!(project.data.tasks[0].desc, "buy milk");
+    assert_eq!(project.data.lists.len(), 1);
+}
+
+#[test]
+fn dependencies_on_removed_tasks() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut project = Project::new(dir.path().join(".tutel.toml"), 0, "demo".to_string());
+    for desc in ["a", "b", "c"] {
+        project.add(desc.to_string(), false);
     }
+    project.depend(1, 2, true).unwrap();
+    project.mark_completion(2, true).unwrap();
+    project.archive(None).unwrap();
+    // takes the index of the archived task
+    assert_eq!(project.add("d".to_string(), false).index, 2);
+    assert!(project.get_task(1).unwrap().depends.is_empty());
+
+    project.depend(0, 2, true).unwrap();
+    assert_eq!(project.get_task(0).unwrap().status, Status::Blocked);
+    project.mark_completion(2, true).unwrap();
+    project.remove_completed(None);
+    project.add("e".to_string(), false);
+    let a = project.get_task(0).unwrap();
+    assert!(a.depends.is_empty());
+    assert_eq!(a.status, Status::Pending);
+}
+
+#[test]
+fn aliases() {
+    let mut project = Project::new(PathBuf::from(".tutel.toml"), 0, "demo".to_string());
+    project.add("deploy".to_string(), false);
+    project.add("buy milk".to_string(), false);
+
+    project.set_alias(0, Some("ship-it".to_string())).unwrap();
+    assert_eq!(project.find_alias("ship-it").unwrap().index, 0);
+    assert!(project.set_alias(1, Some("ship-it".to_string())).is_err());
+    assert!(project.set_alias(1, Some("2nd".to_string())).is_err());
+
+    project.set_alias(0, None).unwrap();
+    assert!(project.find_alias("ship-it").is_err());
+}
+
+#[test]
+fn checklists_refuse_what_they_cant_keep() {
+    let mut project = Project::new(PathBuf::from("TODO.md"), 0, "demo".to_string());
+    project.add("deploy".to_string(), false);
+    project.add("test".to_string(), false);
+
+    assert!(project.set_alias(0, Some("ship-it".to_string())).is_err());
+    assert!(project.depend(0, 1, true).is_err());
+    project.set_alias(0, None).unwrap();
+    project.depend(0, 1, false).unwrap();
+}
+
+#[test]
+fn retagging() {
+    let mut task = Task::new("fix the login #bug (#urgent)", false, 0);
+    task.add_tag("#bug");
+    task.add_tag("ui");
+    task.remove_tag("urgent");
+    assert_eq!(task.desc, "fix the login #bug #ui");
 }
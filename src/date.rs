@@ -0,0 +1,245 @@
+use anyhow::{bail, Result};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, Days, Local, NaiveDate, TimeZone, Weekday,
+};
+use std::{
+    convert::TryFrom,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::tr;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const YEAR: u64 = 365 * DAY;
+
+/// The current time as seconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Formats a duration in the largest fitting unit, e.g. `5m`, `3d` or `2w`
+pub fn format_age(secs: u64) -> String {
+    if secs < HOUR {
+        format!("{}m", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h", secs / HOUR)
+    } else if secs < 2 * WEEK {
+        format!("{}d", secs / DAY)
+    } else if secs < YEAR {
+        format!("{}w", secs / WEEK)
+    } else {
+        format!("{}y", secs / YEAR)
+    }
+}
+
+/// Formats a span of work time in hours and minutes, e.g. `2h 05m` or `40m`
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / HOUR, secs % HOUR / MINUTE);
+    if hours == 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{hours}h {minutes:02}m")
+    }
+}
+
+/// Parses a duration like `30m`, `2d` or `1w` into seconds, the units are
+/// the same `format_age` uses
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let unit = match text.chars().last()? {
+        'm' => MINUTE,
+        'h' => HOUR,
+        'd' => DAY,
+        'w' => WEEK,
+        'y' => YEAR,
+        _ => return None,
+    };
+    let amount: u64 = text[..text.len() - 1].parse().ok()?;
+    amount.checked_mul(unit)
+}
+
+/// How points in time are shown to the user
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `2024-03-01 14:30`
+    #[default]
+    Iso,
+    /// How long ago or from now, e.g. `3d ago` or `in 2h`
+    Relative,
+    /// A strftime format like `%d.%m.%Y %H:%M`
+    Custom(String),
+}
+
+impl DateFormat {
+    /// Parses `iso`, `relative` or a strftime format.
+    ///
+    /// # Errors
+    /// This function will return an error if the strftime format is invalid.
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "iso" => Ok(Self::Iso),
+            "relative" => Ok(Self::Relative),
+            _ => {
+                // chrono only notices invalid formats when they are used, and panics then
+                if StrftimeItems::new(text).any(|item| item == Item::Error) {
+                    bail!(tr!(
+                        "invalid date format: {text}. use iso, relative or e.g. %d.%m.%Y %H:%M",
+                        text
+                    ));
+                }
+                Ok(Self::Custom(text.to_string()))
+            }
+        }
+    }
+
+    /// Formats a point in time, relative ones in respect to `now`
+    pub fn format(&self, timestamp: u64, now: u64) -> String {
+        match self {
+            Self::Iso => format_datetime(timestamp),
+            Self::Relative if timestamp > now => format!("in {}", format_age(timestamp - now)),
+            Self::Relative => format!("{} ago", format_age(now - timestamp)),
+            Self::Custom(format) => local(timestamp).map_or_else(
+                || "invalid date".to_string(),
+                |dt| dt.format(format).to_string(),
+            ),
+        }
+    }
+
+    /// Formats a day like `2024-03-01`, as returned by [`format_day`]
+    pub fn format_day(&self, day: &str, now: u64) -> String {
+        let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+            return day.to_string();
+        };
+        match self {
+            Self::Iso => day.to_string(),
+            Self::Relative => {
+                let today = local(now).map(|dt| dt.date_naive());
+                match today.map(|today| (today - date).num_days()) {
+                    Some(0) => "today".to_string(),
+                    Some(1) => "yesterday".to_string(),
+                    Some(days) if days > 1 => format!("{days}d ago"),
+                    _ => day.to_string(),
+                }
+            }
+            Self::Custom(_) => {
+                midnight(date).map_or_else(|| day.to_string(), |t| self.format(t, now))
+            }
+        }
+    }
+}
+
+/// Formats a timestamp as local date and time
+pub fn format_datetime(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0).map_or_else(
+        || "invalid date".to_string(),
+        |dt| {
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        },
+    )
+}
+
+/// The local day a timestamp falls on, e.g. `2024-03-01`
+pub fn format_day(timestamp: u64) -> String {
+    local(timestamp).map_or_else(
+        || "invalid date".to_string(),
+        |dt| dt.format("%Y-%m-%d").to_string(),
+    )
+}
+
+/// The first second of the local day following the timestamp
+pub fn next_midnight(timestamp: u64) -> Option<u64> {
+    let day = local(timestamp)?
+        .date_naive()
+        .checked_add_days(Days::new(1))?;
+    midnight(day)
+}
+
+/// Parses a point in time: `today`, `yesterday`, a date like `2024-03-01`
+/// (all meaning midnight, local time) or a duration like `2w`, meaning
+/// that long ago
+pub fn parse_date(text: &str, now: u64) -> Option<u64> {
+    let today = local(now)?.date_naive();
+    match text {
+        "today" => midnight(today),
+        "yesterday" => midnight(today.checked_sub_days(Days::new(1))?),
+        _ => match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            Ok(day) => midnight(day),
+            Err(_) => Some(now.saturating_sub(parse_duration(text)?)),
+        },
+    }
+}
+
+/// Parses a point in the future, the counterpart of `parse_date`
+///
+/// Accepts `today`, `tomorrow`, a weekday like `friday` (the next one to
+/// come), a date like `2024-03-01` (all meaning midnight, local time) or a
+/// duration like `2w`, meaning that long from now.
+pub fn parse_until(text: &str, now: u64) -> Option<u64> {
+    let today = local(now)?.date_naive();
+    match text {
+        "today" => midnight(today),
+        "tomorrow" => midnight(today.checked_add_days(Days::new(1))?),
+        _ => {
+            if let Ok(weekday) = text.parse::<Weekday>() {
+                let from_today = weekday.days_since(today.weekday());
+                let days = if from_today == 0 { 7 } else { from_today };
+                return midnight(today.checked_add_days(Days::new(u64::from(days)))?);
+            }
+            match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+                Ok(day) => midnight(day),
+                Err(_) => now.checked_add(parse_duration(text)?),
+            }
+        }
+    }
+}
+
+fn local(timestamp: u64) -> Option<DateTime<Local>> {
+    Local
+        .timestamp_opt(i64::try_from(timestamp).ok()?, 0)
+        .earliest()
+}
+
+fn midnight(day: NaiveDate) -> Option<u64> {
+    let dt = Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    u64::try_from(dt.timestamp()).ok()
+}
+
+#[test]
+fn parse_weekdays() {
+    // a thursday
+    let now = midnight(NaiveDate::from_ymd_opt(2024, 3, 7).unwrap()).unwrap() + HOUR;
+    let day = |text| parse_until(text, now).map(format_day);
+
+    assert_eq!(day("friday").as_deref(), Some("2024-03-08"));
+    assert_eq!(day("mon").as_deref(), Some("2024-03-11"));
+    assert_eq!(day("thursday").as_deref(), Some("2024-03-14"));
+    assert_eq!(day("tomorrow").as_deref(), Some("2024-03-08"));
+    assert_eq!(parse_until("2h", now), Some(now + 2 * HOUR));
+    assert_eq!(parse_until("someday", now), None);
+}
+
+#[test]
+fn date_formats() {
+    let now = midnight(NaiveDate::from_ymd_opt(2024, 3, 7).unwrap()).unwrap() + 15 * HOUR;
+
+    assert_eq!(DateFormat::Iso.format(now, now), "2024-03-07 15:00");
+    assert_eq!(DateFormat::Relative.format(now - 3 * DAY, now), "3d ago");
+    assert_eq!(DateFormat::Relative.format(now + 2 * HOUR, now), "in 2h");
+    let custom = DateFormat::parse("%d.%m. %H:%M").unwrap();
+    assert_eq!(custom.format(now, now), "07.03. 15:00");
+    assert_eq!(custom.format_day("2024-03-01", now), "01.03. 00:00");
+    assert_eq!(
+        DateFormat::Relative.format_day("2024-03-06", now),
+        "yesterday"
+    );
+    assert!(DateFormat::parse("%Q").is_err());
+}
@@ -3,13 +3,17 @@ use serde::{
     Deserialize,
 };
 
-use super::{data::ProjectData, Task};
+use super::{
+    data::{Interval, Priority, ProjectData, Status},
+    Task,
+};
 
-const PROJECT_DATA_FIELDS: &[&str] = &["name", "tasks"];
+const PROJECT_DATA_FIELDS: &[&str] = &["name", "tasks", "lists"];
 
 enum ProjectDataField {
     Name,
     Tasks,
+    Lists,
 }
 
 struct ProjectDataFieldVisitor;
@@ -18,7 +22,7 @@ impl<'de> Visitor<'de> for ProjectDataFieldVisitor {
     type Value = ProjectDataField;
 
     fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        fmt.write_str("'name' or 'tasks'")
+        fmt.write_str("'name', 'tasks' or 'lists'")
     }
 
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -28,6 +32,7 @@ impl<'de> Visitor<'de> for ProjectDataFieldVisitor {
         match v {
             "name" => Ok(ProjectDataField::Name),
             "tasks" => Ok(ProjectDataField::Tasks),
+            "lists" => Ok(ProjectDataField::Lists),
             _ => Err(de::Error::unknown_field(v, PROJECT_DATA_FIELDS)),
         }
     }
@@ -57,6 +62,7 @@ impl<'de> Visitor<'de> for ProjectDataVisitor {
     {
         let mut name = None;
         let mut tasks = None;
+        let mut lists = None;
         while let Some(key) = map.next_key()? {
             match key {
                 ProjectDataField::Name => {
@@ -71,13 +77,21 @@ impl<'de> Visitor<'de> for ProjectDataVisitor {
                     }
                     tasks = Some(map.next_value()?);
                 }
+                ProjectDataField::Lists => {
+                    if lists.is_some() {
+                        return Err(de::Error::duplicate_field("lists"));
+                    }
+                    lists = Some(map.next_value()?);
+                }
             }
         }
 
         let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
         let tasks = tasks.ok_or_else(|| de::Error::missing_field("tasks"))?;
 
-        Ok(ProjectData { name, tasks })
+        let lists = lists.unwrap_or_default();
+
+        Ok(ProjectData { name, tasks, lists })
     }
 }
 
@@ -90,12 +104,54 @@ impl<'de> Deserialize<'de> for ProjectData {
     }
 }
 
-const TASK_FIELDS: &[&str] = &["name", "index", "completed"];
+const TASK_FIELDS: &[&str] = &[
+    "name",
+    "index",
+    "id",
+    "status",
+    "completed",
+    "closed",
+    "note",
+    "created",
+    "estimate",
+    "pomodoros",
+    "snoozed",
+    "waiting",
+    "assignee",
+    "priority",
+    "due",
+    "pinned",
+    "hidden",
+    "depends",
+    "external",
+    "attachments",
+    "alias",
+    "intervals",
+];
 
 enum TaskField {
     Name,
     Index,
+    Id,
+    Status,
     Completed,
+    Closed,
+    Note,
+    Created,
+    Estimate,
+    Pomodoros,
+    Snoozed,
+    Waiting,
+    Assignee,
+    Priority,
+    Due,
+    Pinned,
+    Hidden,
+    Depends,
+    External,
+    Attachments,
+    Alias,
+    Intervals,
 }
 
 struct TaskFieldVisitor;
@@ -104,7 +160,7 @@ impl<'de> Visitor<'de> for TaskFieldVisitor {
     type Value = TaskField;
 
     fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        fmt.write_str("'name', 'index' or 'completed'")
+        fmt.write_str("a task field like 'name', 'index' or 'status'")
     }
 
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -114,7 +170,26 @@ impl<'de> Visitor<'de> for TaskFieldVisitor {
         match v {
             "name" | "desc" => Ok(TaskField::Name),
             "index" => Ok(TaskField::Index),
+            "id" => Ok(TaskField::Id),
+            "status" => Ok(TaskField::Status),
             "completed" => Ok(TaskField::Completed),
+            "closed" => Ok(TaskField::Closed),
+            "note" => Ok(TaskField::Note),
+            "created" => Ok(TaskField::Created),
+            "estimate" => Ok(TaskField::Estimate),
+            "pomodoros" => Ok(TaskField::Pomodoros),
+            "snoozed" => Ok(TaskField::Snoozed),
+            "waiting" => Ok(TaskField::Waiting),
+            "assignee" => Ok(TaskField::Assignee),
+            "priority" => Ok(TaskField::Priority),
+            "due" => Ok(TaskField::Due),
+            "pinned" => Ok(TaskField::Pinned),
+            "hidden" => Ok(TaskField::Hidden),
+            "depends" => Ok(TaskField::Depends),
+            "external" => Ok(TaskField::External),
+            "attachments" => Ok(TaskField::Attachments),
+            "alias" => Ok(TaskField::Alias),
+            "intervals" => Ok(TaskField::Intervals),
             _ => Err(de::Error::unknown_field(v, TASK_FIELDS)),
         }
     }
@@ -144,7 +219,26 @@ impl<'de> Visitor<'de> for TaskVisitor {
     {
         let mut description = None;
         let mut index = None;
+        let mut id = None;
+        let mut status = None;
         let mut completed = None;
+        let mut closed = None;
+        let mut note = None;
+        let mut created = None;
+        let mut estimate = None;
+        let mut pomodoros = None;
+        let mut snoozed = None;
+        let mut waiting = None;
+        let mut assignee = None;
+        let mut priority = None;
+        let mut due = None;
+        let mut pinned = None;
+        let mut hidden = None;
+        let mut depends = None;
+        let mut external = None;
+        let mut attachments = None;
+        let mut alias = None;
+        let mut intervals = None;
         while let Some(key) = map.next_key()? {
             match key {
                 TaskField::Name => {
@@ -159,23 +253,161 @@ impl<'de> Visitor<'de> for TaskVisitor {
                     }
                     index = Some(map.next_value()?);
                 }
+                TaskField::Id => {
+                    if id.is_some() {
+                        return Err(de::Error::duplicate_field("id"));
+                    }
+                    id = Some(map.next_value()?);
+                }
+                TaskField::Status => {
+                    if status.is_some() {
+                        return Err(de::Error::duplicate_field("status"));
+                    }
+                    status = Some(map.next_value()?);
+                }
                 TaskField::Completed => {
                     if completed.is_some() {
                         return Err(de::Error::duplicate_field("completed"));
                     }
                     completed = Some(map.next_value()?);
                 }
+                TaskField::Closed => {
+                    if closed.is_some() {
+                        return Err(de::Error::duplicate_field("closed"));
+                    }
+                    closed = Some(map.next_value()?);
+                }
+                TaskField::Note => {
+                    if note.is_some() {
+                        return Err(de::Error::duplicate_field("note"));
+                    }
+                    note = Some(map.next_value()?);
+                }
+                TaskField::Created => {
+                    if created.is_some() {
+                        return Err(de::Error::duplicate_field("created"));
+                    }
+                    created = Some(map.next_value()?);
+                }
+                TaskField::Estimate => {
+                    if estimate.is_some() {
+                        return Err(de::Error::duplicate_field("estimate"));
+                    }
+                    estimate = Some(map.next_value()?);
+                }
+                TaskField::Pomodoros => {
+                    if pomodoros.is_some() {
+                        return Err(de::Error::duplicate_field("pomodoros"));
+                    }
+                    pomodoros = Some(map.next_value()?);
+                }
+                TaskField::Snoozed => {
+                    if snoozed.is_some() {
+                        return Err(de::Error::duplicate_field("snoozed"));
+                    }
+                    snoozed = Some(map.next_value()?);
+                }
+                TaskField::Waiting => {
+                    if waiting.is_some() {
+                        return Err(de::Error::duplicate_field("waiting"));
+                    }
+                    waiting = Some(map.next_value()?);
+                }
+                TaskField::Assignee => {
+                    if assignee.is_some() {
+                        return Err(de::Error::duplicate_field("assignee"));
+                    }
+                    assignee = Some(map.next_value()?);
+                }
+                TaskField::Priority => {
+                    if priority.is_some() {
+                        return Err(de::Error::duplicate_field("priority"));
+                    }
+                    priority = Some(map.next_value()?);
+                }
+                TaskField::Due => {
+                    if due.is_some() {
+                        return Err(de::Error::duplicate_field("due"));
+                    }
+                    due = Some(map.next_value()?);
+                }
+                TaskField::Pinned => {
+                    if pinned.is_some() {
+                        return Err(de::Error::duplicate_field("pinned"));
+                    }
+                    pinned = Some(map.next_value()?);
+                }
+                TaskField::Hidden => {
+                    if hidden.is_some() {
+                        return Err(de::Error::duplicate_field("hidden"));
+                    }
+                    hidden = Some(map.next_value()?);
+                }
+                TaskField::Depends => {
+                    if depends.is_some() {
+                        return Err(de::Error::duplicate_field("depends"));
+                    }
+                    depends = Some(map.next_value()?);
+                }
+                TaskField::External => {
+                    if external.is_some() {
+                        return Err(de::Error::duplicate_field("external"));
+                    }
+                    external = Some(map.next_value()?);
+                }
+                TaskField::Attachments => {
+                    if attachments.is_some() {
+                        return Err(de::Error::duplicate_field("attachments"));
+                    }
+                    attachments = Some(map.next_value()?);
+                }
+                TaskField::Alias => {
+                    if alias.is_some() {
+                        return Err(de::Error::duplicate_field("alias"));
+                    }
+                    alias = Some(map.next_value()?);
+                }
+                TaskField::Intervals => {
+                    if intervals.is_some() {
+                        return Err(de::Error::duplicate_field("intervals"));
+                    }
+                    intervals = Some(map.next_value()?);
+                }
             }
         }
 
         let desc = description.ok_or_else(|| de::Error::missing_field("desc or name"))?;
         let index = index.ok_or_else(|| de::Error::missing_field("index"))?;
-        let completed = completed.ok_or_else(|| de::Error::missing_field("completed"))?;
+        // older versions only knew whether a task was completed
+        let status = match (status, completed) {
+            (Some(status), _) => status,
+            (None, Some(completed)) => Status::from_completion(completed),
+            (None, None) => return Err(de::Error::missing_field("status")),
+        };
 
         Ok(Task {
             desc,
             index,
-            completed,
+            // assigned when the project is loaded
+            id: id.unwrap_or_default(),
+            status,
+            closed,
+            note,
+            created,
+            intervals: intervals.unwrap_or_default(),
+            estimate,
+            pomodoros: pomodoros.unwrap_or_default(),
+            snoozed,
+            waiting,
+            assignee,
+            priority,
+            due,
+            pinned: pinned.unwrap_or_default(),
+            hidden: hidden.unwrap_or_default(),
+            depends: depends.unwrap_or_default(),
+            external: external.unwrap_or_default(),
+            attachments: attachments.unwrap_or_default(),
+            alias,
         })
     }
 }
@@ -188,3 +420,208 @@ impl<'de> Deserialize<'de> for Task {
         deserializer.deserialize_struct("Task", TASK_FIELDS, TaskVisitor)
     }
 }
+
+struct StatusVisitor;
+
+impl<'de> Visitor<'de> for StatusVisitor {
+    type Value = Status;
+
+    fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str("a status like 'pending', 'in-progress' or 'done'")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StatusVisitor)
+    }
+}
+
+struct PriorityVisitor;
+
+impl<'de> Visitor<'de> for PriorityVisitor {
+    type Value = Priority;
+
+    fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str("a priority like 'low', 'medium' or 'high'")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(PriorityVisitor)
+    }
+}
+
+const INTERVAL_FIELDS: &[&str] = &["start", "end"];
+
+enum IntervalField {
+    Start,
+    End,
+}
+
+struct IntervalFieldVisitor;
+
+impl<'de> Visitor<'de> for IntervalFieldVisitor {
+    type Value = IntervalField;
+
+    fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str("'start' or 'end'")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v {
+            "start" => Ok(IntervalField::Start),
+            "end" => Ok(IntervalField::End),
+            _ => Err(de::Error::unknown_field(v, INTERVAL_FIELDS)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IntervalField {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(IntervalFieldVisitor)
+    }
+}
+
+struct IntervalVisitor;
+
+impl<'de> Visitor<'de> for IntervalVisitor {
+    type Value = Interval;
+
+    fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.write_str("struct Interval")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut start = None;
+        let mut end = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                IntervalField::Start => {
+                    if start.is_some() {
+                        return Err(de::Error::duplicate_field("start"));
+                    }
+                    start = Some(map.next_value()?);
+                }
+                IntervalField::End => {
+                    if end.is_some() {
+                        return Err(de::Error::duplicate_field("end"));
+                    }
+                    end = Some(map.next_value()?);
+                }
+            }
+        }
+
+        let start = start.ok_or_else(|| de::Error::missing_field("start"))?;
+
+        Ok(Interval { start, end })
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Interval", INTERVAL_FIELDS, IntervalVisitor)
+    }
+}
+
+#[test]
+fn intervals_round_trip() {
+    let mut task = Task::new("work", false, 0);
+    task.intervals = vec![
+        Interval {
+            start: 10,
+            end: Some(20),
+        },
+        Interval {
+            start: 30,
+            end: None,
+        },
+    ];
+    let data = ProjectData {
+        name: "test".to_string(),
+        tasks: vec![task],
+        lists: std::collections::BTreeMap::new(),
+    };
+
+    let parsed: ProjectData = toml::from_str(&toml::to_string(&data).unwrap()).unwrap();
+    assert_eq!(parsed.tasks[0].intervals, data.tasks[0].intervals);
+}
+
+#[test]
+fn status_from_completion() {
+    let data: ProjectData = toml::from_str(
+        "name = 'old'
+
+        [[tasks]]
+        desc = 'done before statuses existed'
+        completed = true
+        index = 0
+
+        [[tasks]]
+        desc = 'blocked'
+        status = 'blocked'
+        index = 1",
+    )
+    .unwrap();
+
+    assert_eq!(data.tasks[0].status, Status::Done);
+    assert_eq!(data.tasks[1].status, Status::Blocked);
+    assert!(toml::from_str::<ProjectData>("name = 'x'\n[[tasks]]\ndesc = ''\nindex = 0").is_err());
+}
+
+#[test]
+fn priority_and_due_round_trip() {
+    let mut task = Task::new("ship it", false, 0);
+    task.priority = Some(Priority::High);
+    task.due = Some(1_700_000_000);
+    let data = ProjectData {
+        name: "test".to_string(),
+        tasks: vec![task],
+        lists: std::collections::BTreeMap::new(),
+    };
+
+    let toml: ProjectData = toml::from_str(&toml::to_string(&data).unwrap()).unwrap();
+    let binary: ProjectData =
+        rmp_serde::from_slice(&rmp_serde::to_vec_named(&data).unwrap()).unwrap();
+    for parsed in [toml, binary] {
+        assert_eq!(parsed.tasks[0].priority, Some(Priority::High));
+        assert_eq!(parsed.tasks[0].due, Some(1_700_000_000));
+    }
+    assert!(toml::from_str::<ProjectData>(
+        "name = 'x'\n[[tasks]]\ndesc = ''\nindex = 0\nstatus = 'pending'\npriority = 'urgent'"
+    )
+    .is_err());
+}
@@ -0,0 +1,76 @@
+use colored::Colorize;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new`, each marked as kept, removed or added,
+/// going by their longest common subsequence
+fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// Renders the changes from `old` to `new` like `diff -u` does, removed lines
+/// in red and added ones in green. Empty if nothing changed.
+pub fn render(old: &str, new: &str) -> String {
+    let lines = diff(old, new);
+    if lines.iter().all(|line| matches!(line, Line::Same(_))) {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    for line in lines {
+        let line = match line {
+            Line::Same(text) => format!(" {text}"),
+            Line::Removed(text) => format!("-{text}").red().to_string(),
+            Line::Added(text) => format!("+{text}").green().to_string(),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn line_diff() {
+    use Line::*;
+    assert_eq!(
+        diff("a\nb\nc\nd", "a\nc\nd\ne"),
+        vec![Same("a"), Removed("b"), Same("c"), Same("d"), Added("e")]
+    );
+    assert_eq!(diff("a", ""), vec![Removed("a")]);
+    assert!(render("same\ntext", "same\ntext").is_empty());
+}
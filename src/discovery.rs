@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{ignore::Ignore, Format};
+
+/// How many directories below the starting one are searched for projects
+pub const MAX_DEPTH: usize = 8;
+
+struct Walk {
+    /// Directories waiting to be read, with their depth and the ignore
+    /// patterns of their parents
+    queue: Vec<(PathBuf, usize, Arc<Ignore>)>,
+    /// How many directories are being read right now. Their subdirectories
+    /// may still end up in the queue, so an empty queue alone doesn't mean
+    /// the walk is over.
+    busy: usize,
+    found: Vec<PathBuf>,
+}
+
+/// Finds the project files in the directory and below, at most `max_depth`
+/// levels down.
+///
+/// Directories are read on all cores, so huge trees don't take ages.
+/// Symlinks are not followed and unreadable directories are skipped, as are
+/// the ones matching `ignore` or the patterns in `.gitignore` and
+/// `.tutelignore` files.
+pub fn find_projects(root: &Path, max_depth: usize, ignore: &[String]) -> Vec<PathBuf> {
+    let walk = Mutex::new(Walk {
+        queue: vec![(root.to_path_buf(), 0, Arc::new(Ignore::new(root, ignore)))],
+        busy: 0,
+        found: Vec::new(),
+    });
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let next = {
+                    let mut walk = walk.lock().unwrap_or_else(|e| e.into_inner());
+                    match walk.queue.pop() {
+                        Some(next) => {
+                            walk.busy += 1;
+                            next
+                        }
+                        None if walk.busy == 0 => break,
+                        None => {
+                            drop(walk);
+                            thread::yield_now();
+                            continue;
+                        }
+                    }
+                };
+
+                let (dir, depth, ignore) = next;
+                let ignore = ignore.enter(&dir);
+                let (subdirs, found) = read_dir(&dir, &ignore);
+
+                let mut walk = walk.lock().unwrap_or_else(|e| e.into_inner());
+                walk.found.extend(found);
+                if depth < max_depth {
+                    walk.queue.extend(
+                        subdirs
+                            .into_iter()
+                            .map(|dir| (dir, depth + 1, ignore.clone())),
+                    );
+                }
+                walk.busy -= 1;
+            });
+        }
+    });
+
+    let mut found = walk.into_inner().unwrap_or_else(|e| e.into_inner()).found;
+    found.sort();
+    found
+}
+
+/// The subdirectories that aren't ignored and the project files in the directory
+fn read_dir(dir: &Path, ignore: &Ignore) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut subdirs = Vec::new();
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (subdirs, found);
+    };
+
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            // never interesting and rarely in a .gitignore
+            if entry.file_name() != ".git" && !ignore.is_ignored(&entry.path(), true) {
+                subdirs.push(entry.path());
+            }
+        } else if file_type.is_file()
+            && Format::ALL.iter().any(|format| {
+                entry.file_name() == format.file_name() && format.is_project(&entry.path())
+            })
+        {
+            found.push(entry.path());
+        }
+    }
+    (subdirs, found)
+}
+
+#[test]
+fn finds_nested_projects() {
+    let root = tempfile::tempdir().unwrap();
+    let deep = root.path().join("a/b/c");
+    fs::create_dir_all(&deep).unwrap();
+    fs::create_dir_all(root.path().join("d")).unwrap();
+    for dir in [root.path(), &root.path().join("a/b"), &deep] {
+        fs::write(dir.join(".tutel.toml"), "").unwrap();
+    }
+    fs::write(root.path().join("d/TODO.md"), "# d\n<!-- tutel -->\n").unwrap();
+    // somebody's own checklist
+    fs::write(root.path().join("a/TODO.md"), "- [ ] not a project\n").unwrap();
+
+    let found = find_projects(root.path(), MAX_DEPTH, &[]);
+    assert_eq!(found.len(), 4);
+    assert_eq!(find_projects(root.path(), 2, &[]).len(), 3);
+    assert_eq!(find_projects(root.path(), 0, &[]).len(), 1);
+
+    assert_eq!(
+        find_projects(root.path(), MAX_DEPTH, &["d".to_string()]).len(),
+        3
+    );
+    fs::write(root.path().join("a/.tutelignore"), "b/c\n").unwrap();
+    assert_eq!(find_projects(root.path(), MAX_DEPTH, &[]).len(), 3);
+}
@@ -0,0 +1,1399 @@
+use anyhow::bail;
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    date::{self, DateFormat},
+    emoji, links,
+    markdown::{self, Span},
+    query::Query,
+    registry,
+    rules::Rule,
+    theme::{Style, Theme},
+    tr,
+    urgency::Urgency,
+    Priority, Project, Status, Task,
+};
+
+/// The markers used to show the status of a task, or whether a project is completed
+#[derive(Debug, Clone)]
+pub struct Symbols {
+    pub done: String,
+    pub pending: String,
+    pub in_progress: String,
+    pub blocked: String,
+    pub cancelled: String,
+    /// Replaces the separator of the task that is being worked on
+    pub active: String,
+    /// Marks pending tasks that wait on something else
+    pub waiting: String,
+    /// Follows the marker of pinned tasks
+    pub pinned: String,
+    /// Precedes the number of completed tasks in prompts and status lines
+    pub prompt_done: String,
+    /// Precedes the number of pending tasks in prompts and status lines
+    pub prompt_pending: String,
+}
+
+impl Symbols {
+    /// Returns the marker matching the given completion state
+    pub fn marker(&self, completed: bool) -> &str {
+        if completed {
+            &self.done
+        } else {
+            &self.pending
+        }
+    }
+
+    /// Returns the marker matching the status of a task. Pending tasks that
+    /// are waiting on something get a marker of their own.
+    pub fn task_marker(&self, task: &Task) -> &str {
+        match task.status {
+            Status::Pending if task.is_waiting() => &self.waiting,
+            Status::Pending => &self.pending,
+            Status::InProgress => &self.in_progress,
+            Status::Blocked => &self.blocked,
+            Status::Done => &self.done,
+            Status::Cancelled => &self.cancelled,
+        }
+    }
+}
+
+impl Symbols {
+    /// Markers that are safe to use on any terminal
+    pub fn ascii() -> Self {
+        Self {
+            done: "[x]".to_string(),
+            pending: "[ ]".to_string(),
+            in_progress: "[>]".to_string(),
+            blocked: "[!]".to_string(),
+            cancelled: "[-]".to_string(),
+            active: ">".to_string(),
+            waiting: "[~]".to_string(),
+            pinned: "*".to_string(),
+            prompt_done: "+".to_string(),
+            prompt_pending: "-".to_string(),
+        }
+    }
+
+    /// Restricts the symbols to ascii characters. If any of them is not
+    /// ascii, all of them are replaced so they still look like a matching set.
+    #[must_use]
+    pub fn into_ascii(self) -> Self {
+        let symbols = [
+            &self.done,
+            &self.pending,
+            &self.in_progress,
+            &self.blocked,
+            &self.cancelled,
+            &self.active,
+            &self.waiting,
+            &self.pinned,
+            &self.prompt_done,
+            &self.prompt_pending,
+        ];
+        if symbols.iter().any(|s| !s.is_ascii()) {
+            Self::ascii()
+        } else {
+            self
+        }
+    }
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Self {
+            done: "[✓]".to_string(),
+            pending: "[X]".to_string(),
+            in_progress: "[>]".to_string(),
+            blocked: "[!]".to_string(),
+            cancelled: "[-]".to_string(),
+            active: "▶".to_string(),
+            waiting: "[~]".to_string(),
+            pinned: "★".to_string(),
+            prompt_done: "✓".to_string(),
+            prompt_pending: "✗".to_string(),
+        }
+    }
+}
+
+/// A column of the table layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Index,
+    Status,
+    Age,
+    Description,
+}
+
+impl Column {
+    pub const ALL: &'static [Self] = &[Self::Index, Self::Status, Self::Age, Self::Description];
+
+    const fn header(self) -> &'static str {
+        match self {
+            Self::Index => "index",
+            Self::Status => "status",
+            Self::Age => "age",
+            Self::Description => "description",
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.iter().find(|c| c.header() == s) {
+            Some(column) => Ok(*column),
+            None => bail!(tr!(
+                "unknown column: {s}. available are index, status, age and description",
+                s
+            )),
+        }
+    }
+}
+
+/// What `show --group-by` puts the tasks under headings by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Tag,
+    Status,
+    Priority,
+    /// Overdue, due today, within a week, later or not at all
+    Due,
+}
+
+impl GroupBy {
+    pub const ALL: &'static [Self] = &[Self::Tag, Self::Status, Self::Priority, Self::Due];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Tag => "tag",
+            Self::Status => "status",
+            Self::Priority => "priority",
+            Self::Due => "due",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.iter().find(|g| g.name() == s) {
+            Some(group_by) => Ok(*group_by),
+            None => bail!(tr!(
+                "can't group by {s}. available are tag, status, priority and due",
+                s
+            )),
+        }
+    }
+}
+
+/// How the tasks of a project are laid out
+#[derive(Debug, Clone, Default)]
+pub enum Layout {
+    /// One line per task
+    #[default]
+    List,
+    /// Aligned columns below a header
+    Table(Vec<Column>),
+}
+
+/// Everything that influences how projects and tasks are rendered
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    pub theme: Theme,
+    pub symbols: Symbols,
+    pub ascii: bool,
+    /// Wrap long descriptions to fit into this many columns
+    pub width: Option<usize>,
+    pub layout: Layout,
+    /// Turn urls into OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    /// Sort the tasks by urgency instead of by index
+    pub urgency: Option<Urgency>,
+    /// Coloring rules for descriptions, the first matching one wins
+    pub rules: Vec<Rule>,
+    /// List snoozed tasks as well
+    pub snoozed: bool,
+    /// List hidden tasks as well
+    pub hidden: bool,
+    /// Only list the tasks waiting on something
+    pub waiting: bool,
+    /// Leave out completed tasks
+    pub open: bool,
+    /// List at most this many tasks
+    pub limit: Option<usize>,
+    /// Mark the age of open tasks older than this many seconds
+    pub stale_after: Option<u64>,
+    /// Only list stale tasks
+    pub stale: bool,
+    /// Only list the tasks assigned to this person
+    pub assignee: Option<String>,
+    /// Only list the tasks mentioning this person
+    pub mentions: Option<String>,
+    /// Only list the tasks with this tag
+    pub tag: Option<String>,
+    /// Only list the tasks matching this query
+    pub query: Option<Query>,
+    /// List the tasks under a heading per group
+    pub group_by: Option<GroupBy>,
+    pub date_format: DateFormat,
+    /// Expand shortcodes like `:rocket:` in descriptions
+    pub emoji: bool,
+    /// Render bold, italics, code and links in descriptions and notes
+    pub markdown: bool,
+    /// Output for screen readers: status words instead of markers, no
+    /// alignment and no box drawing
+    pub plain: bool,
+    /// Stable tab separated lines for scripts, see [`porcelain`]
+    pub porcelain: bool,
+}
+
+impl DisplayOptions {
+    /// Restricts the output to ascii characters, see [`Symbols::into_ascii`]
+    #[must_use]
+    pub fn into_ascii(mut self) -> Self {
+        self.symbols = self.symbols.into_ascii();
+        self.ascii = true;
+        self
+    }
+
+    /// Output for screen readers. Everything that is only shown by color,
+    /// symbols or position is spelled out instead.
+    #[must_use]
+    pub fn into_plain(mut self) -> Self {
+        self = self.into_ascii();
+        self.plain = true;
+        self.layout = Layout::List;
+        self.width = None;
+        self
+    }
+
+    /// Output for scripts, one line per task in a format that doesn't change
+    #[must_use]
+    pub fn into_porcelain(mut self) -> Self {
+        self.porcelain = true;
+        self.layout = Layout::List;
+        self.width = None;
+        self
+    }
+
+    /// The status of a task in words, along with everything the markers of
+    /// the list would show
+    pub(crate) fn status_words(&self, task: &Task) -> String {
+        let mut words = vec![task.status.label()];
+        if task.is_waiting() {
+            words.push(tr("waiting"));
+        }
+        if task.is_active() {
+            words.push(tr("active"));
+        }
+        if task.pinned {
+            words.push(tr("pinned"));
+        }
+        if self.is_stale(task, date::now()) {
+            words.push(tr("stale"));
+        }
+        words.join(", ")
+    }
+
+    pub(crate) const fn separator(&self) -> &'static str {
+        if self.ascii {
+            "|"
+        } else {
+            "│"
+        }
+    }
+
+    fn marker(&self, completed: bool) -> colored::ColoredString {
+        let style = if completed {
+            &self.theme.completed
+        } else {
+            &self.theme.pending
+        };
+        style.paint(self.symbols.marker(completed))
+    }
+
+    fn is_stale(&self, task: &Task, now: u64) -> bool {
+        self.stale_after
+            .is_some_and(|after| task.is_stale(now, after))
+    }
+
+    /// Whether the task is listed, or left out because it is snoozed or hidden
+    pub(crate) fn lists(&self, task: &Task, now: u64) -> bool {
+        (self.hidden || !task.hidden) && (self.snoozed || !task.is_snoozed(now))
+    }
+
+    /// The style of the status of a task, see [`Symbols::task_marker`]
+    const fn status_style(&self, task: &Task) -> &Style {
+        match task.status {
+            Status::Pending if task.is_waiting() => &self.theme.waiting,
+            status => self.style_of(status),
+        }
+    }
+
+    pub(crate) const fn style_of(&self, status: Status) -> &Style {
+        match status {
+            Status::Pending => &self.theme.pending,
+            Status::InProgress => &self.theme.in_progress,
+            Status::Blocked => &self.theme.blocked,
+            Status::Done => &self.theme.completed,
+            Status::Cancelled => &self.theme.cancelled,
+        }
+    }
+
+    fn task_marker(&self, task: &Task) -> String {
+        let marker = self
+            .status_style(task)
+            .paint(self.symbols.task_marker(task));
+        if task.pinned {
+            format!("{marker}{} ", self.theme.pinned.paint(&self.symbols.pinned))
+        } else {
+            marker.to_string()
+        }
+    }
+
+    /// How wide the marker of a task is, see [`DisplayOptions::task_marker`]
+    fn task_marker_width(&self, task: &Task) -> usize {
+        let width = self.symbols.task_marker(task).width();
+        if task.pinned {
+            width + self.symbols.pinned.width() + 1
+        } else {
+            width
+        }
+    }
+
+    /// Paints the description of a task, using the style of the first
+    /// coloring rule matching the task
+    pub(crate) fn description(&self, text: &str, task: &Task) -> String {
+        self.rich_text(text, self.description_style(task))
+    }
+
+    /// Paints the description of a task like [`DisplayOptions::description`],
+    /// wrapped into lines of at most `width` columns if given
+    fn description_lines(&self, text: &str, task: &Task, width: Option<usize>) -> Vec<String> {
+        let style = self.description_style(task);
+        width.map_or_else(
+            || vec![self.rich_text(text, style)],
+            |width| {
+                wrap_fragments(self.fragments(text), width)
+                    .iter()
+                    .map(|line| self.paint_fragments(line, style))
+                    .collect()
+            },
+        )
+    }
+
+    fn description_style(&self, task: &Task) -> &Style {
+        let now = date::now();
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(task, now))
+            .map_or(&self.theme.description, |rule| &rule.style)
+    }
+
+    /// Paints text written by the user, expanding emoji shortcodes and
+    /// rendering markdown if enabled
+    pub(crate) fn rich_text(&self, text: &str, style: &Style) -> String {
+        self.paint_fragments(&self.fragments(text), style)
+    }
+
+    /// How many columns text written by the user takes up once rendered
+    fn rich_width(&self, text: &str) -> usize {
+        self.fragments(text)
+            .iter()
+            .map(|(_, text)| text.width())
+            .sum()
+    }
+
+    /// The text as it is shown, split into pieces that are painted alike
+    fn fragments(&self, text: &str) -> Vec<(Kind, String)> {
+        let text = if self.emoji {
+            emoji::expand(text)
+        } else {
+            text.into()
+        };
+
+        let spans = if self.markdown {
+            markdown::parse(&text)
+        } else {
+            vec![Span::Text(&text)]
+        };
+
+        let mut fragments = Vec::with_capacity(spans.len());
+        for span in spans {
+            match span {
+                Span::Text(text) if self.hyperlinks => {
+                    // urls become fragments of their own, so every piece of
+                    // one that is wrapped still links to all of it
+                    let mut rest = text;
+                    for url in links::find_urls(text) {
+                        if let Some(pos) = rest.find(url) {
+                            fragments.push((Kind::Text, rest[..pos].to_string()));
+                            fragments.push((Kind::Url(url.to_string()), url.to_string()));
+                            rest = &rest[pos + url.len()..];
+                        }
+                    }
+                    fragments.push((Kind::Text, rest.to_string()));
+                }
+                Span::Text(text) => fragments.push((Kind::Text, text.to_string())),
+                Span::Bold(text) => fragments.push((Kind::Bold, text.to_string())),
+                Span::Italic(text) => fragments.push((Kind::Italic, text.to_string())),
+                Span::Code(text) => fragments.push((Kind::Code, text.to_string())),
+                Span::Link { text, url } if self.hyperlinks => {
+                    fragments.push((Kind::Link(Some(url.to_string())), text.to_string()));
+                }
+                Span::Link { text, url } => {
+                    fragments.push((Kind::Link(None), text.to_string()));
+                    fragments.push((Kind::Text, format!(" ({url})")));
+                }
+            }
+        }
+        fragments
+    }
+
+    fn paint_fragments(&self, fragments: &[(Kind, String)], style: &Style) -> String {
+        fragments
+            .iter()
+            .map(|(kind, text)| match kind {
+                Kind::Text => style.paint(text).to_string(),
+                Kind::Bold => style.clone().bold().paint(text).to_string(),
+                Kind::Italic => style.clone().italic().paint(text).to_string(),
+                Kind::Code => self.theme.code.paint(text).to_string(),
+                Kind::Link(Some(url)) => {
+                    links::link(url, &style.clone().underline().paint(text).to_string())
+                }
+                Kind::Link(None) => style.clone().underline().paint(text).to_string(),
+                Kind::Url(url) => links::link(url, &style.paint(text).to_string()),
+            })
+            .collect()
+    }
+}
+
+/// How a piece of text written by the user is painted
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Bold,
+    Italic,
+    Code,
+    /// Underlined, and a hyperlink to the url if there is one
+    Link(Option<String>),
+    /// (A piece of) a url in the text, linking to all of it
+    Url(String),
+}
+
+impl Project {
+    /// Returns a wrapper that renders this project using the given options
+    pub const fn render<'a>(&'a self, options: &'a DisplayOptions) -> Rendered<'a, Self> {
+        Rendered {
+            inner: self,
+            options,
+        }
+    }
+}
+
+impl Task {
+    /// Returns a wrapper that renders this task using the given options
+    pub const fn render<'a>(&'a self, options: &'a DisplayOptions) -> Rendered<'a, Self> {
+        Rendered {
+            inner: self,
+            options,
+        }
+    }
+}
+
+impl Task {
+    /// Returns a wrapper that renders everything known about this task
+    pub const fn details<'a>(&'a self, options: &'a DisplayOptions) -> Details<'a> {
+        Details {
+            task: self,
+            options,
+        }
+    }
+}
+
+/// Renders the wrapped value using the given display options
+pub struct Rendered<'a, T> {
+    inner: &'a T,
+    options: &'a DisplayOptions,
+}
+
+impl Display for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&DisplayOptions::default()))
+    }
+}
+
+impl Display for Rendered<'_, Project> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let project = self.inner;
+        let options = self.options;
+        let theme = &options.theme;
+
+        let completed = project.data.tasks.iter().all(Task::is_completed);
+
+        if options.porcelain {
+            // scripts know which project they asked for
+        } else if options.plain {
+            let done = project
+                .data
+                .tasks
+                .iter()
+                .filter(|t| t.is_completed())
+                .count();
+            let total = project.data.tasks.len();
+            write!(
+                f,
+                "{}: {done} {} {total} {}",
+                project.data.name,
+                tr("of"),
+                tr("done")
+            )?;
+            if project.steps > 0 {
+                write!(f, ", {} {}", project.steps, tr("directories up"))?;
+            }
+        } else {
+            let steps_counter = if project.steps == 0 {
+                String::new()
+            } else {
+                theme
+                    .steps
+                    .paint(&format!(" [-{}]", project.steps))
+                    .to_string()
+            };
+
+            write!(
+                f,
+                "{}{steps_counter} {}",
+                options.marker(completed),
+                theme.name.paint(&project.data.name)
+            )?;
+        }
+
+        if project.data.tasks.is_empty() && !options.porcelain {
+            return write!(f, "\n{}", tr("[empty]"));
+        }
+
+        let now = date::now();
+        let (mut tasks, left_out): (Vec<&Task>, Vec<&Task>) = project
+            .data
+            .tasks
+            .iter()
+            .filter(|t| !options.waiting || t.is_waiting())
+            .filter(|t| !options.open || !t.is_completed())
+            .filter(|t| !options.stale || options.is_stale(t, now))
+            .filter(|t| options.assignee.is_none() || t.assignee == options.assignee)
+            .filter(|t| {
+                options
+                    .mentions
+                    .as_ref()
+                    .is_none_or(|m| t.mentions_person(m))
+            })
+            .filter(|t| options.tag.as_ref().is_none_or(|tag| t.has_tag(tag)))
+            .filter(|t| options.query.as_ref().is_none_or(|q| q.matches(t, now)))
+            .partition(|t| options.lists(t, now));
+        if let Some(urgency) = &options.urgency {
+            urgency.sort(&mut tasks, now);
+        }
+        // the sort is stable, so this keeps any other order among pinned tasks
+        tasks.sort_by_key(|t| !t.pinned);
+
+        let mut more = 0;
+        if let Some(limit) = options.limit {
+            more = tasks.len().saturating_sub(limit);
+            tasks.truncate(limit);
+        }
+
+        if options.porcelain {
+            for (n, t) in tasks.iter().enumerate() {
+                if n > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{}", porcelain(t))?;
+            }
+            return Ok(());
+        }
+
+        let groups = options.group_by.map_or_else(
+            || vec![(String::new(), tasks.clone())],
+            |group_by| groups(&tasks, group_by, date::now()),
+        );
+        for (heading, tasks) in &groups {
+            if options.group_by.is_some() {
+                let heading = format!("{heading} ({})", tasks.len());
+                write!(f, "\n{}", theme.header.paint(&heading))?;
+            }
+            match &options.layout {
+                // aligned columns are hard to follow with a screen reader
+                Layout::Table(columns) if !options.plain => {
+                    write_table(f, tasks, columns, options)?;
+                }
+                _ => {
+                    for t in tasks {
+                        write!(f, "\n{}", t.render(options))?;
+                    }
+                }
+            }
+        }
+
+        // the estimated effort of everything that is left to do
+        if tasks.iter().any(|t| t.estimate.is_some()) {
+            let remaining = tasks.iter().filter_map(|t| t.remaining(now)).sum();
+            write!(
+                f,
+                "\n{} {} {}",
+                theme.header.paint(tr("estimated")),
+                date::format_duration(remaining),
+                tr("left")
+            )?;
+        }
+
+        // hidden tasks that are snoozed as well only count as hidden
+        let hidden = left_out
+            .iter()
+            .filter(|t| t.hidden && !options.hidden)
+            .count();
+        let snoozed = left_out.len() - hidden;
+        for (label, count) in [("more", more), ("snoozed", snoozed), ("hidden", hidden)] {
+            let count = match count {
+                0 => continue,
+                1 => tr("1 task").to_string(),
+                n => tr!("{n} tasks", n),
+            };
+            write!(f, "\n{} {}", theme.header.paint(&count), tr(label))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The tasks under the heading of each group that has any, in the order of
+/// the groups. A task with several tags is listed under each of them, the
+/// ones without come last.
+fn groups<'a>(tasks: &[&'a Task], group_by: GroupBy, now: u64) -> Vec<(String, Vec<&'a Task>)> {
+    // the groups in order, and which of them a task belongs to
+    let buckets = |labels: &[&'static str], bucket: &dyn Fn(&Task) -> usize| {
+        labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let tasks = tasks.iter().filter(|t| bucket(t) == i).copied();
+                (tr(label).to_string(), tasks.collect::<Vec<_>>())
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect()
+    };
+    match group_by {
+        GroupBy::Priority => buckets(
+            &["high", "medium", "low", "no priority"],
+            &|task| match task.priority {
+                Some(Priority::High) => 0,
+                Some(Priority::Medium) => 1,
+                Some(Priority::Low) => 2,
+                None => 3,
+            },
+        ),
+        GroupBy::Due => {
+            let tomorrow = date::next_midnight(now).unwrap_or(now);
+            let week = tomorrow + 6 * 24 * 60 * 60;
+            buckets(
+                &[
+                    "past due",
+                    "due today",
+                    "due within a week",
+                    "due later",
+                    "no due date",
+                ],
+                &|task| match task.due {
+                    Some(due) if due <= now => 0,
+                    Some(due) if due < tomorrow => 1,
+                    Some(due) if due < week => 2,
+                    Some(_) => 3,
+                    None => 4,
+                },
+            )
+        }
+        GroupBy::Status => Status::ALL
+            .iter()
+            .map(|status| {
+                let tasks = tasks.iter().filter(|t| t.status == *status).copied();
+                (status.label().to_string(), tasks.collect::<Vec<_>>())
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect(),
+        GroupBy::Tag => {
+            let mut tagged: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+            let mut untagged = Vec::new();
+            for task in tasks {
+                let tags = task.tags();
+                if tags.is_empty() {
+                    untagged.push(*task);
+                }
+                for tag in tags {
+                    let group = tagged
+                        .entry(format!("#{}", tag.to_lowercase()))
+                        .or_default();
+                    // the same tag twice in a description
+                    if group.last().is_none_or(|last| last.index != task.index) {
+                        group.push(task);
+                    }
+                }
+            }
+            let untagged = (!untagged.is_empty()).then(|| (tr("untagged").to_string(), untagged));
+            tagged.into_iter().chain(untagged).collect()
+        }
+    }
+}
+
+fn write_table(
+    f: &mut std::fmt::Formatter<'_>,
+    tasks: &[&Task],
+    columns: &[Column],
+    options: &DisplayOptions,
+) -> std::fmt::Result {
+    let theme = &options.theme;
+
+    let cell = |task: &Task, column: Column| match column {
+        Column::Index => format!("{:03}", task.index),
+        Column::Status if task.pinned => {
+            format!(
+                "{}{}",
+                options.symbols.task_marker(task),
+                options.symbols.pinned
+            )
+        }
+        Column::Status => options.symbols.task_marker(task).to_string(),
+        Column::Age => task.age().map_or_else(|| "-".to_string(), date::format_age),
+        Column::Description => task.desc.clone(),
+    };
+
+    let rows: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|t| columns.iter().map(|c| cell(t, *c)).collect())
+        .collect();
+
+    // descriptions take up less room than their markup
+    let cell_width = |column: Column, text: &str| {
+        if column == Column::Description {
+            options.rich_width(text)
+        } else {
+            text.width()
+        }
+    };
+
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.header().width()).collect();
+    for row in &rows {
+        for ((width, cell), column) in widths.iter_mut().zip(row).zip(columns) {
+            *width = (*width).max(cell_width(*column, cell));
+        }
+    }
+
+    // the last column isn't padded, a description in there is wrapped instead
+    let last = columns.len().saturating_sub(1);
+    let offset: usize = widths[..last].iter().map(|w| w + 2).sum();
+
+    writeln!(f)?;
+    for (i, column) in columns.iter().enumerate() {
+        let header = column.header();
+        write!(f, "{}", theme.header.paint(header))?;
+        if i < last {
+            write!(f, "{:1$}", "", widths[i] - header.width() + 2)?;
+        }
+    }
+
+    for (task, row) in tasks.iter().zip(&rows) {
+        writeln!(f)?;
+        for (i, (column, text)) in columns.iter().zip(row).enumerate() {
+            let style = match column {
+                Column::Index if task.is_active() => &theme.active,
+                Column::Index => &theme.index,
+                Column::Status => options.status_style(task),
+                Column::Age if options.is_stale(task, date::now()) => &theme.stale,
+                Column::Age => &theme.age,
+                Column::Description => &theme.description,
+            };
+
+            let paint = |text: &str| {
+                if *column == Column::Description {
+                    options.description(text, task)
+                } else {
+                    style.paint(text).to_string()
+                }
+            };
+
+            if i < last {
+                write!(f, "{}", paint(text))?;
+                write!(f, "{:1$}", "", widths[i] - cell_width(*column, text) + 2)?;
+                continue;
+            }
+
+            let lines = if *column == Column::Description {
+                let width = options
+                    .width
+                    .filter(|width| *width > offset)
+                    .map(|width| width - offset);
+                options.description_lines(text, task, width)
+            } else {
+                vec![paint(text)]
+            };
+            for (n, line) in lines.iter().enumerate() {
+                if n > 0 {
+                    write!(f, "\n{:offset$}", "")?;
+                }
+                write!(f, "{line}")?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&DisplayOptions::default()))
+    }
+}
+
+impl Display for Rendered<'_, Task> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let task = self.inner;
+        let options = self.options;
+        let theme = &options.theme;
+
+        if options.plain {
+            return write!(
+                f,
+                "{} {}, {}: {}",
+                tr("task"),
+                task.index,
+                options.status_words(task),
+                options.description(&task.desc, task)
+            );
+        }
+
+        let index = format!("{:03}", task.index);
+        let separator = options.separator();
+        let first_separator = if task.is_active() {
+            theme.active.paint(&options.symbols.active)
+        } else {
+            theme.separator.paint(separator)
+        };
+        write!(
+            f,
+            "{} {} {}",
+            theme.index.paint(&index),
+            first_separator,
+            options.task_marker(task)
+        )?;
+
+        // continuation lines are indented to start below the description
+        let marker_width = options.task_marker_width(task);
+        let indent = index.width() + 1 + separator.width() + 1 + marker_width;
+
+        // the age of stale tasks follows the description
+        let age = task
+            .age()
+            .filter(|_| options.is_stale(task, date::now()))
+            .map(|age| format!(" {}", date::format_age(age)));
+        let age_width = age.as_ref().map_or(0, |age| age.width());
+
+        let width = options
+            .width
+            .filter(|width| *width > indent + age_width)
+            .map(|width| width - indent - age_width);
+        let lines = options.description_lines(&task.desc, task, width);
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                write!(
+                    f,
+                    "\n{:index_width$} {} {:marker_width$}",
+                    "",
+                    theme.separator.paint(separator),
+                    "",
+                    index_width = index.width(),
+                )?;
+            }
+            write!(f, "{line}")?;
+        }
+
+        if let Some(age) = age {
+            write!(f, "{}", theme.stale.paint(&age))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders all fields of a task, one per line
+pub struct Details<'a> {
+    task: &'a Task,
+    options: &'a DisplayOptions,
+}
+
+impl Details<'_> {
+    const KEYS: &'static [&'static str] = &[
+        "description",
+        "status",
+        "id",
+        "alias",
+        "created",
+        "closed",
+        "note",
+        "depends on",
+        "attachments",
+        "estimate",
+        "tracked",
+        "snoozed",
+        "assignee",
+        "priority",
+        "due",
+        "mentions",
+        "pinned",
+        "hidden",
+        "pomodoros",
+        "urgency",
+    ];
+
+    /// A point in the past, along with how long ago it was unless the date
+    /// format says so already
+    fn past(&self, timestamp: u64) -> String {
+        let now = date::now();
+        let format = &self.options.date_format;
+        let formatted = format.format(timestamp, now);
+        if *format == DateFormat::Relative {
+            formatted
+        } else {
+            let ago = date::format_age(now.saturating_sub(timestamp));
+            tr!("{formatted} ({ago} ago)", formatted, ago)
+        }
+    }
+
+    /// Width of the longest field name, in the language of the user
+    fn key_width() -> usize {
+        Self::KEYS
+            .iter()
+            .map(|key| tr(key).width())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn field(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        key: &'static str,
+        value: impl Display,
+    ) -> std::fmt::Result {
+        let key = tr(key);
+        if self.options.plain {
+            return write!(f, "\n{key}: {value}");
+        }
+        write!(
+            f,
+            "\n  {}{:2$}  {value}",
+            self.options.theme.header.paint(key),
+            "",
+            Self::key_width().saturating_sub(key.width())
+        )
+    }
+}
+
+impl Display for Details<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let task = self.task;
+        let options = self.options;
+        let theme = &options.theme;
+
+        if options.plain {
+            write!(f, "{} {}", tr("task"), task.index)?;
+        } else {
+            write!(
+                f,
+                "{} {}",
+                theme.index.paint(&format!("{:03}", task.index)),
+                options.task_marker(task)
+            )?;
+        }
+
+        let indent = 2 + Self::key_width() + 2;
+        let width = options
+            .width
+            .filter(|width| !options.plain && *width > indent)
+            .map(|width| width - indent);
+        let desc = options
+            .description_lines(&task.desc, task, width)
+            .join(&format!("\n{:indent$}", ""));
+        self.field(f, "description", desc)?;
+
+        let style = options.status_style(task);
+        let status = match &task.waiting {
+            Some(on) if task.status == Status::Pending => {
+                let waiting = style.paint(tr("waiting"));
+                if on.is_empty() {
+                    waiting.to_string()
+                } else {
+                    tr!("{waiting} on {on}", waiting, on)
+                }
+            }
+            Some(on) if task.is_waiting() && !on.is_empty() => {
+                tr!(
+                    "{status}, waiting on {on}",
+                    status = style.paint(task.status.label()),
+                    on
+                )
+            }
+            _ => style.paint(task.status.label()).to_string(),
+        };
+        self.field(f, "status", status)?;
+        self.field(f, "id", &task.id)?;
+        if let Some(alias) = &task.alias {
+            self.field(f, "alias", alias)?;
+        }
+
+        match task.created {
+            Some(created) => self.field(f, "created", self.past(created))?,
+            _ => self.field(f, "created", tr("unknown"))?,
+        }
+
+        if let Some(closed) = task.closed.filter(|_| task.is_completed()) {
+            self.field(f, "closed", self.past(closed))?;
+        }
+
+        if let Some(note) = &task.note {
+            self.field(f, "note", options.rich_text(note, &theme.description))?;
+        }
+
+        if !task.depends.is_empty() || !task.external.is_empty() {
+            let mut depends: Vec<String> = task.depends.iter().map(|d| format!("{d:03}")).collect();
+            // looked up in the registry, the other project may be gone by now
+            for reference in &task.external {
+                let resolved = registry::resolve(reference).ok();
+                let other = resolved
+                    .as_ref()
+                    .and_then(|(p, index)| p.get_task(*index).ok());
+                depends.push(other.map_or_else(
+                    || tr!("{reference} (unknown)", reference),
+                    |other| {
+                        let status = options.status_style(other).paint(other.status.label());
+                        format!("{reference} ({status}) {}", other.desc)
+                    },
+                ));
+            }
+            self.field(f, "depends on", depends.join(", "))?;
+        }
+
+        if !task.attachments.is_empty() {
+            self.field(f, "attachments", task.attachments.join(", "))?;
+        }
+
+        if let Some(estimate) = task.estimate {
+            self.field(f, "estimate", date::format_duration(estimate))?;
+        }
+
+        if !task.intervals.is_empty() {
+            let tracked = date::format_duration(task.tracked(date::now()));
+            if task.is_active() {
+                self.field(
+                    f,
+                    "tracked",
+                    format!("{tracked} {}", theme.active.paint(tr("(active)"))),
+                )?;
+            } else {
+                self.field(f, "tracked", tracked)?;
+            }
+        }
+
+        if task.is_snoozed(date::now()) {
+            let until = task.snoozed.unwrap_or_default();
+            let until = options.date_format.format(until, date::now());
+            self.field(f, "snoozed", tr!("until {until}", until))?;
+        }
+
+        if let Some(assignee) = &task.assignee {
+            self.field(f, "assignee", assignee)?;
+        }
+
+        if let Some(priority) = task.priority {
+            self.field(f, "priority", priority.label())?;
+        }
+
+        if let Some(due) = task.due {
+            self.field(f, "due", options.date_format.format(due, date::now()))?;
+        }
+
+        let mentions = task.mentions();
+        if !mentions.is_empty() {
+            self.field(f, "mentions", mentions.join(", "))?;
+        }
+
+        if task.pinned {
+            self.field(f, "pinned", theme.pinned.paint(tr("yes")))?;
+        }
+
+        if task.hidden {
+            self.field(f, "hidden", tr("yes"))?;
+        }
+
+        if task.pomodoros > 0 {
+            self.field(f, "pomodoros", task.pomodoros)?;
+        }
+
+        if let Some(urgency) = &options.urgency {
+            if !task.is_completed() {
+                let score = urgency.score(task, date::now());
+                self.field(f, "urgency", format!("{score:.2}"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Greedily wraps text into lines that are at most `width` columns wide.
+/// Words that don't fit on a line of their own are split.
+#[cfg(test)]
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    wrap_fragments(vec![(Kind::Text, text.to_string())], width)
+        .into_iter()
+        .map(|line| line.into_iter().map(|(_, text)| text).collect())
+        .collect()
+}
+
+/// Wraps rendered text like [`wrap`] does, measuring only what is shown. The
+/// space between two words is painted like the text it was taken from.
+fn wrap_fragments(fragments: Vec<(Kind, String)>, width: usize) -> Vec<Vec<(Kind, String)>> {
+    fn push(line: &mut Vec<(Kind, String)>, kind: &Kind, text: &str) {
+        match line.last_mut() {
+            Some((last, line)) if last == kind => line.push_str(text),
+            _ => line.push((kind.clone(), text.to_string())),
+        }
+    }
+
+    // words made of the pieces between whitespace, with the kind of the
+    // whitespace before them
+    let mut words: Vec<(Kind, Vec<(Kind, &str)>)> = Vec::new();
+    let mut space = None;
+    for (kind, text) in &fragments {
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                space.get_or_insert_with(|| kind.clone());
+                rest = rest.trim_start();
+                continue;
+            }
+            let piece = (kind.clone(), &rest[..end]);
+            match (space.take(), words.last_mut()) {
+                (None, Some((_, pieces))) => pieces.push(piece),
+                (space, _) => words.push((space.unwrap_or(Kind::Text), vec![piece])),
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut line_width = 0;
+
+    for (space, pieces) in words {
+        let word_width: usize = pieces.iter().map(|(_, text)| text.width()).sum();
+
+        if line_width > 0 && line_width + 1 + word_width <= width {
+            push(&mut line, &space, " ");
+            for (kind, text) in &pieces {
+                push(&mut line, kind, text);
+            }
+            line_width += 1 + word_width;
+            continue;
+        }
+
+        if line_width > 0 {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        for (kind, text) in &pieces {
+            for c in text.chars() {
+                let char_width = c.width().unwrap_or(0);
+                if line_width > 0 && line_width + char_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                push(&mut line, kind, c.encode_utf8(&mut [0; 4]));
+                line_width += char_width;
+            }
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// A task as a line of tab separated fields for scripts
+///
+/// The fields are index, status, id, assignee and description. Statuses are
+/// never translated, tabs, newlines and backslashes
+/// in the text are escaped and new fields only ever get added at the end, so
+/// scripts can rely on this format.
+pub fn porcelain(task: &Task) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+    }
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        task.index,
+        task.status.name(),
+        task.id,
+        escape(task.assignee.as_deref().unwrap_or("")),
+        escape(&task.desc)
+    )
+}
+
+#[test]
+fn porcelain_escapes_separators() {
+    let mut task = Task::new("a\tb\nc\\d", false, 3);
+    task.id = "k3x9qa".to_string();
+    assert_eq!(porcelain(&task), "3\tpending\tk3x9qa\t\ta\\tb\\nc\\\\d");
+}
+
+#[test]
+fn group_by_tag() {
+    let tasks = [
+        Task::new("release #web #Docs", false, 0),
+        Task::new("plain", false, 1),
+        Task::new("write #docs #docs", true, 2),
+    ];
+    let tasks: Vec<&Task> = tasks.iter().collect();
+
+    let indices = |group_by| {
+        groups(&tasks, group_by, 0)
+            .into_iter()
+            .map(|(heading, tasks)| (heading, tasks.iter().map(|t| t.index).collect()))
+            .collect::<Vec<(String, Vec<usize>)>>()
+    };
+    let by_tag = indices(GroupBy::Tag);
+    assert_eq!(
+        by_tag,
+        [
+            ("#docs".to_string(), vec![0, 2]),
+            ("#web".to_string(), vec![0]),
+            ("untagged".to_string(), vec![1]),
+        ]
+    );
+    assert_eq!(groups(&tasks, GroupBy::Status, 0).len(), 2);
+}
+
+#[test]
+fn group_by_priority_and_due() {
+    let now = 1_700_000_000;
+    let task = |index, priority, due| Task {
+        priority,
+        due,
+        ..Task::new("", false, index)
+    };
+    let tasks = [
+        task(0, None, Some(now + 30 * 24 * 60 * 60)),
+        task(1, Some(Priority::Low), Some(now - 1)),
+        task(2, Some(Priority::High), None),
+        task(3, Some(Priority::High), Some(now + 2 * 24 * 60 * 60)),
+    ];
+    let tasks: Vec<&Task> = tasks.iter().collect();
+    let indices = |group_by| {
+        groups(&tasks, group_by, now)
+            .into_iter()
+            .map(|(heading, tasks)| (heading, tasks.iter().map(|t| t.index).collect()))
+            .collect::<Vec<(String, Vec<usize>)>>()
+    };
+
+    let heading = |text: &str| text.to_string();
+    assert_eq!(
+        indices(GroupBy::Priority),
+        [
+            (heading("high"), vec![2, 3]),
+            (heading("low"), vec![1]),
+            (heading("no priority"), vec![0]),
+        ]
+    );
+    assert_eq!(
+        indices(GroupBy::Due),
+        [
+            (heading("past due"), vec![1]),
+            (heading("due within a week"), vec![3]),
+            (heading("due later"), vec![0]),
+            (heading("no due date"), vec![2]),
+        ]
+    );
+}
+
+#[test]
+fn wrap_long_descriptions() {
+    assert_eq!(
+        wrap("buy some   milk and a supercalifragilistic cake", 10),
+        ["buy some", "milk and a", "supercalif", "ragilistic", "cake"]
+    );
+    assert_eq!(wrap("", 10), [""]);
+}
+
+#[test]
+fn wrap_rendered_markdown() {
+    let options = DisplayOptions {
+        emoji: true,
+        markdown: true,
+        ..DisplayOptions::default()
+    };
+    let lines = |text: &str, width: usize| {
+        wrap_fragments(options.fragments(text), width)
+            .into_iter()
+            .map(|line| line.into_iter().map(|(_, text)| text).collect::<String>())
+            .collect::<Vec<_>>()
+    };
+
+    // markers don't take up room, and spans keep their style across lines
+    assert_eq!(lines("**ab** cd :rocket:", 8), ["ab cd 🚀"]);
+    assert_eq!(
+        wrap_fragments(options.fragments("x **bold words**"), 6),
+        [
+            vec![
+                (Kind::Text, "x ".to_string()),
+                (Kind::Bold, "bold".to_string())
+            ],
+            vec![(Kind::Bold, "words".to_string())],
+        ]
+    );
+    assert_eq!(
+        options.rich_width("see [docs](x.org)"),
+        "see docs (x.org)".len()
+    );
+}
+
+#[test]
+fn wrap_urls_into_links() {
+    let options = DisplayOptions {
+        hyperlinks: true,
+        ..DisplayOptions::default()
+    };
+    let url = "https://example.com/a/b";
+    let lines = wrap_fragments(options.fragments(&format!("see {url}")), 12);
+    assert_eq!(lines.len(), 3);
+    for line in &lines[1..] {
+        assert!(
+            matches!(&line[..], [(Kind::Url(u), _)] if u == url),
+            "{:?}",
+            line
+        );
+    }
+    let painted = options.paint_fragments(&lines[2], &Style::default());
+    assert!(
+        painted.starts_with(&format!("\x1b]8;;{url}\x1b\\")),
+        "{}",
+        painted
+    );
+}
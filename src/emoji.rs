@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+
+/// The shortcodes that are expanded, a common subset of the ones GitHub and
+/// Slack know
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("bell", "🔔"),
+    ("book", "📖"),
+    ("boom", "💥"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("calendar", "📆"),
+    ("check", "✔️"),
+    ("clock", "🕒"),
+    ("coffee", "☕"),
+    ("construction", "🚧"),
+    ("email", "📧"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("gift", "🎁"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("hourglass", "⌛"),
+    ("house", "🏠"),
+    ("lock", "🔒"),
+    ("memo", "📝"),
+    ("money", "💰"),
+    ("package", "📦"),
+    ("pencil", "✏️"),
+    ("phone", "📞"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("recycle", "♻️"),
+    ("rocket", "🚀"),
+    ("shopping_cart", "🛒"),
+    ("smile", "😄"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("tada", "🎉"),
+    ("test_tube", "🧪"),
+    ("turtle", "🐢"),
+    ("warning", "⚠️"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+/// Replaces known shortcodes like `:rocket:` with their emoji. Unknown ones
+/// are left as they are.
+pub fn expand(text: &str) -> Cow<'_, str> {
+    if !text.contains(':') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        let after = &rest[start + 1..];
+        let emoji = after.find(':').and_then(|end| {
+            let code = &after[..end];
+            let (_, emoji) = SHORTCODES.iter().find(|(c, _)| *c == code)?;
+            Some((emoji, end))
+        });
+
+        match emoji {
+            Some((emoji, end)) => {
+                out.push_str(&rest[..start]);
+                out.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[..=start]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    Cow::Owned(out)
+}
+
+#[test]
+fn expand_shortcodes() {
+    assert_eq!(expand("ship it :rocket::tada:"), "ship it 🚀🎉");
+    assert_eq!(expand("at 10:30 :nope: :bug:"), "at 10:30 :nope: 🐛");
+    assert_eq!(expand("no codes"), "no codes");
+}
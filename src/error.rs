@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use crate::tr;
+
+/// Failures scripts may want to tell apart from everything else, each with
+/// an exit code of its own. They are usually wrapped in an `anyhow::Error`,
+/// use `downcast_ref` to get them back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// No project file in the directory or any of its parents
+    NoProject,
+    /// No task matches the index or id, which is given as `index 3` or
+    /// `id k3x9qa`
+    NoTask(String),
+    /// A file doesn't contain what it should, in English like the other
+    /// messages, it is translated when shown
+    Invalid(&'static str),
+    /// The project, published at this url, can only be looked at
+    ReadOnly(String),
+}
+
+impl Error {
+    /// 1 is left for every other failure
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::NoProject => 2,
+            Self::NoTask(_) => 3,
+            Self::Invalid(_) => 4,
+            Self::ReadOnly(_) => 5,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoProject => write!(f, "{}", tr("no project found")),
+            Self::NoTask(which) => write!(f, "{}", tr!("no task with {which}", which)),
+            Self::Invalid(what) => write!(f, "{}", tr(what)),
+            Self::ReadOnly(url) => write!(f, "{}", tr!("the project at {url} is read-only", url)),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[test]
+fn survives_context() {
+    use anyhow::Context;
+
+    let result: anyhow::Result<()> = Err(Error::NoTask("index 3".to_string()).into());
+    let e = result.context("while editing").unwrap_err();
+    assert_eq!(e.downcast_ref::<Error>().map(Error::exit_code), Some(3));
+
+    let e = "x = ["
+        .parse::<toml::Value>()
+        .context(Error::Invalid("invalid project file syntax"));
+    assert_eq!(
+        e.unwrap_err().downcast_ref::<Error>().map(Error::exit_code),
+        Some(4)
+    );
+}
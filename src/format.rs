@@ -0,0 +1,223 @@
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path, str::FromStr};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{checklist, data::ProjectData, tr, Error};
+
+/// How a project is stored on disk. Everything else only ever sees the
+/// loaded [`crate::Project`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Toml,
+    Yaml,
+    /// A markdown checklist, see [`checklist`]
+    Checklist,
+    /// MessagePack, a lot faster to load than text for huge lists
+    Binary,
+}
+
+impl Format {
+    /// In the order projects are looked for in a directory
+    pub const ALL: &'static [Self] = &[Self::Toml, Self::Yaml, Self::Checklist, Self::Binary];
+
+    /// The format of the project file, going by its extension
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md") => Self::Checklist,
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("msgpack") => Self::Binary,
+            _ => Self::Toml,
+        }
+    }
+
+    /// The name of a project file in this format
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Toml => crate::PROJECT_FILE_NAME,
+            Self::Yaml => ".tutel.yaml",
+            Self::Checklist => checklist::CHECKLIST_FILE_NAME,
+            Self::Binary => ".tutel.msgpack",
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Checklist => "markdown",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// Whether `path` is a project file in this format. Only checklists
+    /// carrying the [`checklist::MARKER`] are, other `TODO.md` files belong
+    /// to the user.
+    pub fn is_project(self, path: &Path) -> bool {
+        path.is_file() && (self != Self::Checklist || checklist::is_project(path))
+    }
+
+    /// Whether people can read and edit the file
+    pub const fn is_text(self) -> bool {
+        !matches!(self, Self::Binary)
+    }
+
+    /// Reads the project stored at `path` from its content
+    pub(crate) fn parse(self, content: &[u8], path: &Path) -> Result<ProjectData> {
+        if self == Self::Binary {
+            return rmp_serde::from_slice(content)
+                .context(Error::Invalid("invalid binary project file"));
+        }
+
+        let text =
+            std::str::from_utf8(content).context(Error::Invalid("project file is not utf-8"))?;
+        Ok(match self {
+            Self::Toml => {
+                toml::from_str(text).context(Error::Invalid("invalid project file syntax"))?
+            }
+            Self::Yaml => {
+                serde_yaml::from_str(text).context(Error::Invalid("invalid project file syntax"))?
+            }
+            Self::Checklist => {
+                let dir_name = path
+                    .parent()
+                    .and_then(|dir| dir.file_name())
+                    .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+                checklist::parse(text, &dir_name)
+            }
+            Self::Binary => unreachable!(),
+        })
+    }
+
+    /// The content of the project file at `path` holding the data
+    pub(crate) fn render(self, data: &ProjectData, path: &Path) -> Result<Vec<u8>> {
+        if self == Self::Binary {
+            // with field names, so older files still load when fields are added
+            return Ok(rmp_serde::to_vec_named(data)?);
+        }
+
+        Ok(match self {
+            Self::Toml => {
+                let serialized = toml::to_string_pretty(data)?;
+                match fs::read_to_string(path) {
+                    Ok(original) if original.contains('#') => {
+                        keep_comments(&original, &serialized).unwrap_or(serialized)
+                    }
+                    _ => serialized,
+                }
+            }
+            Self::Yaml => serde_yaml::to_string(data)?,
+            Self::Checklist => {
+                if !data.lists.is_empty() {
+                    bail!(tr("a checklist only holds one list, convert the project to keep several"));
+                }
+                if data.tasks.iter().any(|t| t.desc.contains('\n')) {
+                    bail!(tr("a checklist item is a single line, convert the project to keep longer descriptions"));
+                }
+                // the prose around the checklist is kept
+                let original = fs::read_to_string(path).unwrap_or_default();
+                checklist::render(&original, data)
+            }
+            Self::Binary => unreachable!(),
+        }
+        .into_bytes())
+    }
+}
+
+/// Carries the comments of the original toml over into the freshly
+/// serialized one. Comments above the name, above a task or one of its
+/// fields and at the end of a line stay where they were written, tasks are
+/// matched by id. `None` if either of them doesn't parse.
+fn keep_comments(original: &str, serialized: &str) -> Option<String> {
+    let old = original.parse::<DocumentMut>().ok()?;
+    let mut new = serialized.parse::<DocumentMut>().ok()?;
+
+    copy_decor(old.as_table(), new.as_table_mut());
+
+    let old_tasks = old.get("tasks").and_then(Item::as_array_of_tables);
+    let new_tasks = new.get_mut("tasks").and_then(Item::as_array_of_tables_mut);
+    if let (Some(old_tasks), Some(new_tasks)) = (old_tasks, new_tasks) {
+        let id = |task: &Table| task.get("id").and_then(Item::as_str).map(str::to_string);
+        for task in new_tasks.iter_mut() {
+            let Some(task_id) = id(task) else {
+                continue;
+            };
+            if let Some(old_task) = old_tasks.iter().find(|t| id(t).as_ref() == Some(&task_id)) {
+                *task.decor_mut() = old_task.decor().clone();
+                copy_decor(old_task, task);
+            }
+        }
+    }
+
+    new.set_trailing(old.trailing().clone());
+    Some(new.to_string())
+}
+
+/// Copies what surrounds the keys and values of one table, comments
+/// included, to the same keys of the other
+fn copy_decor(from: &Table, to: &mut Table) {
+    for (key, item) in from {
+        if let (Some(old), Some(mut new)) = (from.key(key), to.key_mut(key)) {
+            *new.leaf_decor_mut() = old.leaf_decor().clone();
+        }
+        if let (Some(old), Some(new)) = (
+            item.as_value(),
+            to.get_mut(key).and_then(Item::as_value_mut),
+        ) {
+            *new.decor_mut() = old.decor().clone();
+        }
+    }
+}
+
+#[test]
+fn toml_keeps_comments() {
+    let original = "# groceries for the week\nname = 'shop'\n\n# the important one\n[[tasks]]\ndesc = 'buy milk' # oat\nstatus = 'pending'\nindex = 0\nid = 'k3x9qa'\n\n[[tasks]]\ndesc = 'buy eggs'\nstatus = 'pending'\nindex = 1\nid = 'p2m7cc'\n# the end\n";
+    let path = Path::new(".tutel.toml");
+    let mut data = Format::Toml.parse(original.as_bytes(), path).unwrap();
+    data.tasks[0].set_status(crate::Status::Done);
+    data.tasks.remove(1);
+
+    let serialized = toml::to_string_pretty(&data).unwrap();
+    let kept = keep_comments(original, &serialized).unwrap();
+    assert!(kept.starts_with("# groceries for the week\nname = 'shop'\n"));
+    assert!(
+        kept.contains("# the important one\n[[tasks]]\ndesc = 'buy milk' # oat\nstatus = 'done'")
+    );
+    assert!(!kept.contains("eggs"));
+    assert!(kept.ends_with("# the end\n"));
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.iter().find(|f| f.name() == s) {
+            Some(format) => Ok(*format),
+            None => bail!(tr!("expected toml, yaml, markdown or binary, got {s}", s)),
+        }
+    }
+}
+
+#[test]
+fn yaml_and_binary_round_trip() {
+    let mut data = ProjectData {
+        name: "demo".to_string(),
+        tasks: vec![crate::Task::new("buy milk", false, 0)],
+        lists: [(
+            "work".to_string(),
+            vec![crate::Task::new("send invoice", false, 0)],
+        )]
+        .into(),
+    };
+    data.tasks[0].note = Some("from the corner shop".to_string());
+
+    for path in [".tutel.yaml", ".tutel.msgpack"] {
+        let path = Path::new(path);
+        let content = Format::of(path).render(&data, path).unwrap();
+        let parsed = Format::of(path).parse(&content, path).unwrap();
+        assert_eq!(parsed.name, "demo");
+        assert_eq!(parsed.tasks[0].desc, "buy milk");
+        assert_eq!(parsed.tasks[0].note, data.tasks[0].note);
+        assert_eq!(parsed.lists["work"][0].desc, "send invoice");
+    }
+}
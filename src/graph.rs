@@ -0,0 +1,116 @@
+use crate::{Project, Task};
+
+/// Renders the tasks and their dependencies in Graphviz' DOT language. Edges
+/// point from a task to the ones depending on it, completed tasks are grayed out.
+pub fn dot(project: &Project) -> String {
+    let mut out = format!("digraph \"{}\" {{\n", escape(&project.data.name));
+    out.push_str("  rankdir=LR;\n  node [shape=box];\n");
+
+    for task in &project.data.tasks {
+        let style = if task.is_completed() {
+            ", style=dashed, fontcolor=gray"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "  t{} [label=\"{}\"{style}];\n",
+            task.index,
+            label(task)
+        ));
+    }
+
+    for task in &project.data.tasks {
+        for dependency in &task.depends {
+            out.push_str(&format!("  t{dependency} -> t{};\n", task.index));
+        }
+        for reference in &task.external {
+            let reference = escape(reference);
+            out.push_str(&format!(
+                "  \"{reference}\" [style=dotted];\n  \"{reference}\" -> t{};\n",
+                task.index
+            ));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Renders the same graph as [`dot`] as a mermaid flowchart, which GitHub
+/// and GitLab render in markdown
+pub fn mermaid(project: &Project) -> String {
+    let mut out = String::from("flowchart LR\n");
+
+    for task in &project.data.tasks {
+        let desc = task.desc.replace('"', "#quot;");
+        out.push_str(&format!(
+            "    t{}[\"{:03} {desc}\"]\n",
+            task.index, task.index
+        ));
+    }
+
+    for task in &project.data.tasks {
+        for dependency in &task.depends {
+            out.push_str(&format!("    t{dependency} --> t{}\n", task.index));
+        }
+        for reference in &task.external {
+            let node = reference.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+            out.push_str(&format!(
+                "    {node}([\"{reference}\"]) -.-> t{}\n",
+                task.index
+            ));
+        }
+    }
+
+    let completed: Vec<String> = project
+        .data
+        .tasks
+        .iter()
+        .filter(|t| t.is_completed())
+        .map(|t| format!("t{}", t.index))
+        .collect();
+    if !completed.is_empty() {
+        out.push_str("    classDef completed stroke-dasharray: 5 5,color:gray\n");
+        out.push_str(&format!("    class {} completed\n", completed.join(",")));
+    }
+
+    out
+}
+
+fn label(task: &Task) -> String {
+    format!("{:03} {}", task.index, escape(&task.desc))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[test]
+fn render_dot() {
+    let mut project = Project::new("tutel.toml".into(), 0, "home".to_string());
+    project.add("buy \"paint\"".to_string(), true);
+    project.add("paint the fence".to_string(), false);
+    project.depend(1, 0, true).unwrap();
+
+    assert_eq!(
+        dot(&project),
+        "digraph \"home\" {\n  rankdir=LR;\n  node [shape=box];\n  \
+         t0 [label=\"000 buy \\\"paint\\\"\", style=dashed, fontcolor=gray];\n  \
+         t1 [label=\"001 paint the fence\"];\n  t0 -> t1;\n}\n"
+    );
+}
+
+#[test]
+fn render_mermaid() {
+    let mut project = Project::new("tutel.toml".into(), 0, "home".to_string());
+    project.add("buy \"paint\"".to_string(), true);
+    project.add("paint the fence".to_string(), false);
+    project.depend(1, 0, true).unwrap();
+
+    assert_eq!(
+        mermaid(&project),
+        "flowchart LR\n    t0[\"000 buy #quot;paint#quot;\"]\n    \
+         t1[\"001 paint the fence\"]\n    t0 --> t1\n    \
+         classDef completed stroke-dasharray: 5 5,color:gray\n    class t0 completed\n"
+    );
+}
@@ -0,0 +1,205 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{data::fnv, tr, ARCHIVE_FILE_NAME};
+
+/// The project files whose state before this run is already in the journal.
+/// Commands may write a project more than once, e.g. the archive and then the
+/// project file, but that is still a single step to undo.
+static RECORDED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// The project file and its archive as they are on disk, `None` if missing.
+/// Whole files are kept, so renumbered tasks and archive moves come back
+/// exactly as they were.
+struct Snapshot {
+    project: Option<Vec<u8>>,
+    archive: Option<Vec<u8>>,
+}
+
+impl Snapshot {
+    fn take(project_file: &Path) -> Self {
+        Self {
+            project: fs::read(project_file).ok(),
+            archive: fs::read(project_file.with_file_name(ARCHIVE_FILE_NAME)).ok(),
+        }
+    }
+
+    fn restore(&self, project_file: &Path) -> Result<()> {
+        let archive_file = project_file.with_file_name(ARCHIVE_FILE_NAME);
+        for (file, content) in [
+            (project_file, &self.project),
+            (&archive_file, &self.archive),
+        ] {
+            match content {
+                Some(content) => fs::write(file, content),
+                None if file.exists() => fs::remove_file(file),
+                None => Ok(()),
+            }
+            .with_context(|| tr!("unable to restore {file}", file = file.display()))?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        for (name, content) in [("project", &self.project), ("archive", &self.archive)] {
+            if let Some(content) = content {
+                fs::write(dir.join(name), content)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load(dir: &Path) -> Self {
+        Self {
+            project: fs::read(dir.join("project")).ok(),
+            archive: fs::read(dir.join("archive")).ok(),
+        }
+    }
+}
+
+/// The journal of a project: `undo` and `redo` stacks of snapshots, each in
+/// a directory named by its position
+fn journal(project_file: &Path) -> Option<PathBuf> {
+    // a transaction's changes belong to the project it is working on
+    let path = crate::original_path(project_file).canonicalize().ok()?;
+    let key = format!("{:016x}", fnv(&path.to_string_lossy()));
+    Some(crate::data_dir()?.join("history").join(key))
+}
+
+/// The positions on the stack, oldest first
+fn positions(stack: &Path) -> Vec<u64> {
+    let mut positions: Vec<u64> = fs::read_dir(stack)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    positions.sort_unstable();
+    positions
+}
+
+/// Puts the snapshot on top of the stack, dropping the oldest ones beyond
+/// `depth`
+fn push(stack: &Path, snapshot: &Snapshot, depth: usize) -> Result<()> {
+    let positions = positions(stack);
+    let next = positions.last().map_or(0, |last| last + 1);
+    snapshot.save(&stack.join(next.to_string()))?;
+
+    let excess = (positions.len() + 1).saturating_sub(depth);
+    for position in positions.iter().take(excess) {
+        fs::remove_dir_all(stack.join(position.to_string()))?;
+    }
+    Ok(())
+}
+
+fn pop(stack: &Path) -> Result<Option<Snapshot>> {
+    let Some(last) = positions(stack).pop() else {
+        return Ok(None);
+    };
+    let dir = stack.join(last.to_string());
+    let snapshot = Snapshot::load(&dir);
+    fs::remove_dir_all(dir)?;
+    Ok(Some(snapshot))
+}
+
+/// Notes the state of the project before it is changed for the first time
+/// during this run, keeping at most `depth` steps. A new change can't be
+/// redone on top of, so the redo stack is cleared.
+pub fn record(project_file: &Path, depth: usize) -> Result<()> {
+    if depth == 0 {
+        return Ok(());
+    }
+    {
+        let mut recorded = RECORDED.lock().unwrap_or_else(|e| e.into_inner());
+        if recorded.iter().any(|file| file == project_file) {
+            return Ok(());
+        }
+        recorded.push(project_file.to_path_buf());
+    }
+
+    let Some(journal) = journal(project_file) else {
+        return Ok(());
+    };
+    push(&journal.join("undo"), &Snapshot::take(project_file), depth)
+        .context(tr("unable to write the undo history"))?;
+    let _ = fs::remove_dir_all(journal.join("redo"));
+    Ok(())
+}
+
+/// Brings back the project as it was before the last change.
+///
+/// # Errors
+/// This function will return an error if there is nothing to undo or the
+/// files can't be written.
+pub fn undo(project_file: &Path, depth: usize) -> Result<()> {
+    step(project_file, "undo", "redo", depth)
+}
+
+/// Brings back the change that was undone last.
+///
+/// # Errors
+/// This function will return an error if there is nothing to redo or the
+/// files can't be written.
+pub fn redo(project_file: &Path, depth: usize) -> Result<()> {
+    step(project_file, "redo", "undo", depth)
+}
+
+/// Restores the snapshot on top of `from`, after putting the current state
+/// on top of `to`
+fn step(project_file: &Path, from: &str, to: &str, depth: usize) -> Result<()> {
+    let journal = journal(project_file).context(tr("no place for the undo history"))?;
+    let Some(snapshot) = pop(&journal.join(from))? else {
+        bail!(if from == "undo" {
+            tr("nothing to undo")
+        } else {
+            tr("nothing to redo")
+        });
+    };
+    push(
+        &journal.join(to),
+        &Snapshot::take(project_file),
+        depth.max(1),
+    )?;
+    snapshot.restore(project_file)
+}
+
+#[test]
+fn bounded_stack() {
+    let dir = tempfile::tempdir().unwrap();
+    let stack = dir.path().join("undo");
+    for i in 0..5u8 {
+        let snapshot = Snapshot {
+            project: Some(vec![i]),
+            archive: None,
+        };
+        push(&stack, &snapshot, 3).unwrap();
+    }
+
+    assert_eq!(positions(&stack), vec![2, 3, 4]);
+    let last = pop(&stack).unwrap().unwrap();
+    assert_eq!(last.project, Some(vec![4]));
+    assert_eq!(last.archive, None);
+    assert_eq!(positions(&stack), vec![2, 3]);
+}
+
+#[test]
+fn restores_missing_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let project_file = dir.path().join(".tutel.toml");
+    fs::write(&project_file, "before").unwrap();
+    let snapshot = Snapshot::take(&project_file);
+
+    fs::write(&project_file, "after").unwrap();
+    fs::write(dir.path().join(ARCHIVE_FILE_NAME), "archived").unwrap();
+    snapshot.restore(&project_file).unwrap();
+
+    assert_eq!(fs::read_to_string(&project_file).unwrap(), "before");
+    assert!(!dir.path().join(ARCHIVE_FILE_NAME).exists());
+}
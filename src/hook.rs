@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+use tutel::{tr, Config, Project};
+
+const BASH_HOOK: &str = include_str!("../res/tutel-hook.bash");
+const ZSH_HOOK: &str = include_str!("../res/tutel-hook.zsh");
+const FISH_HOOK: &str = include_str!("../res/tutel-hook.fish");
+
+/// The shells a hook exists for
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+/// Returns the snippet that runs `tutel hook --summary` whenever the
+/// directory changes
+pub fn script(shell: &str) -> Result<&'static str> {
+    Ok(match shell {
+        "bash" => BASH_HOOK,
+        "zsh" => ZSH_HOOK,
+        "fish" => FISH_HOOK,
+        _ => bail!(tr!(
+            "no hook for {shell}, only for {shells}",
+            shell,
+            shells = SHELLS.join(", ")
+        )),
+    })
+}
+
+/// One line like `demo: 3 pending`, or nothing if there is nothing to do
+pub fn summary(project: &Project, config: &Config) -> Option<String> {
+    let (_, pending) = crate::prompt::counts(project);
+    (pending > 0).then(|| {
+        format!(
+            "{}: {}",
+            config.theme.name.paint(&project.data.name),
+            config
+                .theme
+                .pending
+                .paint(&format!("{pending} {}", tr("pending")))
+        )
+    })
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, fmt::Display, fs, sync::OnceLock};
+
+/// Catalogs shipped with tutel, by language
+const BUILTIN: &[(&str, &str)] = &[("de", include_str!("../res/locale/de.toml"))];
+
+static CATALOG: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+
+/// Translates a user facing message into the language of the user. Messages
+/// are looked up by their english text, which is also what is returned if
+/// there is no translation.
+pub fn tr(text: &'static str) -> &'static str {
+    CATALOG.get_or_init(load).get(text).copied().unwrap_or(text)
+}
+
+/// Translates a message and fills in its `{name}` placeholders.
+///
+/// Translations may move the placeholders around. The values are given by
+/// name, like `tr!("task {index} is already started", index)`, or with an
+/// expression: `tr!("{count} tasks", count = tasks.len())`.
+#[macro_export]
+macro_rules! tr {
+    (@value $name:ident) => {
+        $name
+    };
+    (@value $name:ident = $value:expr) => {
+        $value
+    };
+    ($text:literal $(, $name:ident $(= $value:expr)?)* $(,)?) => {
+        $crate::fill(
+            $crate::tr($text),
+            &[$((stringify!($name), &$crate::tr!(@value $name $(= $value)?) as &dyn ::std::fmt::Display)),*],
+        )
+    };
+}
+
+/// Replaces the `{name}` placeholders in a message with the values, see
+/// [`tr!`]. Unknown ones are left as they are.
+#[doc(hidden)]
+pub fn fill(message: &str, values: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let value = rest[start + 1..].find('}').and_then(|end| {
+            let name = &rest[start + 1..start + 1 + end];
+            let (_, value) = values.iter().find(|(n, _)| *n == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(&value.to_string());
+                rest = &rest[start + end + 2..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The language messages are shown in, taken from `LC_ALL`, `LC_MESSAGES` or
+/// `LANG` like gettext does, e.g. `de` for `de_DE.UTF-8`. None for english or
+/// the C locale.
+pub fn language() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .find(|value| !value.is_empty())?;
+    let locale = locale.to_string_lossy();
+
+    let language = locale
+        .split(['_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" | "en" => None,
+        _ => Some(language),
+    }
+}
+
+/// The catalog for the language of the user. The builtin one is extended or
+/// overridden by `locale/<language>.toml` next to the config file, which maps
+/// english messages to their translation.
+fn load() -> HashMap<String, &'static str> {
+    let mut catalog = HashMap::new();
+    let Some(language) = language() else {
+        return catalog;
+    };
+
+    let builtin = BUILTIN
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map(|(_, content)| (*content).to_string());
+    let user = crate::config::config_path()
+        .and_then(|path| {
+            Some(
+                path.parent()?
+                    .join("locale")
+                    .join(format!("{language}.toml")),
+            )
+        })
+        .and_then(|path| fs::read_to_string(path).ok());
+
+    // a broken catalog only means the messages stay in english
+    for content in builtin.into_iter().chain(user) {
+        let Ok(table) = toml::from_str::<toml::value::Table>(&content) else {
+            continue;
+        };
+        for (text, translation) in table {
+            if let toml::Value::String(translation) = translation {
+                // lives as long as the process, like the english messages
+                catalog.insert(text, &*Box::leak(translation.into_boxed_str()));
+            }
+        }
+    }
+
+    catalog
+}
+
+#[test]
+fn builtin_catalogs_are_valid() {
+    for (language, content) in BUILTIN {
+        let table: toml::value::Table = toml::from_str(content)
+            .unwrap_or_else(|e| panic!("invalid catalog for {}: {}", language, e));
+        assert!(table.values().all(toml::Value::is_str));
+    }
+}
+
+#[test]
+fn fill_placeholders() {
+    let index = 3;
+    assert_eq!(
+        fill(
+            "{what} {index} and {index}, not {other}",
+            &[("index", &index), ("what", &"task")]
+        ),
+        "task 3 and 3, not {other}"
+    );
+    assert_eq!(
+        tr!("task {index} of {count}", index, count = 4),
+        "task 3 of 4"
+    );
+}
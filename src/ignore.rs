@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Files with patterns of directories that are not searched for projects
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".tutelignore"];
+
+/// The ignore patterns in effect for a directory: the ones of its own
+/// ignore files, followed by the ones of its parents
+#[derive(Debug)]
+pub struct Ignore {
+    parent: Option<Arc<Self>>,
+    /// Anchored patterns are relative to this directory
+    base: PathBuf,
+    patterns: Vec<Pattern>,
+}
+
+/// A single line of an ignore file, a subset of what git understands:
+/// `*`, `?` and `**` globs, `!` to negate, a trailing `/` to only match
+/// directories and a `/` anywhere else to anchor the pattern
+#[derive(Debug)]
+struct Pattern {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl Pattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = line
+            .strip_prefix('!')
+            .map_or((false, line), |line| (true, line));
+        let (dir_only, line) = line
+            .strip_suffix('/')
+            .map_or((false, line), |line| (true, line));
+        let anchored = line.contains('/');
+
+        Some(Self {
+            glob: line.trim_start_matches('/').to_string(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let text = if self.anchored {
+            relative
+        } else {
+            relative.rsplit('/').next().unwrap_or(relative)
+        };
+        glob(self.glob.as_bytes(), text.as_bytes())
+    }
+}
+
+impl Ignore {
+    /// Patterns that apply everywhere below `base`, like the ones from the config
+    pub fn new(base: &Path, patterns: &[String]) -> Self {
+        Self {
+            parent: None,
+            base: base.to_path_buf(),
+            patterns: patterns.iter().filter_map(|p| Pattern::parse(p)).collect(),
+        }
+    }
+
+    /// Adds the patterns of the ignore files in the directory, if there are any
+    pub fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self> {
+        let patterns = IGNORE_FILES
+            .iter()
+            .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+            .flat_map(|content| {
+                content
+                    .lines()
+                    .filter_map(Pattern::parse)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        if patterns.is_empty() {
+            return self.clone();
+        }
+        Arc::new(Self {
+            parent: Some(self.clone()),
+            base: dir.to_path_buf(),
+            patterns,
+        })
+    }
+
+    /// Whether the path is ignored. Like in git, the last matching pattern
+    /// of the innermost ignore file wins.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignore = Some(self);
+        while let Some(current) = ignore {
+            if let Ok(relative) = path.strip_prefix(&current.base) {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if let Some(pattern) = current
+                    .patterns
+                    .iter()
+                    .rev()
+                    .find(|p| p.matches(&relative, is_dir))
+                {
+                    return !pattern.negated;
+                }
+            }
+            ignore = current.parent.as_deref();
+        }
+        false
+    }
+}
+
+/// Matches a glob, where `*` and `?` don't match a `/`, but `**` does
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    if let Some(rest) = pattern.strip_prefix(b"**/") {
+        // any number of whole directories, including none
+        return glob(rest, text)
+            || (0..text.len()).any(|i| text[i] == b'/' && glob(rest, &text[i + 1..]));
+    }
+    if let Some(rest) = pattern.strip_prefix(b"**") {
+        return (0..=text.len()).any(|i| glob(rest, &text[i..]));
+    }
+
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob(&pattern[1..], &text[i..])),
+        Some(b'?') => text.first().is_some_and(|c| *c != b'/') && glob(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && glob(&pattern[1..], &text[1..]),
+    }
+}
+
+#[test]
+fn ignore_patterns() {
+    let ignore = Ignore::new(
+        Path::new("/repo"),
+        &[
+            "# build output".to_string(),
+            "target/".to_string(),
+            "*.log".to_string(),
+            "/docs/**/tmp".to_string(),
+            "vendor".to_string(),
+            "!vendor".to_string(),
+        ],
+    );
+
+    assert!(ignore.is_ignored(Path::new("/repo/target"), true));
+    assert!(ignore.is_ignored(Path::new("/repo/crates/a/target"), true));
+    assert!(!ignore.is_ignored(Path::new("/repo/target"), false));
+    assert!(ignore.is_ignored(Path::new("/repo/a/build.log"), false));
+    assert!(ignore.is_ignored(Path::new("/repo/docs/tmp"), true));
+    assert!(ignore.is_ignored(Path::new("/repo/docs/a/b/tmp"), true));
+    assert!(!ignore.is_ignored(Path::new("/repo/src/docs/tmp"), true));
+    assert!(!ignore.is_ignored(Path::new("/repo/vendor"), true));
+    assert!(!ignore.is_ignored(Path::new("/repo/src"), true));
+}
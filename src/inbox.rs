@@ -0,0 +1,30 @@
+use anyhow::{Context, Result};
+use std::{fs, path::PathBuf};
+
+use crate::{tr, Project};
+
+pub const INBOX_FILE_NAME: &str = "inbox.toml";
+
+/// The location of the global inbox, in the data directory
+pub fn inbox_path() -> Option<PathBuf> {
+    Some(crate::data_dir()?.join(INBOX_FILE_NAME))
+}
+
+/// Loads the global inbox, which is an ordinary project file that doesn't
+/// belong to any directory. A missing inbox is empty.
+///
+/// # Errors
+/// This function will return an error if no location for the inbox can be
+/// determined, or the inbox file exists but can't be loaded.
+pub fn load_inbox() -> Result<Project> {
+    let path = inbox_path().context(tr("no place for the inbox, set $XDG_DATA_HOME or $HOME"))?;
+
+    if path.is_file() {
+        return Project::load(path, 0);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(tr("unable to create the inbox directory"))?;
+    }
+    Ok(Project::new(path, 0, "inbox".to_string()))
+}
@@ -2,50 +2,283 @@
 #![warn(clippy::perf)]
 #![warn(clippy::nursery)]
 #![warn(clippy::style)]
+// messages are filled in by tr!, after they are translated
+#![allow(clippy::literal_string_with_formatting_args)]
 
+mod board;
+mod checklist;
+mod comments;
+mod config;
 mod data;
+mod date;
 mod de;
+mod discovery;
+mod display;
+mod emoji;
+mod error;
+mod format;
+mod graph;
+mod history;
+mod i18n;
+mod ignore;
+mod inbox;
+mod links;
+mod markdown;
+mod merge;
+mod query;
+mod registry;
+mod rules;
+mod search;
 mod ser;
+mod template;
+mod theme;
+mod timesheet;
+mod urgency;
+mod view;
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
 
 use anyhow::{bail, Result};
 
-pub use data::{Project, Task};
+pub use board::Board;
+pub use checklist::{CHECKLIST_FILE_NAME, MARKER as CHECKLIST_MARKER};
+pub use comments::{resolve_comment, CommentAction};
+pub use config::Config;
+pub use config::{Boundary, Discovery, Hyperlinks, Pomodoro, WipLimit};
+pub use data::{Interval, Priority, Project, Status, Task};
+pub use date::{format_duration, now, parse_date, parse_duration, parse_until, DateFormat};
+pub use discovery::{find_projects, MAX_DEPTH};
+pub use display::{porcelain, Column, Details, DisplayOptions, GroupBy, Layout, Rendered, Symbols};
+pub use error::Error;
+pub use format::Format;
+pub use graph::{dot, mermaid};
+pub use history::{record, redo, undo};
+pub use i18n::{fill, language, tr};
+pub use inbox::{inbox_path, load_inbox};
+pub use links::{find_locations, find_mentions, find_tags, find_urls};
+pub use merge::merge;
+pub use query::Query;
+pub use registry::{is_reference, register, registered, resolve};
+pub use rules::Rule;
+pub use search::{search, Hit};
+pub use template::Template;
+pub use theme::{Style, Theme};
+pub use timesheet::{timesheet, timesheet_by_tag, Entry};
+pub use urgency::Urgency;
+pub use view::View;
 
 pub const PROJECT_FILE_NAME: &str = ".tutel.toml";
+/// Archived tasks are kept in this file next to the project file
+pub const ARCHIVE_FILE_NAME: &str = ".tutel.archive.toml";
+/// The name of the list that is used unless another one is selected
+pub const DEFAULT_LIST: &str = "default";
 
-/// Creates a new empty Project in the given directory
-pub fn new_project(name: String) -> Result<Project> {
+/// Creates a new empty Project in the current directory
+pub fn new_project(name: String, format: Format) -> Result<Project> {
     let dir = std::env::current_dir()?;
-    let path = dir.join(PROJECT_FILE_NAME);
+    let path = dir.join(format.file_name());
     let mut project = Project::new(path, 0, name);
 
     project.save()?;
+    registry::register(&project.path)?;
 
     Ok(project)
 }
 
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Report which project gets used, and how it was found, on stderr
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Whether [`set_verbose`] was turned on
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+static BOUNDARY: Mutex<Boundary> = Mutex::new(Boundary::None);
+
+/// Stop searching for a project file in parent directories once `boundary`
+/// is reached
+pub fn set_boundary(boundary: Boundary) {
+    *BOUNDARY.lock().unwrap_or_else(|e| e.into_inner()) = boundary;
+}
+
+static LIST: Mutex<Option<String>> = Mutex::new(None);
+
+/// Have [`load_project_rec`] and [`load_project_file`] select the named
+/// list of the project
+pub fn set_list(list: Option<String>) {
+    *LIST.lock().unwrap_or_else(|e| e.into_inner()) = list;
+}
+
+static READ_ONLY: Mutex<Option<String>> = Mutex::new(None);
+
+/// Refuse to save projects, they are a copy of the one published at `url`
+pub fn set_read_only(url: Option<String>) {
+    *READ_ONLY.lock().unwrap_or_else(|e| e.into_inner()) = url;
+}
+
+/// Fails unless projects may be saved, see [`set_read_only`]
+///
+/// # Errors
+/// [`Error::ReadOnly`] if they may not
+pub fn writable() -> Result<()> {
+    let read_only = READ_ONLY.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match read_only {
+        Some(url) => bail!(Error::ReadOnly(url)),
+        None => Ok(()),
+    }
+}
+
+static MAX_PARENTS: Mutex<Option<usize>> = Mutex::new(None);
+
+/// Search at most this many parent directories for a project file
+pub fn set_max_depth(max_depth: Option<usize>) {
+    *MAX_PARENTS.lock().unwrap_or_else(|e| e.into_inner()) = max_depth;
+}
+
+/// The directories searched for a project file: the path and its parents,
+/// up to the boundary and the maximum depth
+fn search_path(path: &Path) -> impl Iterator<Item = &Path> {
+    let boundary = *BOUNDARY.lock().unwrap_or_else(|e| e.into_inner());
+    let max_parents = *MAX_PARENTS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut reached = false;
+    path.ancestors()
+        .take(max_parents.map_or(usize::MAX, |parents| parents + 1))
+        .take_while(move |dir| {
+            let searched = !reached;
+            reached = reached || boundary.is_reached(dir);
+            searched
+        })
+}
+
+/// A project file, and the copy that is loaded and saved in its place
+static STAGED: Mutex<Option<(PathBuf, PathBuf)>> = Mutex::new(None);
+
+/// Until [`unstage`] is called, [`load_project_rec`] loads `copy` whenever it
+/// finds `project_file`, so all changes end up in the copy
+pub fn stage(project_file: PathBuf, copy: PathBuf) {
+    *STAGED.lock().unwrap_or_else(|e| e.into_inner()) = Some((project_file, copy));
+}
+
+/// Undoes [`stage`]
+pub fn unstage() {
+    *STAGED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The project file the path is a staged copy of, or the path itself. The
+/// copy is thrown away or renamed, nothing should remember it.
+fn original_path(path: &Path) -> PathBuf {
+    let staged = STAGED.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match staged {
+        Some((original, copy)) if copy == path => original,
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Walks the path upwards until a project file is found, without loading
+/// it. Returns the file and how many directories up it is.
+pub fn find_project(path: &Path) -> Option<(PathBuf, usize)> {
+    let start = Instant::now();
+    for (steps, p) in search_path(path).enumerate() {
+        if let Some(mut project_file) = has_project(p) {
+            let staged = STAGED.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            if let Some((_, copy)) = staged.filter(|(original, _)| *original == project_file) {
+                project_file = copy;
+            }
+            if is_verbose() {
+                eprintln!(
+                    "[tutel] found {} after {steps} parent directories in {:?}",
+                    project_file.display(),
+                    start.elapsed()
+                );
+            }
+            return Some((project_file, steps));
+        }
+    }
+    None
+}
+
+/// Loads the project file with the list selected by [`set_list`]
+pub fn load_project_file(project_file: PathBuf, steps: usize) -> Result<Project> {
+    let mut project = Project::load(project_file, steps)?;
+    if let Some(list) = LIST.lock().unwrap_or_else(|e| e.into_inner()).as_deref() {
+        if list != DEFAULT_LIST {
+            project.not_a_checklist(tr("other lists"))?;
+        }
+        project.select_list(list);
+    }
+    Ok(project)
+}
+
 /// Walks the path upwards until a project file(.tutel.toml) is found and loads it
 pub fn load_project_rec(path: &Path) -> Result<Project> {
-    for (steps, p) in path.ancestors().enumerate() {
-        if let Some(project_file) = has_project(p) {
-            return Project::load(project_file, steps);
+    let start = Instant::now();
+    if let Some((project_file, steps)) = find_project(path) {
+        let project = load_project_file(project_file, steps)?;
+        if is_verbose() {
+            eprintln!("[tutel] loaded in {:?}", start.elapsed());
         }
+        return Ok(project);
     }
 
-    bail!("no project found");
+    let searched = search_path(path).collect::<Vec<_>>();
+    if is_verbose() {
+        eprintln!(
+            "[tutel] searched {} directories from {} in {:?}",
+            searched.len(),
+            path.display(),
+            start.elapsed()
+        );
+    }
+    if searched.len() < path.ancestors().count() {
+        // the config cut the search short, which is easy to forget about
+        let searched = searched
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(anyhow::Error::new(Error::NoProject).context(tr!(
+            "only searched {searched}, see stop-at and max-depth in the discovery config",
+            searched
+        )));
+    }
+    bail!(Error::NoProject);
 }
 
-/// Determines whether a project exists in the given path by checking
-/// for the existence of .tutel.project. Returns Some(project_path)
-/// if it does exist, None otherwise
-pub fn has_project(path: &Path) -> Option<PathBuf> {
-    let project = path.join(PROJECT_FILE_NAME);
+/// Where tutel keeps files that don't belong to a project:
+/// `$XDG_DATA_HOME/tutel`, falling back to `~/.local/share/tutel` and
+/// `%APPDATA%\tutel` on Windows
+fn data_dir() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
 
-    if project.exists() && project.is_file() {
-        Some(project)
-    } else {
-        None
-    }
+    Some(dir.join("tutel"))
+}
+
+/// Determines whether a project exists in the given path
+///
+/// Looks for .tutel.toml, .tutel.yaml and a TODO.md checklist tutel
+/// created, in that order. Returns Some(project_path) if one exists, None
+/// otherwise
+pub fn has_project(path: &Path) -> Option<PathBuf> {
+    Format::ALL
+        .iter()
+        .map(|format| (format, path.join(format.file_name())))
+        .find(|(format, project)| format.is_project(project))
+        .map(|(_, project)| project)
 }
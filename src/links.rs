@@ -0,0 +1,106 @@
+const SCHEMES: &[&str] = &["https://", "http://", "file://"];
+
+/// Returns all urls contained in the given text. A url starts with one of the
+/// known schemes and ends at the next whitespace; trailing punctuation is
+/// assumed to belong to the surrounding sentence.
+pub fn find_urls(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let start = SCHEMES.iter().filter_map(|s| word.find(s)).min()?;
+            let url = word[start..].trim_end_matches(|c| ".,;:!?)]}>'\"".contains(c));
+            let has_host = SCHEMES
+                .iter()
+                .any(|s| url.len() > s.len() && url.starts_with(s));
+            has_host.then_some(url)
+        })
+        .collect()
+}
+
+/// Returns the places in source files like `src/main.rs:42` in the given
+/// text, as path and line
+pub fn find_locations(text: &str) -> Vec<(&str, usize)> {
+    text.split_whitespace()
+        .filter(|word| !word.contains("://"))
+        .filter_map(|word| {
+            let word = word
+                .trim_start_matches(|c| "([{<'\"`".contains(c))
+                .trim_end_matches(|c: char| !c.is_alphanumeric());
+            let (path, line) = word.rsplit_once(':')?;
+            // `file:12:5` points at a column as well
+            let (path, line) = match path.rsplit_once(':') {
+                Some((file, l)) if l.parse::<usize>().is_ok() => (file, l),
+                _ => (path, line),
+            };
+            let line = line.parse().ok().filter(|line| *line > 0)?;
+            (!path.is_empty()).then_some((path, line))
+        })
+        .collect()
+}
+
+/// Returns the names of the people mentioned like `@bob` in the given text.
+/// The `@` has to start a word, so email addresses don't count.
+pub fn find_mentions(text: &str) -> Vec<&str> {
+    find_marked(text, '@')
+}
+
+/// Returns the tags like `#release-blocker` in the given text. Numbers like
+/// `#12` usually refer to an issue and are not tags.
+pub fn find_tags(text: &str) -> Vec<&str> {
+    find_marked(text, '#')
+        .into_iter()
+        .filter(|tag| !tag.chars().all(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+/// The words starting with the marker, without it
+fn find_marked(text: &str, marker: char) -> Vec<&str> {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let name = word
+                .trim_start_matches(|c| "([{<'\"".contains(c))
+                .strip_prefix(marker)?
+                .trim_end_matches(|c: char| !c.is_alphanumeric());
+            let valid = name
+                .chars()
+                .all(|c| c.is_alphanumeric() || "-_.".contains(c));
+            (valid && !name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+/// An OSC 8 hyperlink to the url, showing the text
+pub fn link(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+#[test]
+fn find_urls_in_text() {
+    assert_eq!(
+        find_urls("see https://example.com/a?b=c, (or <http://x.org>). not http:// or ftp://y"),
+        ["https://example.com/a?b=c", "http://x.org"]
+    );
+}
+
+#[test]
+fn find_locations_in_text() {
+    assert_eq!(
+        find_locations("TODO in (src/main.rs:42), `lib.rs:7:3` not http://x.org:80 or a:b or :3"),
+        [("src/main.rs", 42), ("lib.rs", 7)]
+    );
+}
+
+#[test]
+fn find_mentions_in_text() {
+    assert_eq!(
+        find_mentions("ask @bob, (cc @alice.w) about bob@example.com and @ or @@x"),
+        ["bob", "alice.w"]
+    );
+}
+
+#[test]
+fn find_tags_in_text() {
+    assert_eq!(
+        find_tags("fix the build #release-blocker, see #12 and C# or (#ci)"),
+        ["release-blocker", "ci"]
+    );
+}
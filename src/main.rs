@@ -1,23 +1,74 @@
 #![warn(clippy::perf)]
 #![warn(clippy::style)]
 #![warn(clippy::nursery)]
+// messages are filled in by tr!, after they are translated
+#![allow(clippy::literal_string_with_formatting_args)]
 
-use app::{Command, TaskSelector};
+use app::{
+    ColorChoice, Command, Dependency, GraphFormat, ShowOptions, TaskChanges, TaskRef, TaskSelector,
+    TimesheetBy,
+};
 use colored::Colorize;
-use std::{fs, io::Write};
+use prompt::{PromptShell, StatusFormat};
+use std::{
+    fs,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 use tempfile::NamedTempFile;
+use tutel::{tr, Config, DisplayOptions, Hyperlinks, Layout};
 
 use anyhow::{bail, Context, Result};
 
 mod app;
-
-const BASH_COMPLETIONS: &str = include_str!("../res/tutel-completions.bash");
-const ZSH_COMPLETIONS: &str = include_str!("../res/tutel-completions.zsh");
-const FISH_COMPLETIONS: &str = include_str!("../res/tutel-completions.fish");
-const ELVISH_COMPLETIONS: &str = include_str!("../res/tutel-completions.elv");
+mod cache;
+mod clipboard;
+mod completions;
+mod diff;
+mod hook;
+mod man;
+mod notify;
+mod pager;
+mod prompt;
+mod remote;
 
 fn main() {
-    match run_app(app::parse_cli()) {
+    let options = app::parse_cli();
+    // the shell captures the prompt, but shows it on the terminal
+    let prompt = matches!(options.command, Command::Prompt(Some(_)));
+    setup_colors(options.color, prompt);
+    tutel::set_verbose(options.verbose);
+    let start = Instant::now();
+
+    let result = Config::load().and_then(|mut config| {
+        config.ascii |= options.ascii;
+        config.plain |= options.plain;
+        config.porcelain = options.porcelain;
+        if let Some(boundary) = options.stop_at {
+            config.discovery.stop_at = boundary;
+        }
+        tutel::set_boundary(config.discovery.stop_at);
+        tutel::set_max_depth(config.discovery.max_depth);
+        tutel::set_list(options.list);
+        if config.porcelain {
+            config.pager = false;
+        }
+        if config.plain || config.porcelain {
+            // signals that are only colors get lost on screen readers anyway
+            colored::control::set_override(false);
+        }
+        let remote = match &options.project {
+            Some(location) => enter_project(location, &mut config)?,
+            None => None,
+        };
+        run_app(options.command, &config)?;
+        remote.map_or(Ok(()), |remote| remote.push())
+    });
+    if tutel::is_verbose() {
+        eprintln!("[tutel] done in {:?}", start.elapsed());
+    }
+    match result {
         Ok(_) => {}
         Err(e) => {
             eprintln!("{} {}", "[tutel]".red(), e,);
@@ -25,59 +76,1327 @@ fn main() {
             if e.chain().len() > 1 {
                 eprintln!("\t{}", e.root_cause());
             }
-            std::process::exit(1);
+            let code = e
+                .downcast_ref::<tutel::Error>()
+                .map_or(1, tutel::Error::exit_code);
+            std::process::exit(code);
         }
     }
 }
 
-fn run_app(command: Command) -> Result<()> {
+/// Decides whether the output gets colored. An explicit choice on the command
+/// line takes precedence over `NO_COLOR` and terminal detection. Output for
+/// the `terminal` is colored even if stdout is something else.
+fn setup_colors(choice: ColorChoice, terminal: bool) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && (terminal || std::io::stdout().is_terminal())
+        }
+    };
+
+    colored::control::set_override(enabled);
+}
+
+fn run_app(command: Command, config: &Config) -> Result<()> {
     //Run Commands
     match command {
-        Command::Show => print_list(),
-        Command::NewProject { name, force } => new_project(name, force),
-        Command::AddTask { desc, completed } => add(desc, completed),
-        Command::MarkCompletion(completed, selector) => done(selector, completed),
-        Command::RemoveTask(selector) => remove(selector),
-        Command::EditTask(editor, index) => edit_task(index, editor),
-        Command::PrintCompletion(shell) => print_completions(shell.as_str()),
+        Command::Show(options) => {
+            // what isn't the id or alias of a task is a view or a query
+            match options
+                .view
+                .clone()
+                .map(|name| resolve(TaskRef::Name(name)))
+            {
+                Some(Ok(index)) => print_task(index, config),
+                _ => print_list(&options, config),
+            }
+        }
+        Command::ShowTask(index) => print_task(resolve(index)?, config),
+        Command::Focus(limit) => print_focus(limit, config),
+        Command::Dashboard(limit) => print_dashboard(limit, config),
+        Command::Search { words, fuzzy } => search(&words, fuzzy, config),
+        Command::Board(width) => print_board(width, config),
+        Command::NewProject {
+            name,
+            force,
+            format,
+        } => new_project(name, force, format.unwrap_or(config.format)),
+        Command::AddTask {
+            desc,
+            completed,
+            estimate,
+            assignee,
+            force,
+        } => add(desc, completed, estimate, assignee, force, config),
+        Command::Depend { depends, index, on } => {
+            let on = match on {
+                Dependency::Index(on) => Dependency::Index(resolve(on)?),
+                Dependency::External(reference) => Dependency::External(reference),
+            };
+            depend(resolve(index)?, on, depends, config)
+        }
+        Command::Graph(format) => print_graph(format),
+        Command::AddTemplate(name) => add_template(&name, config),
+        Command::AddInteractive => add_interactive(config),
+        Command::Capture(desc) => capture(desc),
+        Command::Triage => triage(config),
+        Command::Log { desc, at } => log(desc, at, config),
+        Command::MarkCompletion {
+            completed,
+            note,
+            comment,
+            selector,
+        } => done(selector.try_map(resolve)?, completed, note, comment, config),
+        Command::RemoveTask(selector) => remove(selector.try_map(resolve)?, config),
+        Command::EditTask {
+            editor,
+            yes,
+            selector,
+            changes,
+        } => edit_task(selector.try_map(resolve)?, editor, yes, &changes, config),
+        Command::SetTask {
+            index,
+            status,
+            force,
+        } => set_task(resolve(index)?, status, force, config),
+        Command::Pin(pinned, index) => pin(resolve(index)?, pinned, config),
+        Command::Hide(hidden, index) => hide(resolve(index)?, hidden, config),
+        Command::Open { index, editor } => open(resolve(index)?, &editor),
+        Command::Alias { index, alias } => set_alias(resolve(index)?, alias, config),
+        Command::Attach {
+            index,
+            target,
+            remove,
+        } => attach(resolve(index)?, target, remove, config),
+        Command::Start { index, force } => start(resolve(index)?, force, config),
+        Command::Stop => stop(config),
+        Command::Snooze { index, until } => snooze(resolve(index)?, until, config),
+        Command::Wait { index, on } => wait(resolve(index)?, on, config),
+        Command::Pomodoro { index, work, rest } => pomodoro(resolve(index)?, work, rest, config),
+        Command::Timesheet {
+            since,
+            by,
+            csv,
+            query,
+        } => print_timesheet(since, by, csv, query.as_ref(), config),
+        Command::Yank {
+            index,
+            markdown,
+            osc52,
+        } => yank(resolve(index)?, markdown, osc52),
+        Command::Prompt(shell) => {
+            print_prompt(shell, config);
+            Ok(())
+        }
+        Command::Hook(shell) => {
+            print!("{}", hook::script(&shell)?);
+            Ok(())
+        }
+        Command::HookSummary(previous) => print_hook_summary(previous, config),
+        Command::Env => print_env(),
+        Command::Lists => print_lists(config),
+        Command::Guard(tags) => guard(&tags, config),
+        Command::Status { format, max_age } => print_status(format, max_age, config),
+        Command::Path => print_path(),
+        Command::Cat => print_project_file(),
+        Command::Batch(file) => batch(file, config),
+        Command::Convert(format) => convert(format),
+        Command::Merge {
+            base,
+            ours,
+            theirs,
+            name,
+            auto,
+        } => merge(base.as_deref(), &ours, &theirs, name.as_deref(), auto),
+        Command::Transaction(commands) => transaction(&commands, config),
+        Command::EditProject { editor, yes } => edit_project(&editor, yes, config),
+        Command::PrintCompletion { shell, install } => print_completions(shell, install),
+        Command::Man { output, command } => print_man(output, command),
+        Command::Archive(auto) => archive(auto, config),
+        Command::Undo => undo(config),
+        Command::Redo => redo(config),
         Command::RemoveProject => remove_project(),
     }
 }
 
-fn print_list() -> Result<()> {
-    let p = tutel::load_project_rec(&*std::env::current_dir()?)?;
-    println!("{p}");
+/// Looks up a task given on the command line in the project in effect
+fn resolve(task: TaskRef) -> Result<usize> {
+    let name = match task {
+        TaskRef::Index(index) => return Ok(index),
+        TaskRef::Name(name) => name,
+    };
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    match project
+        .find_id(&name)
+        .or_else(|_| project.find_alias(&name))
+    {
+        Ok(task) => Ok(task.index),
+        Err(_) => bail!(tutel::Error::NoTask(tr!("id or alias {name}", name))),
+    }
+}
+
+/// Loads the project in effect, set up as the config says
+fn load_project(config: &Config) -> Result<tutel::Project> {
+    let mut project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    project.archive_after = config.archive_after;
+    project.history = config.history;
+    project.search_index = config.search_index && !config.remote;
+    // other projects refer to this one by name, but failing to note it is not
+    // worth stopping for
+    if !config.remote {
+        let _ = tutel::register(&project.path);
+    }
+    Ok(project)
+}
+
+/// Makes the project at `location` the one commands work on, like `git -C`
+/// does. A remote project is fetched into a temporary directory first, and
+/// has to be pushed back once the command is done.
+fn enter_project(location: &str, config: &mut Config) -> Result<Option<remote::Remote>> {
+    if let Some(remote) = remote::Remote::fetch(location) {
+        let remote = remote?;
+        std::env::set_current_dir(remote.dir())?;
+        if remote.is_read_only() {
+            tutel::set_read_only(Some(location.to_string()));
+        }
+        config.remote = true;
+        // the next run gets another copy, there would be nothing to undo
+        config.history = 0;
+        return Ok(Some(remote));
+    }
+
+    let path = Path::new(location);
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+    // an empty parent is the current directory
+    if !dir.as_os_str().is_empty() {
+        std::env::set_current_dir(dir)
+            .with_context(|| tr!("unable to enter {dir}", dir = dir.display()))?;
+    }
+    Ok(None)
+}
+
+fn print_list(options: &ShowOptions, config: &Config) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let projects = if options.recursive {
+        let found = tutel::find_projects(&dir, tutel::MAX_DEPTH, &config.discovery.ignore);
+        if found.is_empty() {
+            bail!(tutel::Error::NoProject);
+        }
+        found
+            .into_iter()
+            .map(|file| tutel::load_project_file(file, 0))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        vec![tutel::load_project_rec(&dir)?]
+    };
+
+    // options given on the command line win over the ones of the view
+    let none = tutel::View::default();
+    let mut query = None;
+    let view = match &options.view {
+        Some(name) => match config.views.iter().find(|view| view.name == *name) {
+            Some(view) => view,
+            None => {
+                query = Some(name.parse::<tutel::Query>()?);
+                &none
+            }
+        },
+        None => &none,
+    };
+
+    let mut display = display_options(config);
+    display.width = options
+        .width
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| width.0 as usize));
+    if let Some(columns) = options.columns.as_ref().or(view.columns.as_ref()) {
+        display.layout = Layout::Table(columns.clone());
+    }
+    if let Some(urgency) = view.urgency {
+        display.urgency = urgency.then(|| config.urgency.clone());
+    }
+    display.snoozed = options.snoozed || view.snoozed;
+    display.hidden = options.hidden || view.hidden;
+    display.waiting = options.waiting || view.waiting;
+    display.limit = options.limit.or(view.limit);
+    display.stale = options.stale || view.stale;
+    display.assignee = options.assignee.clone().or_else(|| view.assignee.clone());
+    display.mentions = options.mentions.clone().or_else(|| view.mentions.clone());
+    display.tag = options.tag.clone().or_else(|| view.tag.clone());
+    display.group_by = options.group_by.or(view.group_by);
+    display.query = query;
+
+    let output = projects
+        .iter()
+        .map(|p| p.render(&display).to_string())
+        .filter(|output| !output.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if output.is_empty() {
+        // nothing matched in porcelain mode
+        Ok(())
+    } else if config.pager && !options.no_pager {
+        pager::print_paged(&output)
+    } else {
+        println!("{output}");
+        Ok(())
+    }
+}
+
+/// Lists the tasks with all the words, noting the list of those on another
+/// one and the archived ones
+fn search(words: &[String], fuzzy: bool, config: &Config) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let use_index = config.search_index && !config.remote;
+    let hits = tutel::search(&project, &words.join(" "), fuzzy, use_index)?;
+
+    if config.porcelain {
+        for hit in &hits {
+            let list = hit.list.as_deref().unwrap_or("archive");
+            println!("{}\t{}\t{list}\t{}", hit.index, hit.id, hit.desc);
+        }
+        return Ok(());
+    }
+    if hits.is_empty() {
+        println!("{}", tutel::tr("no matches"));
+        return Ok(());
+    }
+
+    let current = project.list.as_deref().unwrap_or(tutel::DEFAULT_LIST);
+    let width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize);
+    for hit in &hits {
+        let place = match &hit.list {
+            Some(list) if list == current => String::new(),
+            Some(list) => format!(" ({list})"),
+            None => format!(" ({})", tutel::tr("archived")),
+        };
+        let index = format!("{:03} ", hit.index);
+        let width =
+            width.map(|width| width.saturating_sub(index.chars().count() + place.chars().count()));
+        println!("{index}{}{}", highlight(hit, width, config), place.dimmed());
+    }
+    Ok(())
+}
+
+/// The description of the hit with the matches styled, cut down to the part
+/// around the first match if it is wider than `width`
+fn highlight(hit: &tutel::Hit, width: Option<usize>, config: &Config) -> String {
+    let ellipsis = if config.ascii { "..." } else { "…" };
+    let shown = width.map_or(0..hit.desc.len(), |width| {
+        hit.context(width.saturating_sub(2 * ellipsis.chars().count()).max(1))
+    });
+
+    let mut out = String::new();
+    if shown.start > 0 {
+        out.push_str(ellipsis);
+    }
+    let mut pos = shown.start;
+    for span in &hit.spans {
+        let (start, end) = (span.start.max(pos), span.end.min(shown.end));
+        if start >= end {
+            continue;
+        }
+        out.push_str(&hit.desc[pos..start]);
+        out.push_str(
+            &config
+                .theme
+                .matched
+                .paint(&hit.desc[start..end])
+                .to_string(),
+        );
+        pos = end;
+    }
+    out.push_str(&hit.desc[pos..shown.end]);
+    if shown.end < hit.desc.len() {
+        out.push_str(ellipsis);
+    }
+    out
+}
+
+/// Lists only the most urgent tasks that are left to do
+fn print_focus(limit: usize, config: &Config) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+
+    let mut display = display_options(config);
+    display.width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize);
+    display.urgency = Some(config.urgency.clone());
+    display.open = true;
+    display.limit = Some(limit);
+
+    println!("{}", p.render(&display));
+
+    Ok(())
+}
+
+/// Every registered project with its most urgent tasks and how much is
+/// left, stale or over estimate, followed by the totals
+fn print_dashboard(limit: usize, config: &Config) -> Result<()> {
+    // the others are still worth showing
+    let mut failed = 0;
+    let projects = tutel::registered()
+        .into_iter()
+        .filter_map(|file| match tutel::Project::load(file.clone(), 0) {
+            Ok(project) => Some(project),
+            Err(e) => {
+                eprintln!("{} {}: {e:#}", "[tutel]".red(), file.display());
+                failed += 1;
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if projects.is_empty() {
+        if failed > 0 {
+            return dashboard_failures(failed);
+        }
+        bail!(tr(
+            "no projects known yet, they are registered once a command changed them"
+        ));
+    }
+
+    let mut display = display_options(config);
+    display.width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize);
+    display.urgency = Some(config.urgency.clone());
+    display.open = true;
+    display.limit = Some(limit);
+
+    let now = tutel::now();
+    let mut blocks = Vec::new();
+    let mut totals = [0; 3];
+    for p in &projects {
+        let open = p
+            .data
+            .tasks
+            .iter()
+            .filter(|t| !t.is_completed())
+            .collect::<Vec<_>>();
+        let counts = [
+            open.len(),
+            config.stale.map_or(0, |after| {
+                open.iter().filter(|t| t.is_stale(now, after)).count()
+            }),
+            open.iter()
+                .filter(|t| t.estimate.is_some_and(|e| t.tracked(now) > e))
+                .count(),
+        ];
+        for (total, count) in totals.iter_mut().zip(counts) {
+            *total += count;
+        }
+
+        let [pending, stale, over] = counts;
+        if config.porcelain {
+            println!(
+                "{}\t{}\t{pending}\t{stale}\t{over}",
+                p.path.display(),
+                p.data.name
+            );
+            continue;
+        }
+        blocks.push(format!(
+            "{}\n{}",
+            p.render(&display),
+            tr!(
+                "{pending} pending, {stale} stale, {over} over estimate",
+                pending,
+                stale,
+                over
+            )
+        ));
+    }
+    if config.porcelain {
+        return dashboard_failures(failed);
+    }
+
+    let [pending, stale, over] = totals;
+    let projects = match projects.len() {
+        1 => tr("1 project").to_string(),
+        n => tr!("{n} projects", n),
+    };
+    blocks.push(tr!(
+        "{pending} pending in {projects}, {stale} stale, {over} over estimate",
+        pending,
+        projects,
+        stale,
+        over
+    ));
+    let output = blocks.join("\n\n");
+    if config.pager {
+        pager::print_paged(&output)?;
+    } else {
+        println!("{output}");
+    }
+    dashboard_failures(failed)
+}
+
+/// Fails if any of the registered projects couldn't be loaded
+fn dashboard_failures(failed: usize) -> Result<()> {
+    match failed {
+        0 => Ok(()),
+        1 => bail!(tr("1 project could not be loaded")),
+        n => bail!(tr!("{n} projects could not be loaded", n)),
+    }
+}
+
+fn print_task(index: usize, config: &Config) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let task = p.get_task(index)?;
+
+    if config.porcelain {
+        println!("{}", tutel::porcelain(task));
+        return Ok(());
+    }
+
+    let mut display = display_options(config);
+    display.width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize);
+
+    println!("{}", task.details(&display));
+
+    Ok(())
+}
+
+fn print_path() -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    println!("{}", fs::canonicalize(&p.path)?.display());
+    Ok(())
+}
+
+fn print_project_file() -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    // binary projects are printed as they are as well, for piping them
+    std::io::stdout().write_all(&fs::read(&p.path)?)?;
+    Ok(())
+}
+
+/// Writes the project in the new format next to the old file, which is
+/// removed afterwards
+fn convert(format: tutel::Format) -> Result<()> {
+    let mut project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    if tutel::Format::of(&project.path) == format {
+        bail!(tr!(
+            "the project is stored as {format} already",
+            format = format.name()
+        ));
+    }
+
+    let old = project.path.clone();
+    project.path = old.with_file_name(format.file_name());
+    if project.path.exists() {
+        bail!(tr!("{file} exists already", file = project.path.display()));
+    }
+    project.save()?;
+    fs::remove_file(&old)?;
+    let _ = tutel::register(&project.path);
+
+    println!(
+        "{}",
+        tr!(
+            "converted {old} to {new}",
+            old = old.display(),
+            new = project.path.display()
+        )
+    );
+    Ok(())
+}
+
+fn merge(
+    base: Option<&Path>,
+    ours: &Path,
+    theirs: &Path,
+    name: Option<&Path>,
+    auto: bool,
+) -> Result<()> {
+    let conflicts = tutel::merge(base, ours, theirs, name.unwrap_or(ours), auto)?;
+    for conflict in &conflicts {
+        eprintln!("{} {conflict}", "[conflict]".red());
+    }
+    if !conflicts.is_empty() {
+        bail!(tr!(
+            "{count} conflicts, kept our side of them",
+            count = conflicts.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Runs every command of the script against a copy of the project, see
+/// [`staged`]. Either all of them succeed, or nothing is changed.
+fn batch(file: Option<PathBuf>, config: &Config) -> Result<()> {
+    let script = match &file {
+        Some(file) => fs::read_to_string(file)
+            .with_context(|| tr!("unable to read {file}", file = file.display()))?,
+        None => std::io::read_to_string(std::io::stdin())?,
+    };
+
+    // nothing runs unless every line makes sense
+    let mut commands = Vec::new();
+    for (n, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let command = app::parse_line(line).map_err(|e| {
+            anyhow::anyhow!(tr!(
+                "line {line}: {error}",
+                line = n + 1,
+                error = e.trim_end()
+            ))
+        })?;
+        if matches!(command, Command::Batch(_)) {
+            bail!(tr!("line {line}: batches can't be nested", line = n + 1));
+        }
+        if reaches_outside(&command) {
+            bail!(tr!(
+                "line {line}: changes more than the project, which can't be part of a batch",
+                line = n + 1
+            ));
+        }
+        commands.push((n + 1, command));
+    }
+
+    staged(config, |config| {
+        commands.into_iter().try_for_each(|(n, command)| {
+            run_app(command, config)
+                .map_err(|e| e.context(tr!("line {n} failed, nothing was changed", n)))
+        })
+    })
+}
+
+/// Whether the command changes anything besides the project file and its
+/// archive, which a batch or transaction couldn't take back if a later
+/// command fails
+const fn reaches_outside(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::NewProject { .. }
+            | Command::Capture(_)
+            | Command::Triage
+            | Command::MarkCompletion {
+                comment: Some(_),
+                ..
+            }
+            | Command::Undo
+            | Command::Redo
+            | Command::Convert(_)
+            | Command::Merge { .. }
+            | Command::RemoveProject
+            | Command::PrintCompletion { install: true, .. }
+            | Command::Man {
+                output: Some(_),
+                ..
+            }
+    )
+}
+
+/// Runs `run` against copies of the project file and its archive, made in a
+/// temporary directory next to them. Only once it succeeds the copies replace
+/// the originals, as a single step to undo. Otherwise they are thrown away
+/// and nothing has changed.
+fn staged(config: &Config, run: impl FnOnce(&Config) -> Result<()>) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let dir = project
+        .path
+        .parent()
+        .context(tr("project file without a directory"))?;
+    // in the same directory, so the copies can be renamed over the originals
+    let stage = tempfile::Builder::new()
+        .prefix(".tutel.tx")
+        .tempdir_in(dir)?;
+    let copy = stage
+        .path()
+        .join(project.path.file_name().unwrap_or_default());
+    fs::copy(&project.path, &copy)?;
+    // archiving writes the archive next to the copy
+    let archive = project.path.with_file_name(tutel::ARCHIVE_FILE_NAME);
+    let archive_copy = copy.with_file_name(tutel::ARCHIVE_FILE_NAME);
+    if archive.is_file() {
+        fs::copy(&archive, &archive_copy)?;
+    }
+
+    // the originals are noted in the history once everything succeeded, and
+    // a search rebuilds the index that went stale
+    let inner = Config {
+        history: 0,
+        search_index: false,
+        ..config.clone()
+    };
+    tutel::stage(project.path.clone(), copy.clone());
+    let result = run(&inner);
+    tutel::unstage();
+    // dropping the stage deletes the copies
+    result?;
+
+    tutel::record(&project.path, config.history)?;
+    if archive_copy.is_file() {
+        fs::rename(&archive_copy, &archive)?;
+    }
+    fs::rename(&copy, &project.path)?;
+    Ok(())
+}
+
+/// Runs the commands against a copy of the project, see [`staged`]. Anyone
+/// watching the project file only ever sees it before or after all of them.
+fn transaction(commands: &[Vec<String>], config: &Config) -> Result<()> {
+    let commands = commands
+        .iter()
+        .map(|words| {
+            let command = app::parse_words(words)
+                .map_err(|e| anyhow::anyhow!("{}: {}", words.join(" "), e.trim_end()))?;
+            if matches!(command, Command::Batch(_) | Command::Transaction(_))
+                || reaches_outside(&command)
+            {
+                bail!(tr!(
+                    "{command}: can't be part of a transaction",
+                    command = words.join(" ")
+                ));
+            }
+            Ok(command)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    staged(config, |config| {
+        commands
+            .into_iter()
+            .try_for_each(|command| run_app(command, config))
+    })
+}
+
+fn print_board(width: Option<usize>, config: &Config) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+
+    let mut display = display_options(config);
+    display.width =
+        width.or_else(|| terminal_size::terminal_size().map(|(width, _)| width.0 as usize));
+
+    println!("{}", p.board(&display));
+
+    Ok(())
+}
+
+fn display_options(config: &Config) -> DisplayOptions {
+    let options = DisplayOptions {
+        theme: config.theme.clone(),
+        symbols: config.symbols.clone(),
+        ascii: false,
+        width: None,
+        layout: Layout::List,
+        hyperlinks: match config.hyperlinks {
+            Hyperlinks::Always => true,
+            Hyperlinks::Never => false,
+            Hyperlinks::Auto => supports_hyperlinks(),
+        },
+        urgency: config.urgency.sort.then(|| config.urgency.clone()),
+        rules: config.rules.clone(),
+        snoozed: false,
+        hidden: false,
+        waiting: false,
+        open: false,
+        limit: None,
+        stale_after: config.stale,
+        stale: false,
+        assignee: None,
+        mentions: None,
+        tag: None,
+        query: None,
+        group_by: None,
+        date_format: config.date_format.clone(),
+        emoji: config.emoji,
+        markdown: config.markdown,
+        plain: false,
+        porcelain: false,
+    };
+
+    if config.porcelain {
+        options.into_porcelain()
+    } else if config.plain {
+        options.into_plain()
+    } else if config.ascii {
+        options.into_ascii()
+    } else {
+        options
+    }
+}
+
+/// Guesses whether the terminal understands OSC 8 hyperlinks. Terminals
+/// that don't may print the escape sequences literally, so only the ones
+/// known to support them are considered.
+fn supports_hyperlinks() -> bool {
+    if !std::io::stdout().is_terminal() {
+        return false;
+    }
+
+    let var = |name| std::env::var(name).unwrap_or_default();
+
+    if ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"]
+        .contains(&var("TERM_PROGRAM").as_str())
+    {
+        return true;
+    }
+
+    if ["KITTY_WINDOW_ID", "WT_SESSION", "KONSOLE_VERSION"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some())
+    {
+        return true;
+    }
+
+    // VTE based terminals(gnome-terminal, tilix, ...) support them since 0.50
+    if var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000) {
+        return true;
+    }
+
+    let term = var("TERM");
+    ["kitty", "alacritty", "foot", "wezterm"]
+        .iter()
+        .any(|t| term.contains(t))
+}
+
+/// Runs on every prompt, so this never complains. Being outside of a project
+/// is the common case and errors would end up in the prompt.
+fn print_prompt(shell: Option<PromptShell>, config: &Config) {
+    let Ok(dir) = std::env::current_dir() else {
+        return;
+    };
+
+    if let Some(counts) = prompt::project_counts(&dir) {
+        print!("{}", prompt::segment(counts, shell, config));
+    }
+}
+
+/// Prints a line about the pending tasks, unless the shell just moved around
+/// within the same project
+fn print_hook_summary(previous: Option<PathBuf>, config: &Config) -> Result<()> {
+    let Ok(project) = tutel::load_project_rec(&std::env::current_dir()?) else {
+        return Ok(());
+    };
+    let previous = previous.and_then(|dir| tutel::load_project_rec(&dir).ok());
+    if previous.is_some_and(|p| p.path == project.path) {
+        return Ok(());
+    }
+
+    // the hook runs on every cd, big projects shouldn't slow that down
+    let key = project.path.to_string_lossy().to_string();
+    let summary = cache::cached("hook", &key, Duration::from_secs(10), || {
+        hook::summary(&project, config)
+    });
+    if let Some(summary) = summary {
+        println!("{summary}");
+    }
+    Ok(())
+}
+
+fn print_env() -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    for (name, value) in prompt::variables(&project) {
+        println!("export {name}={}", prompt::shell_quote(&value));
+    }
+    Ok(())
+}
+
+/// Prints every list with its number of pending tasks, marking the one in use
+fn print_lists(config: &Config) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let current = project.list.as_deref().unwrap_or(tutel::DEFAULT_LIST);
+
+    let mut lists: Vec<(&str, &[tutel::Task])> = project
+        .data
+        .lists
+        .iter()
+        .map(|(name, tasks)| (name.as_str(), tasks.as_slice()))
+        .collect();
+    lists.push((current, &project.data.tasks));
+    if !lists.iter().any(|(name, _)| *name == tutel::DEFAULT_LIST) {
+        lists.push((tutel::DEFAULT_LIST, &[]));
+    }
+    lists.sort_by_key(|(name, _)| (*name != tutel::DEFAULT_LIST, *name));
+
+    for (name, tasks) in lists {
+        let pending = tasks.iter().filter(|t| !t.is_completed()).count();
+        let marker = if name == current { '*' } else { ' ' };
+        println!(
+            "{marker} {name}: {}",
+            config
+                .theme
+                .pending
+                .paint(&format!("{pending} {}", tutel::tr("pending")))
+        );
+    }
+    Ok(())
+}
+
+/// Lists the open tasks with any of the tags and fails if there are any
+fn guard(tags: &[String], config: &Config) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let open: Vec<&tutel::Task> = project
+        .data
+        .tasks
+        .iter()
+        .filter(|t| !t.is_completed())
+        .filter(|t| tags.is_empty() || tags.iter().any(|tag| t.has_tag(tag)))
+        .collect();
+
+    if open.is_empty() {
+        return Ok(());
+    }
+
+    let display = display_options(config);
+    for task in &open {
+        eprintln!("{}", task.render(&display));
+    }
+    match tags {
+        [] => bail!(tr!("{count} tasks are still open", count = open.len())),
+        _ => bail!(tr!(
+            "{count} tasks tagged {tags} are still open",
+            count = open.len(),
+            tags = tags
+                .iter()
+                .map(|t| format!("#{}", t.trim_start_matches('#')))
+                .collect::<Vec<_>>()
+                .join(tr(" or "))
+        )),
+    }
+}
+
+fn print_status(format: StatusFormat, max_age: u64, config: &Config) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let render = || {
+        let counts = prompt::project_counts(&dir)?;
+        Some(match format {
+            StatusFormat::Plain => prompt::segment(counts, None, config),
+            StatusFormat::Starship => prompt::starship(counts, config),
+            StatusFormat::Tmux => prompt::tmux(counts, config),
+        })
+    };
+
+    let status = match format {
+        // tmux refreshes the status line every few seconds for every client,
+        // there is no need to look at the project file every time
+        StatusFormat::Tmux => {
+            let max_age = Duration::from_secs(max_age);
+            cache::cached("tmux", &dir.to_string_lossy(), max_age, render)
+        }
+        _ => render(),
+    };
+
+    let Some(status) = status else {
+        // lets status bars and starship's `when` hide the module
+        std::process::exit(1);
+    };
+    println!("{status}");
+
+    Ok(())
+}
 
+fn add(
+    desc: String,
+    completed: bool,
+    estimate: Option<u64>,
+    assignee: Option<String>,
+    force: bool,
+    config: &Config,
+) -> Result<()> {
+    let mut p = load_project(config)?;
+    if let Some(task) = p.find_duplicate(&desc).filter(|_| !force) {
+        bail!(tr!(
+            "task {index} looks the same: {desc}. use --force to add it anyway",
+            index = task.index,
+            desc = task.desc
+        ));
+    }
+    let task = p.add(desc, completed);
+    task.estimate = estimate;
+    task.assignee = assignee;
+    p.save()?;
     Ok(())
 }
 
-fn add(desc: String, completed: bool) -> Result<()> {
-    let mut p = tutel::load_project_rec(&*std::env::current_dir()?)?;
-    p.add(desc, completed);
+/// Adds the tasks of a template, asking for the values of its placeholders
+fn add_template(name: &str, config: &Config) -> Result<()> {
+    let Some(template) = config.templates.iter().find(|t| t.name == name) else {
+        bail!(tr!("no such template: {name}", name));
+    };
+
+    let mut values = Vec::new();
+    for placeholder in template.placeholders() {
+        let value = ask(&format!("{placeholder}: "))?;
+        values.push((placeholder, value));
+    }
+
+    let mut p = load_project(config)?;
+    for desc in template.expand(&values)? {
+        p.add(desc, false);
+    }
+    p.save()
+}
+
+/// Adds a task, asking for each field with the values already in use as hints.
+/// Everything but the description can be skipped with enter.
+fn add_interactive(config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+
+    let mut desc = String::new();
+    while desc.is_empty() {
+        desc = ask(&format!("{}: ", tr("description")))?;
+    }
+    if let Some(task) = p.find_duplicate(&desc) {
+        let question = tr!(
+            "task {index} looks the same, add it anyway? [y/N]: ",
+            index = task.index
+        );
+        if !ask(&question)?.eq_ignore_ascii_case("y") {
+            return Ok(());
+        }
+    }
+
+    let mut tags: Vec<&str> = p.data.tasks.iter().flat_map(tutel::Task::tags).collect();
+    tags.sort_unstable();
+    tags.dedup();
+    let hint = tags.iter().map(|t| format!("#{t}")).collect::<Vec<_>>();
+    let tags = ask(&format!("{} {}: ", tr("tags"), hints(&hint)))?;
+    for tag in tags.split_whitespace() {
+        desc.push_str(" #");
+        desc.push_str(tag.trim_start_matches('#'));
+    }
+
+    let estimate = loop {
+        let estimate = ask(tr("estimate, e.g. 30m or 2h [none]: "))?;
+        if estimate.is_empty() {
+            break None;
+        }
+        match tutel::parse_duration(&estimate) {
+            Some(estimate) => break Some(estimate),
+            None => eprintln!("{}", tr!("invalid duration: {estimate}", estimate)),
+        }
+    };
+
+    let mut assignees: Vec<String> = p
+        .data
+        .tasks
+        .iter()
+        .filter_map(|t| t.assignee.clone())
+        .collect();
+    assignees.sort_unstable();
+    assignees.dedup();
+    let assignee = ask(&format!("{} {}: ", tr("assignee"), hints(&assignees)))?;
+
+    let pinned = ask(tr("pin it to the top? [y/N]: "))?.eq_ignore_ascii_case("y");
+
+    let task = p.add(desc, false);
+    task.estimate = estimate;
+    task.assignee = Some(assignee).filter(|a| !a.is_empty());
+    task.pinned = pinned;
+    let index = task.index;
     p.save()?;
+    println!("{}", tr!("added task {index}", index));
+    Ok(())
+}
+
+/// Known values to pick from, or that nothing is the default
+fn hints(known: &[String]) -> String {
+    if known.is_empty() {
+        tr("[none]").to_string()
+    } else {
+        tr!("[none, or {known}]", known = known.join(", "))
+    }
+}
+
+/// Prints the question and returns the trimmed answer. The end of input
+/// aborts, so a wizard never loops forever on a closed stdin.
+fn ask(question: &str) -> Result<String> {
+    print!("{question}");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        bail!(tr("aborted"));
+    }
+    Ok(answer.trim().to_string())
+}
+
+fn capture(desc: String) -> Result<()> {
+    let mut inbox = tutel::load_inbox()?;
+    inbox.add(desc, false);
+    inbox.save()
+}
+
+/// Asks where every task in the inbox belongs. The inbox is saved after
+/// every step, so quitting halfway doesn't lose anything.
+fn triage(config: &Config) -> Result<()> {
+    let mut inbox = tutel::load_inbox()?;
+    let indices: Vec<usize> = inbox.data.tasks.iter().map(|t| t.index).collect();
+
+    if indices.is_empty() {
+        println!("{}", tr("the inbox is empty"));
+        return Ok(());
+    }
+
+    for index in indices {
+        let desc = &inbox.get_task(index)?.desc;
+        println!("{} {desc}", format!("{index:03}").bold());
+        print!(
+            "{}",
+            tr("move to which directory? [path, s to skip, d to delete, q to quit]: ")
+        );
+        std::io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            break;
+        }
+
+        match answer.trim() {
+            "" | "s" => continue,
+            "q" => break,
+            "d" => inbox.remove(index),
+            dir => {
+                let mut project = tutel::load_project_rec(&PathBuf::from(dir))
+                    .with_context(|| tr!("no project for {dir}", dir))?;
+                project.archive_after = config.archive_after;
+
+                let mut task = inbox.get_task(index)?.clone();
+                task.index = project.next_index();
+                // the indices it depends on mean other tasks over there
+                if !task.depends.is_empty() {
+                    task.depends.clear();
+                    if task.status == tutel::Status::Blocked {
+                        task.set_status(tutel::Status::Pending);
+                    }
+                }
+                project.data.tasks.push(task);
+                // saved first, a duplicate is better than a lost task
+                project.save()?;
+                inbox.remove(index);
+                println!("{}", tr!("moved to {path}", path = project.path.display()));
+            }
+        }
+        inbox.save()?;
+    }
+
     Ok(())
 }
 
-fn done(selector: TaskSelector, completed: bool) -> Result<()> {
-    let mut p = tutel::load_project_rec(&*std::env::current_dir()?)?;
+/// Adds a task that never went through the list, as if it was completed at
+/// the given time
+fn log(desc: String, at: Option<u64>, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+    let task = p.add(desc, true);
+    if let Some(at) = at {
+        task.created = Some(at);
+        task.closed = Some(at);
+    }
+    p.save()
+}
+
+fn done(
+    selector: TaskSelector,
+    completed: bool,
+    note: Option<String>,
+    comment: Option<tutel::CommentAction>,
+    config: &Config,
+) -> Result<()> {
+    let mut p = load_project(config)?;
+
+    let selector = match selector {
+        TaskSelector::Indexed(_) | TaskSelector::All => selector,
+        selector => TaskSelector::Indexed(selected(&p, &selector)),
+    };
 
     match selector {
         TaskSelector::Indexed(indices) => {
             for index in indices {
-                p.mark_completion(index, completed)?;
+                match &note {
+                    Some(note) => p.complete_with_note(index, note.as_str())?,
+                    None => p.mark_completion(index, completed)?,
+                }
+                if let Some(action) = comment.filter(|_| completed) {
+                    resolve_comments(&p, index, action)?;
+                }
+            }
+        }
+        TaskSelector::All => {
+            p.mark_completion_all(completed);
+            if let Some(note) = note {
+                for t in &mut p.data.tasks {
+                    t.note = Some(note.clone());
+                }
             }
         }
-        TaskSelector::All => p.mark_completion_all(completed),
-        TaskSelector::Completed => unreachable!(),
+        TaskSelector::Completed(_)
+        | TaskSelector::Pending
+        | TaskSelector::Tagged(_)
+        | TaskSelector::Matching(_) => unreachable!(),
     }
 
+    report_unblocked(&mut p)?;
     p.save()?;
 
     Ok(())
 }
 
-fn remove(selector: TaskSelector) -> Result<()> {
-    let mut p = tutel::load_project_rec(&*std::env::current_dir()?)?;
+/// The indices of the tasks the selector stands for
+fn selected(p: &tutel::Project, selector: &TaskSelector) -> Vec<usize> {
+    if let TaskSelector::Indexed(indices) = selector {
+        return indices.clone();
+    }
+    p.data
+        .tasks
+        .iter()
+        .filter(|t| match selector {
+            TaskSelector::Indexed(_) | TaskSelector::All => true,
+            TaskSelector::Completed(before) => t.is_completed_before(*before),
+            TaskSelector::Pending => !t.is_completed(),
+            TaskSelector::Tagged(tag) => t.has_tag(tag),
+            TaskSelector::Matching(query) => query.matches(t, tutel::now()),
+        })
+        .map(|t| t.index)
+        .collect()
+}
+
+/// Deletes or annotates the TODO comments at the places the task refers to
+fn resolve_comments(p: &tutel::Project, index: usize, action: tutel::CommentAction) -> Result<()> {
+    let dir = p
+        .path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."))
+        .canonicalize()?;
+    for (file, line) in tutel::find_locations(&p.get_task(index)?.desc) {
+        let Ok(path) = dir.join(file).canonicalize() else {
+            continue;
+        };
+        if !path.is_file() {
+            continue;
+        }
+        // a description is no reason to touch files elsewhere
+        if !path.starts_with(&dir) {
+            println!(
+                "{}",
+                tr!("{file} is outside of the project, leaving it alone", file)
+            );
+            continue;
+        }
+        if !tutel::resolve_comment(&path, line, action)? {
+            println!(
+                "{}",
+                tr!(
+                    "no TODO comment in {file}:{line}, leaving it alone",
+                    file,
+                    line
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Unblocks the tasks that don't have to wait anymore and tells which ones
+fn report_unblocked(p: &mut tutel::Project) -> Result<()> {
+    for index in p.unblock() {
+        println!(
+            "{}",
+            tr!(
+                "now unblocked: {index} {desc}",
+                index = format!("{index:03}"),
+                desc = p.get_task(index)?.desc
+            )
+        );
+    }
+    Ok(())
+}
+
+fn set_task(index: usize, status: tutel::Status, force: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+    if status == tutel::Status::InProgress {
+        check_wip_limit(&p, index, force, config)?;
+    }
+    p.set_status(index, status)?;
+    report_unblocked(&mut p)?;
+    p.save()
+}
+
+/// Warns if putting the task in progress exceeds the configured limit, or
+/// fails if the limit is strict and not overridden
+fn check_wip_limit(
+    project: &tutel::Project,
+    index: usize,
+    force: bool,
+    config: &Config,
+) -> Result<()> {
+    let Some(limit) = config.wip.limit else {
+        return Ok(());
+    };
+
+    let task = project.get_task(index)?;
+    if task.is_completed() || task.status == tutel::Status::InProgress {
+        return Ok(());
+    }
+
+    let in_progress = project
+        .data
+        .tasks
+        .iter()
+        .filter(|t| t.status == tutel::Status::InProgress)
+        .count();
+    if in_progress < limit {
+        return Ok(());
+    }
+
+    let message = tr!(
+        "the limit of {limit} tasks in progress is reached already",
+        limit
+    );
+    if config.wip.strict && !force {
+        bail!(tr!("{message}. use --force to go ahead anyway", message));
+    }
+    eprintln!("{} {message}", "[tutel]".yellow());
+
+    Ok(())
+}
+
+fn pin(index: usize, pinned: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+    p.get_task_mut(index)?.pinned = pinned;
+    p.save()
+}
+
+fn hide(index: usize, hidden: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+    p.get_task_mut(index)?.hidden = hidden;
+    p.save()
+}
+
+fn depend(index: usize, on: Dependency, depends: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+
+    match on {
+        Dependency::Index(on) => p.depend(index, on, depends)?,
+        Dependency::External(reference) => {
+            let task = p.get_task_mut(index)?;
+            task.external.retain(|r| *r != reference);
+            if depends {
+                // only to make sure it exists
+                tutel::resolve(&reference)?;
+                task.external.push(reference);
+            }
+        }
+    }
+
+    p.save()
+}
+
+fn print_graph(format: GraphFormat) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    match format {
+        GraphFormat::Dot => print!("{}", tutel::dot(&p)),
+        GraphFormat::Mermaid => print!("{}", tutel::mermaid(&p)),
+    }
+    Ok(())
+}
+
+fn remove(selector: TaskSelector, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
 
     match selector {
         TaskSelector::Indexed(indices) => {
@@ -86,60 +1405,830 @@ fn remove(selector: TaskSelector) -> Result<()> {
             }
         }
         TaskSelector::All => p.remove_all(),
-        TaskSelector::Completed => p.remove_completed(),
+        TaskSelector::Completed(before) => p.remove_completed(before),
+        TaskSelector::Pending => p.remove_pending(),
+        TaskSelector::Tagged(tag) => p.remove_where(|t| t.has_tag(&tag)),
+        TaskSelector::Matching(query) => {
+            let now = tutel::now();
+            p.remove_where(|t| query.matches(t, now));
+        }
     }
 
+    report_unblocked(&mut p)?;
+    p.save()?;
+
+    Ok(())
+}
+
+fn undo(config: &Config) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    tutel::undo(&p.path, config.history)?;
+    println!("{}", tr("undid the last change, tutel redo brings it back"));
+    Ok(())
+}
+
+fn redo(config: &Config) -> Result<()> {
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    tutel::redo(&p.path, config.history)?;
+    println!("{}", tr("redid the last undone change"));
+    Ok(())
+}
+
+fn archive(auto: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+
+    let before = if auto {
+        let Some(after) = config.archive_after else {
+            bail!(tr(
+                "set 'after' in the [archive] table of the config to use --auto"
+            ));
+        };
+        Some(tutel::now().saturating_sub(after))
+    } else {
+        None
+    };
+
+    let archived = p.archive(before)?;
     p.save()?;
+    if archived == 1 {
+        println!("{}", tr("archived 1 task"));
+    } else {
+        println!("{}", tr!("archived {archived} tasks", archived));
+    }
 
     Ok(())
 }
 
 fn remove_project() -> Result<()> {
-    let p = tutel::load_project_rec(&*std::env::current_dir()?)?;
+    let p = tutel::load_project_rec(&std::env::current_dir()?)?;
+    // a checklist tutel didn't create is the user's own file
+    if !tutel::Format::of(&p.path).is_project(&p.path) {
+        bail!(tr!("{file} is not a project", file = p.path.display()));
+    }
+
+    p.delete()
+}
+
+fn print_completions(shell: Option<String>, install: bool) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None if install => completions::detect_shell()?,
+        None => bail!(tr("no shell given")),
+    };
+
+    if install {
+        let (path, hint) = completions::install(&shell)?;
+        println!(
+            "{}",
+            tr!(
+                "installed {shell} completions to {path}",
+                shell,
+                path = path.display()
+            )
+        );
+        if let Some(hint) = hint {
+            println!("{hint}");
+        }
+    } else {
+        println!("{}", completions::script(&shell)?);
+    }
 
-    fs::remove_file(p.path).context("could not delete project file")
+    Ok(())
 }
 
-fn print_completions(shell: &str) -> Result<()> {
-    match shell {
-        "bash" => println!("{}", BASH_COMPLETIONS),
-        "zsh" => println!("{}", ZSH_COMPLETIONS),
-        "fish" => println!("{}", FISH_COMPLETIONS),
-        "elvish" => println!("{}", ELVISH_COMPLETIONS),
-        _ => bail!("no such shell: shell"),
+fn print_man(output: Option<PathBuf>, command: Option<String>) -> Result<()> {
+    let main_help = app::help(None).context(tr("couldn't render the help"))?;
+    let commands = man::commands(&main_help);
+
+    let page = |command: Option<&str>| -> Result<String> {
+        let Some(command) = command else {
+            let mut page = man::render("tutel", env!("CARGO_PKG_DESCRIPTION"), &main_help);
+            let see_also: Vec<String> = commands
+                .iter()
+                .map(|(name, _)| format!("\\fBtutel\\-{name}\\fR(1)"))
+                .collect();
+            page.push_str(&format!(".SH SEE ALSO\n{}\n", see_also.join(", ")));
+            return Ok(page);
+        };
+
+        let (_, summary) = commands
+            .iter()
+            .find(|(name, _)| name == command)
+            .with_context(|| tr!("no such command: {command}", command))?;
+        let help = app::help(Some(command)).context(tr("couldn't render the help"))?;
+        Ok(man::render(&format!("tutel-{command}"), summary, &help))
+    };
+
+    let Some(dir) = output else {
+        print!("{}", page(command.as_deref())?);
+        return Ok(());
     };
 
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("tutel.1"), page(None)?)?;
+    for (name, _) in &commands {
+        fs::write(dir.join(format!("tutel-{name}.1")), page(Some(name))?)?;
+    }
+
     Ok(())
 }
 
-fn edit_task(index: usize, editor: String) -> Result<()> {
-    let mut project = tutel::load_project_rec(&*std::env::current_dir()?)?;
+fn edit_task(
+    selector: TaskSelector,
+    editor: String,
+    yes: bool,
+    changes: &TaskChanges,
+    config: &Config,
+) -> Result<()> {
+    let mut project = load_project(config)?;
+
+    let index = match selector {
+        TaskSelector::Indexed(indices) if indices.len() == 1 => indices[0],
+        _ if changes.is_empty() => {
+            bail!(tr(
+                "only a single task can be edited in the editor, set the fields with flags"
+            ))
+        }
+        selector => {
+            let indices = selected(&project, &selector);
+            return edit_tasks(&mut project, &indices, changes);
+        }
+    };
     let task = project.get_task_mut(index)?;
 
+    if !changes.is_empty() {
+        apply_changes(task, changes);
+        return project.save();
+    }
+
     let mut tmpfile = NamedTempFile::new()?;
     tmpfile.write_all(task.desc.as_bytes())?;
+    // windows doesn't let editors replace a file that is still open
+    let tmpfile = tmpfile.into_temp_path();
+
+    run_editor(&editor, &tmpfile, None)?;
+
+    // Write changes, lines() takes care of windows line endings as well
+    let new = fs::read_to_string(&tmpfile)?;
+    let new = new.lines().collect::<Vec<_>>().join(" ");
+
+    let changes = diff::render(
+        &format!("{:03} {}", task.index, task.desc),
+        &format!("{:03} {new}", task.index),
+    );
+    if changes.is_empty() {
+        println!("{}", tr("no changes"));
+        return Ok(());
+    }
+    // declining is not a failure
+    if !confirm(&changes, yes)? {
+        println!("{}", tr("the task is unchanged"));
+        return Ok(());
+    }
+    task.desc = new;
+
+    project.save()?;
+
+    Ok(())
+}
+
+/// Shows what is about to change and asks whether to go ahead, unless the
+/// user said yes up front. Editors are quick to truncate or mangle a line,
+/// this is the last chance to notice.
+fn confirm(changes: &str, yes: bool) -> Result<bool> {
+    print!("{changes}");
+    if yes {
+        return Ok(true);
+    }
+    let answer = ask(tr("save these changes? [y/N]: "))?;
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+/// Changes the fields of several tasks at once and lists what changed
+fn edit_tasks(
+    project: &mut tutel::Project,
+    indices: &[usize],
+    changes: &TaskChanges,
+) -> Result<()> {
+    let mut changed = 0;
+    for index in indices {
+        let task = project.get_task_mut(*index)?;
+        let fields = apply_changes(task, changes);
+        if !fields.is_empty() {
+            println!("{:03} {}: {}", task.index, task.desc, fields.join(", "));
+            changed += 1;
+        }
+    }
+    println!(
+        "{}",
+        tr!(
+            "changed {changed} of {count} tasks",
+            changed,
+            count = indices.len()
+        )
+    );
+    project.save()
+}
+
+/// Returns the names of the fields that actually changed
+fn apply_changes(task: &mut tutel::Task, changes: &TaskChanges) -> Vec<&'static str> {
+    let before = task.clone();
+    if let Some(desc) = &changes.desc {
+        task.desc.clone_from(desc);
+    }
+    if let Some(tag) = &changes.remove_tag {
+        task.remove_tag(tag);
+    }
+    if let Some(tag) = &changes.add_tag {
+        task.add_tag(tag);
+    }
+    if changes.estimate.is_some() {
+        task.estimate = changes.estimate;
+    }
+    if let Some(assignee) = &changes.assignee {
+        task.assignee = Some(assignee.clone()).filter(|a| !a.is_empty());
+    }
+    if let Some(due) = changes.due {
+        task.due = due;
+    }
+    if let Some(priority) = changes.priority {
+        task.priority = priority;
+    }
+    if let Some(pinned) = changes.pinned {
+        task.pinned = pinned;
+    }
+
+    let mut fields = Vec::new();
+    if task.desc != before.desc {
+        // tags are part of the description
+        fields.push(if changes.desc.is_some() {
+            "description"
+        } else {
+            "tags"
+        });
+    }
+    if task.estimate != before.estimate {
+        fields.push("estimate");
+    }
+    if task.assignee != before.assignee {
+        fields.push("assignee");
+    }
+    if task.due != before.due {
+        fields.push("due");
+    }
+    if task.priority != before.priority {
+        fields.push("priority");
+    }
+    if task.pinned != before.pinned {
+        fields.push("pinned");
+    }
+    fields
+}
+
+/// Opens the file in the editor, at the line if given, and waits until it is
+/// closed
+fn run_editor(editor: &str, file: &std::path::Path, line: Option<usize>) -> Result<()> {
+    // $EDITOR may contain arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().context(tr("no editor given"))?;
 
-    // Spawn editor process
-    let mut cmd = std::process::Command::new(editor.as_str())
-        .arg(tmpfile.path())
+    // `+N` is understood by vi, emacs, nano, micro, helix and others
+    let mut cmd = std::process::Command::new(program)
+        .args(words)
+        .args(line.map(|line| format!("+{line}")))
+        .arg(file)
         .spawn()
-        .context("editor {editor} not found")?;
+        .with_context(|| tr!("editor {editor} not found", editor))?;
 
     cmd.wait()?;
+    Ok(())
+}
+
+/// Lets the user edit the project file by hand. The edited file is only
+/// written back if it still parses, otherwise the user gets to fix it or
+/// throw the changes away.
+fn edit_project(editor: &str, yes: bool, config: &Config) -> Result<()> {
+    let project = load_project(config)?;
+    // rather than after the editing is done
+    tutel::writable()?;
+    if !tutel::Format::of(&project.path).is_text() {
+        bail!(tr(
+            "binary projects can't be edited, convert them to toml first"
+        ));
+    }
+    let original = fs::read_to_string(&project.path)?;
 
-    // Write changes
-    let new = fs::read_to_string(tmpfile.path())?;
-    task.desc = new.replace('\n', " ");
+    // the extension gives editors a hint for syntax highlighting, and tells
+    // how to parse the file
+    let mut tmpfile = tempfile::Builder::new()
+        .suffix(&extension(&project.path))
+        .tempfile()?;
+    tmpfile.write_all(original.as_bytes())?;
+    let tmpfile = tmpfile.into_temp_path();
+
+    let edited = loop {
+        run_editor(editor, &tmpfile, None)?;
+        match tutel::Project::load(tmpfile.to_path_buf(), 0) {
+            Ok(edited) => break edited,
+            Err(e) => {
+                eprintln!("{} {e:#}", "[tutel]".red());
+                print!("{}", tr("edit again? [Y/n]: "));
+                std::io::stdout().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if answer.trim().eq_ignore_ascii_case("n") {
+                    println!("{}", tr("the project file is unchanged"));
+                    return Ok(());
+                }
+            }
+        }
+    };
 
+    let text = fs::read_to_string(&tmpfile)?;
+    if text == original {
+        println!("{}", tr("no changes"));
+        return Ok(());
+    }
+    let changes = diff_tasks(&project, &edited);
+    // changes outside the tasks, like comments, are not worth asking about
+    if !changes.is_empty() && !confirm(&changes, yes)? {
+        println!("{}", tr("the project file is unchanged"));
+        return Ok(());
+    }
+    project.write(text.as_bytes())?;
+
+    println!("{}", summarize_changes(&project, &edited));
+    Ok(())
+}
+
+/// The extension of the file with its dot, `.toml` or `.md` for projects
+fn extension(path: &std::path::Path) -> String {
+    path.extension()
+        .map_or_else(String::new, |ext| format!(".{}", ext.to_string_lossy()))
+}
+
+/// The tasks that were added, removed or changed, going by their ids, as a
+/// diff of their fields
+fn diff_tasks(old: &tutel::Project, new: &tutel::Project) -> String {
+    let render = |task: Option<&tutel::Task>| {
+        task.map_or_else(String::new, |task| {
+            let fields = toml::to_string(task).unwrap_or_default();
+            format!("[{:03}]\n{fields}", task.index)
+        })
+    };
+
+    let removed_or_changed = old
+        .data
+        .tasks
+        .iter()
+        .map(|task| diff::render(&render(Some(task)), &render(new.find_id(&task.id).ok())));
+    let added = new
+        .data
+        .tasks
+        .iter()
+        .filter(|task| old.find_id(&task.id).is_err())
+        .map(|task| diff::render("", &render(Some(task))));
+    removed_or_changed.chain(added).collect()
+}
+
+/// How many tasks were added, removed and changed, going by their ids
+fn summarize_changes(old: &tutel::Project, new: &tutel::Project) -> String {
+    let same =
+        |a: &tutel::Task, b: &tutel::Task| toml::to_string(a).ok() == toml::to_string(b).ok();
+    let added = new
+        .data
+        .tasks
+        .iter()
+        .filter(|t| old.find_id(&t.id).is_err())
+        .count();
+    let removed = old
+        .data
+        .tasks
+        .iter()
+        .filter(|t| new.find_id(&t.id).is_err())
+        .count();
+    let changed = old
+        .data
+        .tasks
+        .iter()
+        .filter(|t| new.find_id(&t.id).is_ok_and(|n| !same(t, n)))
+        .count();
+
+    tr!(
+        "{added} added, {removed} removed, {changed} changed",
+        added,
+        removed,
+        changed
+    )
+}
+
+fn start(index: usize, force: bool, config: &Config) -> Result<()> {
+    let mut project = load_project(config)?;
+    check_wip_limit(&project, index, force, config)?;
+    project.start(index)?;
+    project.save()
+}
+
+fn stop(config: &Config) -> Result<()> {
+    let mut project = load_project(config)?;
+
+    let stopped = project.stop();
+    if stopped.is_empty() {
+        bail!(tr("no task is active"));
+    }
+
+    for index in stopped {
+        let task = project.get_task(index)?;
+        let worked = task.intervals.last().map_or(0, |i| i.duration(0));
+        println!(
+            "{}",
+            tr!(
+                "stopped task {index} after {duration}",
+                index,
+                duration = tutel::format_duration(worked)
+            )
+        );
+    }
+
+    project.save()
+}
+
+fn snooze(index: usize, until: u64, config: &Config) -> Result<()> {
+    let mut project = load_project(config)?;
+    let task = project.get_task_mut(index)?;
+
+    if until > tutel::now() {
+        task.snoozed = Some(until);
+    } else {
+        // snoozing until a time that has passed wakes the task up again
+        task.snoozed = None;
+    }
+
+    project.save()
+}
+
+fn wait(index: usize, on: Option<String>, config: &Config) -> Result<()> {
+    let mut project = load_project(config)?;
+    project.get_task_mut(index)?.waiting = on;
+    project.save()
+}
+
+/// Runs a pomodoro for the task and the break after it. The pomodoro is only
+/// recorded once the timer ran out, so interrupting it leaves no trace.
+fn pomodoro(index: usize, work: Option<u64>, rest: Option<u64>, config: &Config) -> Result<()> {
+    let work = work.unwrap_or(config.pomodoro.work);
+    let rest = rest.unwrap_or(config.pomodoro.rest);
+
+    let mut project = load_project(config)?;
+    let desc = project.get_task(index)?.desc.clone();
+
+    // time must not be tracked twice
+    if !project.stop().is_empty() {
+        project.save()?;
+    }
+
+    let start = tutel::now();
+    countdown(&tr!("task {index}: {desc}", index, desc), work)?;
+    let end = tutel::now();
+
+    // the list might have been changed in the meantime
+    let mut project = load_project(config)?;
+    let task = project.get_task_mut(index)?;
+    task.intervals.push(tutel::Interval {
+        start,
+        end: Some(end),
+    });
+    task.pomodoros += 1;
+    let pomodoros = task.pomodoros;
     project.save()?;
 
+    notify::send(
+        tr("Pomodoro finished"),
+        &tr!("{desc} ({pomodoros} so far)", desc, pomodoros),
+    );
+
+    if rest > 0 {
+        countdown(tr("break"), rest)?;
+        notify::send(tr("Break is over"), &tr!("back to {desc}", desc));
+    }
+
+    Ok(())
+}
+
+/// Waits for the given number of seconds, showing the time left on terminals
+fn countdown(label: &str, secs: u64) -> Result<()> {
+    let mut stdout = std::io::stdout();
+
+    if !stdout.is_terminal() {
+        println!(
+            "{}",
+            tr!(
+                "{duration} of {label}",
+                duration = tutel::format_duration(secs),
+                label
+            )
+        );
+        std::thread::sleep(Duration::from_secs(secs));
+        return Ok(());
+    }
+
+    for left in (1..=secs).rev() {
+        write!(stdout, "\r\x1b[2K{:02}:{:02} {label}", left / 60, left % 60)?;
+        stdout.flush()?;
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    writeln!(stdout, "\r\x1b[2K00:00 {label}")?;
+
     Ok(())
 }
 
+fn print_timesheet(
+    since: Option<u64>,
+    by: TimesheetBy,
+    csv: bool,
+    query: Option<&tutel::Query>,
+    config: &Config,
+) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let now = tutel::now();
+    let mut entries = tutel::timesheet(&project, since.unwrap_or(0), now);
+    if let Some(query) = query {
+        entries.retain(|e| {
+            project
+                .get_task(e.index)
+                .is_ok_and(|t| query.matches(t, now))
+        });
+    }
+
+    if csv {
+        let quote = |text: &str| format!("\"{}\"", text.replace('"', "\"\""));
+        let hours = |secs| secs as f64 / 3600.0;
+        match by {
+            TimesheetBy::Day => {
+                println!("date,index,description,hours");
+                for e in &entries {
+                    let hours = hours(e.secs);
+                    println!("{},{},{},{hours:.2}", e.day, e.index, quote(&e.desc));
+                }
+            }
+            TimesheetBy::Task => {
+                println!("index,description,hours");
+                for (index, desc, secs) in timesheet_by_task(&entries) {
+                    let hours = hours(secs);
+                    println!("{index},{},{hours:.2}", quote(desc));
+                }
+            }
+            TimesheetBy::Tag => {
+                println!("tag,hours");
+                for (tag, secs) in tutel::timesheet_by_tag(&entries) {
+                    let hours = hours(secs);
+                    println!("{},{hours:.2}", quote(tag.as_deref().unwrap_or_default()));
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{}", tr("no time tracked"));
+        return Ok(());
+    }
+
+    let theme = &config.theme;
+    let duration = |secs| format!("{:>7}", tutel::format_duration(secs));
+
+    if by == TimesheetBy::Task {
+        for (index, desc, secs) in timesheet_by_task(&entries) {
+            // compared with the estimate, if there is one
+            let estimate = project
+                .get_task(index)
+                .ok()
+                .and_then(|t| t.estimate)
+                .map(|estimate| format!(" / {}", tutel::format_duration(estimate)))
+                .unwrap_or_default();
+            // how it was resolved, if it was
+            let note = project
+                .get_task(index)
+                .ok()
+                .and_then(|t| t.note.as_deref())
+                .map(|note| format!(" ({note})"))
+                .unwrap_or_default();
+            // padded like a day, to line up with the total
+            let index = theme.index.paint(&format!("{index:03}"));
+            println!(
+                "{index}{:7}  {}{estimate:10}  {desc}{note}",
+                "",
+                duration(secs)
+            );
+        }
+    } else if by == TimesheetBy::Tag {
+        for (tag, secs) in tutel::timesheet_by_tag(&entries) {
+            let label = tag.map_or_else(|| tr("untagged").to_string(), |tag| format!("#{tag}"));
+            println!(
+                "{}  {}",
+                theme.header.paint(&format!("{label:10}")),
+                duration(secs)
+            );
+        }
+    } else {
+        let mut days: Vec<&str> = entries.iter().map(|e| e.day.as_str()).collect();
+        days.dedup();
+        for day in days {
+            let secs = entries
+                .iter()
+                .filter(|e| e.day == day)
+                .map(|e| e.secs)
+                .sum();
+            let label = config.date_format.format_day(day, tutel::now());
+            println!(
+                "{}  {}",
+                theme.header.paint(&format!("{label:10}")),
+                duration(secs)
+            );
+            for e in entries.iter().filter(|e| e.day == day) {
+                let index = theme.index.paint(&format!("{:03}", e.index));
+                println!("  {index} {}  {}", duration(e.secs), e.desc);
+            }
+        }
+    }
+
+    let total = entries.iter().map(|e| e.secs).sum();
+    println!(
+        "{}  {}",
+        theme.header.paint(&format!("{:10}", tr("total"))),
+        duration(total)
+    );
+
+    Ok(())
+}
+
+/// The time of every task with its description, ordered by index
+fn timesheet_by_task(entries: &[tutel::Entry]) -> Vec<(usize, &str, u64)> {
+    let mut tasks: Vec<(usize, &str, u64)> = Vec::new();
+    for e in entries {
+        match tasks.iter_mut().find(|(index, ..)| *index == e.index) {
+            Some((.., secs)) => *secs += e.secs,
+            None => tasks.push((e.index, &e.desc, e.secs)),
+        }
+    }
+    tasks.sort_unstable_by_key(|(index, ..)| *index);
+    tasks
+}
+
+fn open(index: usize, editor: &str) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let task = project.get_task(index)?;
+
+    // places in the code the task is about come first
+    let dir = project
+        .path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let location = tutel::find_locations(&task.desc)
+        .into_iter()
+        .map(|(file, line)| (dir.join(file), line))
+        .find(|(file, _)| file.is_file());
+    if let Some((file, line)) = location {
+        return run_editor(editor, &file, Some(line));
+    }
+
+    let attachments: Vec<String> = task
+        .attachments
+        .iter()
+        .map(|a| {
+            if a.contains("://") {
+                a.clone()
+            } else {
+                dir.join(a).to_string_lossy().to_string()
+            }
+        })
+        .collect();
+    let mut urls = tutel::find_urls(&task.desc);
+    urls.extend(attachments.iter().map(String::as_str));
+
+    let selected = match urls.len() {
+        0 => bail!(tr!(
+            "task {index} doesn't contain any urls or attachments",
+            index
+        )),
+        1 => urls,
+        _ => select_urls(urls)?,
+    };
+
+    for url in selected {
+        open_url(url)?;
+    }
+
+    Ok(())
+}
+
+fn set_alias(index: usize, alias: Option<String>, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+    p.set_alias(index, alias)?;
+    p.save()
+}
+
+fn attach(index: usize, target: String, remove: bool, config: &Config) -> Result<()> {
+    let mut p = load_project(config)?;
+
+    let attachment = if target.contains("://") || (remove && !Path::new(&target).exists()) {
+        target
+    } else {
+        attachment_path(&p, &target)?
+    };
+    let task = p.get_task_mut(index)?;
+    task.attachments.retain(|a| *a != attachment);
+    if !remove {
+        task.attachments.push(attachment);
+    }
+
+    p.save()
+}
+
+/// Files inside of the project are kept relative to it, so they are found
+/// from every directory and after moving the project
+fn attachment_path(project: &tutel::Project, file: &str) -> Result<String> {
+    let file = Path::new(file)
+        .canonicalize()
+        .with_context(|| tr!("no such file: {file}", file))?;
+    let relative = project
+        .path
+        .parent()
+        .and_then(|dir| dir.canonicalize().ok())
+        .and_then(|dir| file.strip_prefix(dir).ok().map(Path::to_path_buf));
+    Ok(relative.unwrap_or(file).to_string_lossy().to_string())
+}
+
+/// Asks which of the urls should be opened
+fn select_urls(urls: Vec<&str>) -> Result<Vec<&str>> {
+    for (i, url) in urls.iter().enumerate() {
+        println!("{} {url}", format!("{})", i + 1).bold());
+    }
+    print!(
+        "{}",
+        tr!(
+            "open which one? [1-{count}, a for all]: ",
+            count = urls.len()
+        )
+    );
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+
+    if answer == "a" {
+        return Ok(urls);
+    }
+
+    match answer.parse::<usize>() {
+        Ok(n) if (1..=urls.len()).contains(&n) => Ok(vec![urls[n - 1]]),
+        _ => bail!(tr!("no such url: {answer}", answer)),
+    }
+}
+
+/// Opens the url with the platform's default application
+fn open_url(url: &str) -> Result<()> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+
+    let status = cmd
+        .arg(url)
+        .status()
+        .with_context(|| tr!("unable to open {url}", url))?;
+
+    if !status.success() {
+        bail!(tr!("unable to open {url}", url));
+    }
+
+    Ok(())
+}
+
+fn yank(index: usize, markdown: bool, osc52: bool) -> Result<()> {
+    let project = tutel::load_project_rec(&std::env::current_dir()?)?;
+    let task = project.get_task(index)?;
+
+    let text = if markdown {
+        let marker = if task.is_completed() { "x" } else { " " };
+        format!("- [{marker}] {}", task.desc)
+    } else {
+        task.desc.clone()
+    };
+
+    clipboard::copy(&text, osc52)
+}
+
 /// Creates a new project
 ///
 /// If no project name is given, the name of the current directory is chosen
-fn new_project(name: Option<String>, force: bool) -> Result<()> {
+fn new_project(name: Option<String>, force: bool, format: tutel::Format) -> Result<()> {
     let path = std::env::current_dir()?;
 
     // TODO: un-hack me
@@ -148,18 +2237,32 @@ fn new_project(name: Option<String>, force: bool) -> Result<()> {
     } else if let Some(name) = path.file_name() {
         name.to_string_lossy().to_string()
     } else {
-        bail!("no project name given and cannot be inferred")
+        bail!(tr("no project name given and cannot be inferred"))
     };
 
-    let new = path.join(tutel::PROJECT_FILE_NAME);
-    if new.exists() && !force {
-        bail!(
-            "project already exists at {}. try using --force",
-            path.to_string_lossy()
-        );
+    // the other file would hide the new one, or the new one the other
+    if let Some(file) = tutel::has_project(&path).filter(|file| tutel::Format::of(file) != format) {
+        bail!(tr!(
+            "{file} is a project already, tutel convert stores it in another format",
+            file = file.display()
+        ));
+    }
+    let file = path.join(format.file_name());
+    if file.exists() && !format.is_project(&file) {
+        bail!(tr!(
+            "{file} exists already, add a line {marker} to it to make it a project",
+            file = file.display(),
+            marker = tutel::CHECKLIST_MARKER
+        ));
+    }
+    if file.exists() && !force {
+        bail!(tr!(
+            "project already exists at {path}. try using --force",
+            path = path.to_string_lossy()
+        ));
     }
 
-    tutel::new_project(name)?;
+    tutel::new_project(name, format)?;
 
     Ok(())
 }
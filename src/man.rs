@@ -0,0 +1,97 @@
+/// Renders a roff man page from the `--help` output of bpaf. `name` is the
+/// full name of the page like `tutel-done`, `summary` the line shown after
+/// it in the NAME section.
+pub fn render(name: &str, summary: &str, help: &str) -> String {
+    let mut out = format!(
+        ".TH {} 1 \"\" \"tutel v{}\"\n.SH NAME\n{} \\- {}\n",
+        name.to_uppercase(),
+        env!("CARGO_PKG_VERSION"),
+        name,
+        escape(summary),
+    );
+
+    let mut synopsis = String::new();
+    let mut description = String::new();
+    let mut sections = String::new();
+
+    for block in help.trim().split("\n\n") {
+        let mut lines = block.lines();
+        let first = lines.next().unwrap_or_default();
+
+        if let Some(usage) = first.strip_prefix("Usage: ") {
+            let command = name.replace('-', " ");
+            synopsis = format!(".SH SYNOPSIS\n\\fB{command}\\fR {}\n", escape(usage));
+        } else if let Some(title) = first
+            .strip_prefix("Available ")
+            .and_then(|t| t.strip_suffix(':'))
+        {
+            sections.push_str(&format!(".SH {}\n", title.to_uppercase()));
+            for line in lines {
+                let line = line.trim();
+                let (term, help) = line.split_once("  ").unwrap_or((line, ""));
+                sections.push_str(&format!(
+                    ".TP\n\\fB{}\\fR\n{}\n",
+                    escape(term),
+                    escape(help.trim())
+                ));
+            }
+        } else {
+            // the description of tutel itself starts with its name
+            let block = block.strip_prefix(&format!("{name}\n")).unwrap_or(block);
+            description.push_str(&format!(".PP\n{}\n", escape(block)));
+        }
+    }
+
+    out.push_str(&synopsis);
+    if !description.is_empty() {
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&description);
+    }
+    out.push_str(&sections);
+
+    out
+}
+
+/// Returns the name and help of the subcommands listed in the help text
+pub fn commands(help: &str) -> Vec<(String, String)> {
+    help.split("\n\n")
+        .filter_map(|block| block.strip_prefix("Available commands:\n"))
+        .flat_map(str::lines)
+        .filter_map(|line| {
+            let (term, help) = line.trim().split_once("  ")?;
+            let name = term.split(',').next()?;
+            Some((name.to_string(), help.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Escapes text so roff doesn't interpret it
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            // lines starting with these are requests
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn render_sections() {
+    let help = "descr\n\nUsage: [-a] <x>\n\nAvailable options:\n    -a, --all   all of them\n";
+    assert_eq!(
+        render("tutel-rm", "remove a task", help),
+        format!(
+            ".TH TUTEL-RM 1 \"\" \"tutel v{}\"\n.SH NAME\ntutel-rm \\- remove a task\n\
+             .SH SYNOPSIS\n\\fBtutel rm\\fR [\\-a] <x>\n\
+             .SH DESCRIPTION\n.PP\ndescr\n\
+             .SH OPTIONS\n.TP\n\\fB\\-a, \\-\\-all\\fR\nall of them\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+}
@@ -0,0 +1,110 @@
+/// A piece of a description with light markdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Span<'a> {
+    Text(&'a str),
+    /// `**bold**` or `__bold__`
+    Bold(&'a str),
+    /// `*italic*` or `_italic_`
+    Italic(&'a str),
+    /// `` `code` ``
+    Code(&'a str),
+    /// `[text](url)`
+    Link {
+        text: &'a str,
+        url: &'a str,
+    },
+}
+
+/// Splits text into spans of markdown. Only what fits on a single line is
+/// recognized, markers without a counterpart are kept as text.
+pub fn parse(text: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut plain = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        let found = if rest.starts_with('`') {
+            delimited(rest, "`").map(|(inner, len)| (Span::Code(inner), len))
+        } else if rest.starts_with("**") || rest.starts_with("__") {
+            delimited(rest, &rest[..2]).map(|(inner, len)| (Span::Bold(inner), len))
+        } else if rest.starts_with('*') || rest.starts_with('_') {
+            // snake_case and 2*3*4 are not emphasis
+            let word_before = text[..i].chars().last().is_some_and(char::is_alphanumeric);
+            delimited(rest, &rest[..1])
+                .filter(|_| !word_before)
+                .map(|(inner, len)| (Span::Italic(inner), len))
+        } else if rest.starts_with('[') {
+            link(rest)
+        } else {
+            None
+        };
+
+        match found {
+            Some((span, len)) => {
+                if plain < i {
+                    spans.push(Span::Text(&text[plain..i]));
+                }
+                spans.push(span);
+                i += len;
+                plain = i;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    if plain < text.len() {
+        spans.push(Span::Text(&text[plain..]));
+    }
+    spans
+}
+
+/// The text between a marker at the start of `text` and the next one, along
+/// with the length of the whole thing
+fn delimited<'a>(text: &'a str, marker: &str) -> Option<(&'a str, usize)> {
+    let inner = &text[marker.len()..];
+    let end = inner.find(marker)?;
+    let content = &inner[..end];
+    if content.is_empty() || content.starts_with(' ') || content.ends_with(' ') {
+        return None;
+    }
+    Some((content, marker.len() * 2 + end))
+}
+
+fn link(text: &str) -> Option<(Span<'_>, usize)> {
+    let close = text.find("](")?;
+    let end = text[close..].find(')')? + close;
+    let label = &text[1..close];
+    let url = &text[close + 2..end];
+    if label.is_empty() || url.is_empty() || label.contains('[') || url.contains(' ') {
+        return None;
+    }
+    Some((Span::Link { text: label, url }, end + 1))
+}
+
+#[test]
+fn parse_markdown() {
+    use Span::*;
+
+    assert_eq!(
+        parse("fix **the** `parser`, see [docs](https://x.org) *now*"),
+        [
+            Text("fix "),
+            Bold("the"),
+            Text(" "),
+            Code("parser"),
+            Text(", see "),
+            Link {
+                text: "docs",
+                url: "https://x.org"
+            },
+            Text(" "),
+            Italic("now"),
+        ]
+    );
+    assert_eq!(
+        parse("rename snake_case_name"),
+        [Text("rename snake_case_name")]
+    );
+    assert_eq!(parse("2 * 3 and **open"), [Text("2 * 3 and **open")]);
+}
@@ -0,0 +1,399 @@
+use anyhow::{Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+use toml::{value::Table, Value};
+
+use crate::{
+    data::{ProjectData, Task},
+    tr, Format,
+};
+
+/// Merges the changes made to a project file in `theirs` into `ours`, like a
+/// git merge driver does.
+///
+/// `base` is the version both started from. The result is written to `ours`,
+/// in the format `name` has, and the conflicts that could not be merged are
+/// returned. With `auto` there are none: see [`Merger`].
+///
+/// Without a base every task that is only in one version is kept, as there
+/// is no telling whether it was added or removed.
+///
+/// # Errors
+/// This function will return an error if a file can't be read, isn't a
+/// project or the result can't be written.
+pub fn merge(
+    base: Option<&Path>,
+    ours: &Path,
+    theirs: &Path,
+    name: &Path,
+    auto: bool,
+) -> Result<Vec<String>> {
+    let format = Format::of(name);
+    let load = |path: &Path| -> Result<ProjectData> {
+        let content =
+            fs::read(path).with_context(|| tr!("unable to read {file}", file = path.display()))?;
+        let mut data = format
+            .parse(&content, name)
+            .with_context(|| tr!("{file} is not a project", file = path.display()))?;
+        data.assign_ids();
+        Ok(data)
+    };
+
+    let base = match base {
+        Some(base) => load(base)?,
+        None => ProjectData {
+            name: String::new(),
+            tasks: Vec::new(),
+            lists: BTreeMap::new(),
+        },
+    };
+    let mut merger = Merger {
+        auto,
+        conflicts: Vec::new(),
+    };
+    let merged = merger.data(&base, &load(ours)?, &load(theirs)?)?;
+    fs::write(ours, format.render(&merged, ours)?)
+        .context(tr("unable to write the merge result"))?;
+    Ok(merger.conflicts)
+}
+
+/// Where both sides changed the same thing, ours is kept and a conflict is
+/// noted.
+///
+/// In `auto` mode the sides are treated alike instead, so merging ours into
+/// theirs gives the same project as merging theirs into ours, and every
+/// device syncing the file ends up with the same tasks on the same indices:
+/// - a task changed on one side and removed on the other is kept
+/// - a completed status wins over an open one, otherwise the greater value
+///   wins
+/// - tasks added on both sides keep their indices unless they collide, then
+///   the one with the lower id keeps it and the other moves to the lowest
+///   free index
+/// - tasks are sorted by index
+struct Merger {
+    auto: bool,
+    conflicts: Vec<String>,
+}
+
+/// Statuses by how far along a task is, the later one wins in `auto` mode
+const PROGRESS: [&str; 5] = ["pending", "blocked", "in-progress", "cancelled", "done"];
+
+impl Merger {
+    fn conflict(&mut self, conflict: String) {
+        if !self.auto {
+            self.conflicts.push(conflict);
+        }
+    }
+
+    fn data(
+        &mut self,
+        base: &ProjectData,
+        ours: &ProjectData,
+        theirs: &ProjectData,
+    ) -> Result<ProjectData> {
+        let name = self
+            .value(
+                "name",
+                Some(&base.name),
+                Some(&ours.name),
+                Some(&theirs.name),
+            )
+            .unwrap_or_else(|name| {
+                self.conflict("both renamed the project, kept ours".to_string());
+                name
+            });
+
+        let tasks = self.tasks(&base.tasks, &ours.tasks, &theirs.tasks)?;
+        let mut lists = BTreeMap::new();
+        let names: BTreeSet<&String> = ours.lists.keys().chain(theirs.lists.keys()).collect();
+        for list in names {
+            let tasks_of = |data: &ProjectData| data.lists.get(list).cloned().unwrap_or_default();
+            let tasks = self.tasks(&tasks_of(base), &tasks_of(ours), &tasks_of(theirs))?;
+            if !tasks.is_empty() {
+                lists.insert(list.clone(), tasks);
+            }
+        }
+
+        Ok(ProjectData {
+            name: name.cloned().unwrap_or_default(),
+            tasks,
+            lists,
+        })
+    }
+
+    /// Merges the tasks matched by id. Our order comes first, followed by the
+    /// tasks they added.
+    fn tasks(&mut self, base: &[Task], ours: &[Task], theirs: &[Task]) -> Result<Vec<Task>> {
+        let find = |tasks: &[Task], id: &str| tasks.iter().find(|t| t.id == id).map(fields);
+        let (ours, theirs) = self.renumber(base, ours, theirs);
+
+        let mut merged = Vec::new();
+        for task in &ours {
+            let base = find(base, &task.id).transpose()?;
+            match (base, find(&theirs, &task.id).transpose()?) {
+                (base, Some(their_task)) => {
+                    merged.push(self.task(base.as_ref(), &fields(task)?, &their_task)?);
+                }
+                (Some(base), None) if base != fields(task)? => {
+                    self.conflict(format!(
+                        "{:03} {}: changed here but removed in theirs, kept it",
+                        task.index, task.desc
+                    ));
+                    merged.push(task.clone());
+                }
+                // removed by them
+                (Some(_), None) => {}
+                // added by us
+                (None, None) => merged.push(task.clone()),
+            }
+        }
+
+        for task in theirs.iter().filter(|t| ours.iter().all(|o| o.id != t.id)) {
+            match find(base, &task.id).transpose()? {
+                Some(base) if base != fields(task)? => {
+                    self.conflict(format!(
+                        "{:03} {}: removed here but changed in theirs, kept it",
+                        task.index, task.desc
+                    ));
+                    merged.push(task.clone());
+                }
+                // removed by us
+                Some(_) => {}
+                // added by them
+                None => merged.push(task.clone()),
+            }
+        }
+
+        if self.auto {
+            merged.sort_by_key(|task| task.index);
+        }
+        Ok(merged)
+    }
+
+    /// Moves added tasks off indices that are taken. Usually only theirs move,
+    /// in `auto` mode the lower id keeps the index. Dependencies on the moved
+    /// tasks follow them.
+    fn renumber(&self, base: &[Task], ours: &[Task], theirs: &[Task]) -> (Vec<Task>, Vec<Task>) {
+        let in_base = |task: &&Task| base.iter().any(|b| b.id == task.id);
+        let in_ours = |task: &&Task| ours.iter().any(|o| o.id == task.id);
+
+        // the tasks that may have to move, and whether they are ours
+        let (added, mut used) = if self.auto {
+            let mut added: Vec<(&Task, bool)> = ours
+                .iter()
+                .filter(|t| !in_base(t))
+                .map(|t| (t, true))
+                .collect();
+            added.extend(
+                theirs
+                    .iter()
+                    .filter(|t| !in_base(t) && !in_ours(t))
+                    .map(|t| (t, false)),
+            );
+            added.sort_by(|(a, _), (b, _)| (a.index, &a.id).cmp(&(b.index, &b.id)));
+            let used: BTreeSet<usize> = ours
+                .iter()
+                .chain(theirs)
+                .filter(in_base)
+                .map(|t| t.index)
+                .collect();
+            (added, used)
+        } else {
+            let added = theirs
+                .iter()
+                .filter(|t| !in_ours(t))
+                .map(|t| (t, false))
+                .collect();
+            (added, ours.iter().map(|t| t.index).collect())
+        };
+
+        let mut moved_ours = HashMap::new();
+        let mut moved_theirs = HashMap::new();
+        for (task, is_ours) in added {
+            if !used.insert(task.index) {
+                let index = (0..).find(|i| !used.contains(i)).unwrap_or_default();
+                used.insert(index);
+                let moved = if is_ours {
+                    &mut moved_ours
+                } else {
+                    &mut moved_theirs
+                };
+                moved.insert(task.index, index);
+            }
+        }
+
+        // indices are unique on each side, so only the added task has it
+        let apply = |tasks: &[Task], moved: &HashMap<usize, usize>| {
+            let mut tasks = tasks.to_vec();
+            for task in &mut tasks {
+                task.index = moved.get(&task.index).copied().unwrap_or(task.index);
+                for on in &mut task.depends {
+                    *on = moved.get(on).copied().unwrap_or(*on);
+                }
+            }
+            tasks
+        };
+        (apply(ours, &moved_ours), apply(theirs, &moved_theirs))
+    }
+
+    /// Merges the task field by field
+    fn task(&mut self, base: Option<&Table>, ours: &Table, theirs: &Table) -> Result<Task> {
+        let keys: BTreeSet<&String> = ours.keys().chain(theirs.keys()).collect();
+        let mut merged = Table::new();
+        for key in keys {
+            let value = self
+                .value(
+                    key,
+                    base.and_then(|base| base.get(key)),
+                    ours.get(key),
+                    theirs.get(key),
+                )
+                .unwrap_or_else(|value| {
+                    self.conflict(tr!(
+                        "{task}: both changed {key}, kept ours",
+                        task = describe(ours),
+                        key
+                    ));
+                    value
+                });
+            if let Some(value) = value {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(Value::Table(merged).try_into()?)
+    }
+
+    /// The value after both changes, or ours as an error if they both changed
+    /// it differently
+    fn value<'a, T: PartialEq + ToString>(
+        &self,
+        key: &str,
+        base: Option<&'a T>,
+        ours: Option<&'a T>,
+        theirs: Option<&'a T>,
+    ) -> Result<Option<&'a T>, Option<&'a T>> {
+        if ours == theirs || theirs == base {
+            Ok(ours)
+        } else if ours == base {
+            Ok(theirs)
+        } else if self.auto {
+            let rank = |value: &Option<&T>| {
+                let value = value.map(ToString::to_string);
+                let progress = value
+                    .as_deref()
+                    .and_then(|v| PROGRESS.iter().position(|p| v.trim_matches('"') == *p))
+                    .filter(|_| key == "status");
+                (progress, value)
+            };
+            Ok(if rank(&theirs) > rank(&ours) {
+                theirs
+            } else {
+                ours
+            })
+        } else {
+            Err(ours)
+        }
+    }
+}
+
+fn fields(task: &Task) -> Result<Table> {
+    match Value::try_from(task)? {
+        Value::Table(table) => Ok(table),
+        _ => unreachable!("a task is serialized as a table"),
+    }
+}
+
+/// The index and description of the task, for conflict messages
+fn describe(task: &Table) -> String {
+    let index = task.get("index").and_then(Value::as_integer).unwrap_or(0);
+    let desc = task.get("desc").and_then(Value::as_str).unwrap_or("");
+    format!("{index:03} {desc}")
+}
+
+#[test]
+fn merges_tasks_by_id() {
+    let project = |tasks: &[(&str, usize, &str)]| ProjectData {
+        name: "p".to_string(),
+        tasks: tasks
+            .iter()
+            .map(|(id, index, desc)| {
+                let mut task = Task::new(*desc, false, *index);
+                task.id = (*id).to_string();
+                task.created = None;
+                task
+            })
+            .collect(),
+        lists: BTreeMap::new(),
+    };
+    let merge = |base: &ProjectData, ours: &ProjectData, theirs: &ProjectData, auto| {
+        let mut merger = Merger {
+            auto,
+            conflicts: Vec::new(),
+        };
+        let merged = merger.data(base, ours, theirs).unwrap();
+        (merged, merger.conflicts)
+    };
+
+    let base = project(&[("a", 0, "keep"), ("b", 1, "edit"), ("c", 2, "drop")]);
+    let ours = project(&[
+        ("a", 0, "keep"),
+        ("b", 1, "edited"),
+        ("c", 2, "drop"),
+        ("d", 3, "ours"),
+    ]);
+    let mut theirs = project(&[("a", 0, "keep"), ("b", 1, "edit"), ("e", 3, "theirs")]);
+    theirs.tasks[0].pinned = true;
+
+    let (merged, conflicts) = merge(&base, &ours, &theirs, false);
+    assert!(conflicts.is_empty());
+    let tasks: Vec<_> = merged
+        .tasks
+        .iter()
+        .map(|t| (t.id.as_str(), t.index, t.desc.as_str(), t.pinned))
+        .collect();
+    assert_eq!(
+        tasks,
+        vec![
+            ("a", 0, "keep", true),
+            ("b", 1, "edited", false),
+            ("d", 3, "ours", false),
+            ("e", 4, "theirs", false),
+        ]
+    );
+
+    let mut theirs = project(&[("a", 0, "keep"), ("b", 1, "other"), ("c", 2, "drop")]);
+    theirs.tasks[2].pinned = true;
+    let (merged, conflicts) = merge(&base, &ours, &theirs, false);
+    assert_eq!(merged.tasks[1].desc, "edited");
+    assert_eq!(conflicts, vec!["001 edited: both changed desc, kept ours"]);
+    // the same result whichever side merges, without conflicts
+    theirs
+        .tasks
+        .push(project(&[("e", 3, "theirs")]).tasks.remove(0));
+    theirs.tasks[1].set_status(crate::Status::Done);
+    let (merged, conflicts) = merge(&base, &ours, &theirs, true);
+    let (reverse, _) = merge(&base, &theirs, &ours, true);
+    assert!(conflicts.is_empty());
+    assert_eq!(
+        toml::to_string(&merged).unwrap(),
+        toml::to_string(&reverse).unwrap()
+    );
+    let tasks: Vec<_> = merged
+        .tasks
+        .iter()
+        .map(|t| (t.id.as_str(), t.index, t.status))
+        .collect();
+    assert_eq!(
+        tasks,
+        vec![
+            ("a", 0, crate::Status::Pending),
+            ("b", 1, crate::Status::Done),
+            ("c", 2, crate::Status::Pending),
+            ("d", 3, crate::Status::Pending),
+            ("e", 4, crate::Status::Pending),
+        ]
+    );
+}
@@ -0,0 +1,37 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Shows a desktop notification. Uses `notify-send` or `osascript` on macOS,
+/// falling back to ringing the terminal bell if neither is available.
+pub fn send(title: &str, body: &str) {
+    let sent = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        );
+        run(Command::new("osascript").args(["-e", &script]))
+    } else {
+        run(Command::new("notify-send").args(["--app-name", "tutel", title, body]))
+    };
+
+    if !sent {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07");
+        let _ = stdout.flush();
+    }
+}
+
+fn run(cmd: &mut Command) -> bool {
+    cmd.stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Escapes text for an AppleScript string literal
+fn quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use std::{
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+};
+use tutel::tr;
+
+/// Prints the output, piping it through `$PAGER` (or `less`) when it does not
+/// fit on the screen. Output that doesn't go to a terminal is never paged.
+pub fn print_paged(output: &str) -> Result<()> {
+    let height = match terminal_size::terminal_size() {
+        Some((_, height)) if std::io::stdout().is_terminal() => height.0 as usize,
+        _ => 0,
+    };
+
+    // keep one line free for the prompt
+    if height == 0 || output.lines().count() < height {
+        println!("{output}");
+        return Ok(());
+    }
+
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+
+    let mut words = pager.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap_or("less"));
+    cmd.args(words).stdin(Stdio::piped());
+
+    // same defaults as git: keep colors and don't clear the screen
+    if std::env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        cmd.env("LV", "-c");
+    }
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        // no usable pager, just print everything
+        Err(_) => {
+            println!("{output}");
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager closing its input early(e.g. quitting less) is not an error
+        let _ = writeln!(stdin, "{output}");
+    }
+
+    child.wait().context(tr("failed to wait for the pager"))?;
+
+    Ok(())
+}
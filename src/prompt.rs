@@ -0,0 +1,202 @@
+use std::path::Path;
+use tutel::{Config, Project, Status, Symbols};
+
+use crate::cache;
+
+/// The shell a prompt segment is rendered for. Escape sequences need to be
+/// marked as zero width, otherwise the shell miscalculates the prompt length.
+#[derive(Debug, Clone, Copy)]
+pub enum PromptShell {
+    Bash,
+    Zsh,
+}
+
+/// The output format of the status command
+#[derive(Debug, Clone, Copy)]
+pub enum StatusFormat {
+    /// Same as the prompt command
+    Plain,
+    Starship,
+    /// Colored using tmux's format markup, for `status-right`
+    Tmux,
+}
+
+/// Counts the completed and pending tasks of a project
+pub fn counts(project: &Project) -> (usize, usize) {
+    let done = project
+        .data
+        .tasks
+        .iter()
+        .filter(|t| t.is_completed())
+        .count();
+    (done, project.data.tasks.len() - done)
+}
+
+/// Counts the completed and pending tasks of the project in effect for the
+/// directory. Prompts run after every command, so the counts are cached
+/// until the project file changes instead of parsing it every time.
+pub fn project_counts(dir: &Path) -> Option<(usize, usize)> {
+    let (file, _) = tutel::find_project(dir)?;
+    let counts = cache::cached_while_unchanged("counts", &file, || {
+        let (done, pending) = counts(&tutel::load_project_rec(dir).ok()?);
+        Some(format!("{done} {pending}"))
+    })?;
+
+    let (done, pending) = counts.split_once(' ')?;
+    Some((done.parse().ok()?, pending.parse().ok()?))
+}
+
+/// The symbols of the summaries, ascii ones if the config asks for them
+fn symbols(config: &Config) -> Symbols {
+    if config.ascii {
+        config.symbols.clone().into_ascii()
+    } else {
+        config.symbols.clone()
+    }
+}
+
+/// Renders a compact summary like `✓3 ✗2`, leaving out counts that are zero.
+/// It is only colored if a shell is given and colors are turned on.
+pub fn segment(
+    (done, pending): (usize, usize),
+    shell: Option<PromptShell>,
+    config: &Config,
+) -> String {
+    let symbols = symbols(config);
+    let (done_symbol, pending_symbol) = (&symbols.prompt_done, &symbols.prompt_pending);
+
+    let mut parts = Vec::new();
+    if done > 0 {
+        parts.push((format!("{done_symbol}{done}"), &config.theme.completed));
+    }
+    if pending > 0 {
+        parts.push((format!("{pending_symbol}{pending}"), &config.theme.pending));
+    }
+
+    let parts: Vec<String> = match shell {
+        None => parts.into_iter().map(|(text, _)| text).collect(),
+        Some(shell) => parts
+            .into_iter()
+            .map(|(text, style)| zero_width(&style.paint(&text).to_string(), shell))
+            .collect(),
+    };
+
+    parts.join(" ")
+}
+
+/// Output for a starship custom module. This format is documented and must
+/// not change: `✗N` with N pending tasks, `✓` if there are none, with the
+/// prompt symbols of the config. Styling is left to starship.
+pub fn starship(counts: (usize, usize), config: &Config) -> String {
+    let symbols = symbols(config);
+    match counts {
+        (_, 0) => symbols.prompt_done,
+        (_, pending) => format!("{}{pending}", symbols.prompt_pending),
+    }
+}
+
+/// Renders the same summary as [`segment`], colored with tmux markup
+pub fn tmux((done, pending): (usize, usize), config: &Config) -> String {
+    let symbols = symbols(config);
+    let (done_symbol, pending_symbol) = (&symbols.prompt_done, &symbols.prompt_pending);
+
+    let mut parts = Vec::new();
+    if done > 0 {
+        parts.push(
+            config
+                .theme
+                .completed
+                .paint_tmux(&format!("{done_symbol}{done}")),
+        );
+    }
+    if pending > 0 {
+        parts.push(
+            config
+                .theme
+                .pending
+                .paint_tmux(&format!("{pending_symbol}{pending}")),
+        );
+    }
+
+    parts.join(" ")
+}
+
+/// The state of the project as environment variables, for `.envrc` files and
+/// prompt frameworks. Names are only ever added, never changed.
+pub fn variables(project: &Project) -> Vec<(&'static str, String)> {
+    let (done, pending) = counts(project);
+    let count = |status: Status| {
+        project
+            .data
+            .tasks
+            .iter()
+            .filter(|t| t.status == status)
+            .count()
+    };
+    let active = project
+        .data
+        .tasks
+        .iter()
+        .find(|t| t.is_active())
+        .map_or_else(String::new, |t| t.index.to_string());
+
+    vec![
+        ("TUTEL_PROJECT", project.path.to_string_lossy().to_string()),
+        ("TUTEL_NAME", project.data.name.clone()),
+        ("TUTEL_PENDING", pending.to_string()),
+        ("TUTEL_DONE", done.to_string()),
+        ("TUTEL_IN_PROGRESS", count(Status::InProgress).to_string()),
+        ("TUTEL_BLOCKED", count(Status::Blocked).to_string()),
+        ("TUTEL_ACTIVE", active),
+    ]
+}
+
+/// Quotes the value for POSIX shells
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[test]
+fn starship_follows_ascii() {
+    let mut config = Config::default();
+    assert_eq!(starship((1, 2), &config), "✗2");
+    assert_eq!(starship((3, 0), &config), "✓");
+    config.ascii = true;
+    assert_eq!(starship((1, 2), &config), "-2");
+    assert_eq!(starship((3, 0), &config), "+");
+}
+
+#[test]
+fn quote_for_the_shell() {
+    assert_eq!(shell_quote("it's 3"), r"'it'\''s 3'");
+    assert_eq!(shell_quote(""), "''");
+}
+
+/// Wraps every escape sequence in the markers the shell uses for zero width text
+fn zero_width(text: &str, shell: PromptShell) -> String {
+    let (open, close) = match shell {
+        PromptShell::Bash => ("\x01", "\x02"),
+        PromptShell::Zsh => ("%{", "%}"),
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+
+        out.push_str(open);
+        out.push(c);
+        for c in chars.by_ref() {
+            out.push(c);
+            if c.is_ascii_alphabetic() {
+                break;
+            }
+        }
+        out.push_str(close);
+    }
+
+    out
+}
@@ -0,0 +1,348 @@
+use anyhow::{bail, Context, Result};
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::{date, tr, Status, Task};
+
+/// A filter over tasks like `tag:home and (age>1w or status:blocked)`.
+///
+/// Terms next to each other have to match all, `or` is weaker than `and` and
+/// parentheses group. The terms are:
+/// - `tag:NAME` or `#NAME`, `assignee:NAME`, `mentions:NAME` or `@NAME`
+/// - `status:STATUS` and `is:open|closed|waiting|active|pinned|snoozed|hidden`
+/// - `age`, `estimate` and `tracked` compared with a duration, like `age>2w`
+/// - anything else, or a "quoted phrase", is looked for in the description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query(Expr);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Term(Term),
+    Not(Box<Self>),
+    And(Vec<Self>),
+    Or(Vec<Self>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    /// Part of the description, in lowercase
+    Text(String),
+    Tag(String),
+    Assignee(String),
+    Mentions(String),
+    Status(Status),
+    Is(State),
+    Age(Ordering, u64),
+    Estimate(Ordering, u64),
+    Tracked(Ordering, u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Open,
+    Closed,
+    Waiting,
+    Active,
+    Pinned,
+    Snoozed,
+    Hidden,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Open,
+    Close,
+    Compare(Ordering),
+}
+
+impl Query {
+    /// Whether the task matches the query at the given time
+    pub fn matches(&self, task: &Task, now: u64) -> bool {
+        self.0.matches(task, now)
+    }
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            pos: 0,
+        };
+        if parser.tokens.is_empty() {
+            bail!(tr("the query is empty"));
+        }
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Self(expr)),
+            Some(Token::Close) => bail!(tr("unmatched ) in the query")),
+            Some(token) => bail!(tr!(
+                "unexpected {token} in the query",
+                token = format!("{token:?}")
+            )),
+        }
+    }
+}
+
+impl Expr {
+    fn matches(&self, task: &Task, now: u64) -> bool {
+        match self {
+            Self::Term(term) => term.matches(task, now),
+            Self::Not(expr) => !expr.matches(task, now),
+            Self::And(exprs) => exprs.iter().all(|e| e.matches(task, now)),
+            Self::Or(exprs) => exprs.iter().any(|e| e.matches(task, now)),
+        }
+    }
+}
+
+impl Term {
+    fn parse(word: &str) -> Result<Self> {
+        if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            return Ok(Self::Tag(tag.to_string()));
+        }
+        if let Some(name) = word.strip_prefix('@').filter(|name| !name.is_empty()) {
+            return Ok(Self::Mentions(name.to_string()));
+        }
+        let Some((field, value)) = word.split_once(':') else {
+            return Ok(Self::Text(word.to_lowercase()));
+        };
+        if value.is_empty() {
+            bail!(tr!("no value given for {field}: in the query", field));
+        }
+        Ok(match field {
+            "tag" => Self::Tag(value.trim_start_matches('#').to_string()),
+            "assignee" => Self::Assignee(value.to_string()),
+            "mentions" => Self::Mentions(value.trim_start_matches('@').to_string()),
+            "status" => Self::Status(value.parse()?),
+            "is" => Self::Is(match value {
+                "open" => State::Open,
+                "closed" => State::Closed,
+                "waiting" => State::Waiting,
+                "active" => State::Active,
+                "pinned" => State::Pinned,
+                "snoozed" => State::Snoozed,
+                "hidden" => State::Hidden,
+                _ => bail!(
+                    tr!("unknown state: {value}. available are open, closed, waiting, active, pinned, snoozed and hidden", value)
+                ),
+            }),
+            _ => bail!(
+                tr!("unknown field in the query: {field}. available are tag, assignee, mentions, status and is", field)
+            ),
+        })
+    }
+
+    fn compare(field: &str, ordering: Ordering, duration: &str) -> Result<Self> {
+        let secs = date::parse_duration(duration)
+            .with_context(|| tr!("invalid duration: {duration}. use e.g. 3d or 2w", duration))?;
+        Ok(match field {
+            "age" => Self::Age(ordering, secs),
+            "estimate" => Self::Estimate(ordering, secs),
+            "tracked" => Self::Tracked(ordering, secs),
+            _ => bail!(tr!(
+                "can't compare {field}. available are age, estimate and tracked",
+                field
+            )),
+        })
+    }
+
+    fn matches(&self, task: &Task, now: u64) -> bool {
+        let compare = |value: Option<u64>, ordering, limit: &u64| {
+            value.is_some_and(|v| v.cmp(limit) == ordering)
+        };
+        match self {
+            Self::Text(text) => task.desc.to_lowercase().contains(text),
+            Self::Tag(tag) => task.has_tag(tag),
+            Self::Assignee(name) => task
+                .assignee
+                .as_ref()
+                .is_some_and(|a| a.eq_ignore_ascii_case(name)),
+            Self::Mentions(name) => task.mentions_person(name),
+            Self::Status(status) => task.status == *status,
+            Self::Is(state) => match state {
+                State::Open => !task.is_completed(),
+                State::Closed => task.is_completed(),
+                State::Waiting => task.is_waiting(),
+                State::Active => task.is_active(),
+                State::Pinned => task.pinned,
+                State::Snoozed => task.is_snoozed(now),
+                State::Hidden => task.hidden,
+            },
+            Self::Age(ordering, secs) => compare(
+                task.created.map(|created| now.saturating_sub(created)),
+                *ordering,
+                secs,
+            ),
+            Self::Estimate(ordering, secs) => compare(task.estimate, *ordering, secs),
+            Self::Tracked(ordering, secs) => compare(Some(task.tracked(now)), *ordering, secs),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '<' => tokens.push(Token::Compare(Ordering::Less)),
+            '>' => tokens.push(Token::Compare(Ordering::Greater)),
+            '"' => {
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Quoted(phrase.to_lowercase()));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()<>\"".contains(*c))
+                {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Skips the keyword if it comes next
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.keyword("or") {
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.not()?];
+        loop {
+            if self.keyword("and") {
+                exprs.push(self.not()?);
+                continue;
+            }
+            // terms next to each other have to match all as well
+            match self.peek() {
+                Some(Token::Word(word)) if !word.eq_ignore_ascii_case("or") => {}
+                Some(Token::Quoted(_) | Token::Open) => {}
+                _ => break,
+            }
+            exprs.push(self.not()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            Ok(Expr::Not(Box::new(self.not()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    bail!(tr("missing ) in the query"));
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Quoted(phrase)) => Ok(Expr::Term(Term::Text(phrase))),
+            Some(Token::Word(word)) => {
+                if let Some(Token::Compare(ordering)) = self.peek().cloned() {
+                    self.pos += 1;
+                    let Some(Token::Word(duration)) = self.peek().cloned() else {
+                        bail!(tr!("no duration given to compare {word} with", word));
+                    };
+                    self.pos += 1;
+                    return Ok(Expr::Term(Term::compare(&word, ordering, &duration)?));
+                }
+                Ok(Expr::Term(Term::parse(&word)?))
+            }
+            Some(Token::Close) => bail!(tr("unmatched ) in the query")),
+            Some(Token::Compare(_)) => bail!(tr("nothing to compare in the query")),
+            None => bail!(tr("the query ends too early")),
+        }
+    }
+}
+
+#[test]
+fn parse_queries() {
+    use Ordering::*;
+
+    let query: Query = "tag:home and (age>1w OR status:blocked) not milk"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        query.0,
+        Expr::And(vec![
+            Expr::Term(Term::Tag("home".to_string())),
+            Expr::Or(vec![
+                Expr::Term(Term::Age(Greater, 7 * 24 * 60 * 60)),
+                Expr::Term(Term::Status(Status::Blocked)),
+            ]),
+            Expr::Not(Box::new(Expr::Term(Term::Text("milk".to_string())))),
+        ])
+    );
+    assert_eq!(
+        "estimate < 2h".parse::<Query>().unwrap().0,
+        Expr::Term(Term::Estimate(Less, 2 * 60 * 60))
+    );
+
+    for invalid in [
+        "",
+        "(tag:home",
+        "tag:home)",
+        "due<1w",
+        "priority:high",
+        "age>soon",
+        "not",
+    ] {
+        assert!(invalid.parse::<Query>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn match_tasks() {
+    let task = |desc: &str| Task::new(desc, false, 0);
+    let query: Query = "#home \"buy milk\" or @bob".parse().unwrap();
+    assert!(query.matches(&task("Buy milk #home"), 0));
+    assert!(!query.matches(&task("buy milk"), 0));
+    assert!(query.matches(&task("ask @Bob"), 0));
+    assert!("is:open".parse::<Query>().unwrap().matches(&task("x"), 0));
+}
@@ -0,0 +1,95 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{tr, Project};
+
+pub const REGISTRY_FILE_NAME: &str = "projects";
+
+/// The registry lists the project files tutel has seen, one path per line,
+/// so tasks of other projects can be found by the name of their project
+fn registry_path() -> Option<PathBuf> {
+    Some(crate::data_dir()?.join(REGISTRY_FILE_NAME))
+}
+
+/// The project files in the registry that still exist
+pub fn registered() -> Vec<PathBuf> {
+    let Some(content) = registry_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Adds the project file to the registry, unless it is listed already.
+///
+/// # Errors
+/// This function will return an error if the registry can't be written.
+pub fn register(project_file: &Path) -> Result<()> {
+    let path = registry_path().context(tr("no place for the project registry"))?;
+    let project_file = crate::original_path(project_file)
+        .canonicalize()
+        .context(tr("unable to resolve the project path"))?;
+
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    if content.lines().any(|line| Path::new(line) == project_file) {
+        return Ok(());
+    }
+
+    content.push_str(&project_file.to_string_lossy());
+    content.push('\n');
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, content).context(tr("unable to write the project registry"))
+}
+
+/// Whether the text refers to a task of another project, like `name:id`
+pub fn is_reference(text: &str) -> bool {
+    text.split_once(':')
+        .is_some_and(|(project, id)| !project.is_empty() && !id.is_empty())
+}
+
+/// Loads the registered project with the name in the reference `name:id`,
+/// returning it together with the index of the task.
+///
+/// # Errors
+/// This function will return an error if the reference is malformed, or no
+/// registered project has this name or a task with this id.
+pub fn resolve(reference: &str) -> Result<(Project, usize)> {
+    let Some((name, id)) = reference.split_once(':') else {
+        bail!(tr!(
+            "not a task reference: {reference}. use project:id",
+            reference
+        ));
+    };
+
+    for path in registered() {
+        let Ok(project) = Project::load(path, 0) else {
+            continue;
+        };
+        if project.data.name == name {
+            let index = project.find_id(id)?.index;
+            return Ok((project, index));
+        }
+    }
+
+    bail!(tr!(
+        "no project named {name}, it is known once a command changed it",
+        name
+    ))
+}
+
+#[test]
+fn task_references() {
+    assert!(is_reference("website:k3x9qa"));
+    assert!(!is_reference("k3x9qa"));
+    assert!(!is_reference(":k3x9qa"));
+    assert!(!is_reference("website:"));
+}
@@ -0,0 +1,243 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    time::Duration,
+};
+use tempfile::TempDir;
+
+use crate::cache;
+use tutel::tr;
+
+/// How long a project fetched over http is shown without asking the server
+/// again
+const HTTP_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// A project on another machine, reached with the `ssh` command or published
+/// over http. Its files are copied into a temporary directory for the
+/// command to work on, and the ones that changed are copied back afterwards.
+pub struct Remote {
+    location: String,
+    /// What ssh connects to, `ssh://[user@]host[:port]`. `None` over http,
+    /// which is read-only.
+    destination: Option<String>,
+    /// The directory of the project on the remote machine
+    dir: String,
+    /// The project file and its archive with their content before the
+    /// command ran, `None` if missing
+    files: Vec<(String, Option<Vec<u8>>)>,
+    local: TempDir,
+}
+
+impl Remote {
+    /// Fetches the project at `ssh://[user@]host[:port]/path`, where the
+    /// path is the project directory or file, starting with `~/` it is
+    /// relative to the home directory. Or the project file published at an
+    /// `http(s)://` url. `None` if the location is neither.
+    pub fn fetch(location: &str) -> Option<Result<Self>> {
+        let remote = if let Some(rest) = location.strip_prefix("ssh://") {
+            fetch_ssh(rest)
+        } else if location.starts_with("http://") || location.starts_with("https://") {
+            fetch_http(location)
+        } else {
+            return None;
+        };
+        Some(
+            remote
+                .map(|remote| Self {
+                    location: location.to_string(),
+                    ..remote
+                })
+                .with_context(|| tr!("unable to fetch the project at {location}", location)),
+        )
+    }
+
+    /// Whether changes can be written back
+    pub const fn is_read_only(&self) -> bool {
+        self.destination.is_none()
+    }
+
+    /// The local copy of the project directory
+    pub fn dir(&self) -> &Path {
+        self.local.path()
+    }
+
+    /// Copies the files that changed back to the remote machine
+    pub fn push(&self) -> Result<()> {
+        for (name, before) in &self.files {
+            let after = fs::read(self.local.path().join(name)).ok();
+            if after == *before {
+                continue;
+            }
+            let Some(destination) = &self.destination else {
+                bail!(tutel::Error::ReadOnly(self.location.clone()));
+            };
+            let file = format!("{}/{}", self.dir, quote(name));
+            match after {
+                // the file is replaced in one step, a broken connection
+                // never leaves half of it behind
+                Some(content) => {
+                    let tmp = format!("{}/{}", self.dir, quote(&format!("{name}.tmp")));
+                    let command = format!("cat > {tmp} && mv {tmp} {file}");
+                    ssh(destination, &command, Some(&content))?;
+                }
+                None => {
+                    ssh(destination, &format!("rm -f {file}"), None)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fetch_ssh(rest: &str) -> Result<Remote> {
+    let (host, path) = rest
+        .split_once('/')
+        .context(tr("no path to the project given"))?;
+    if host.is_empty() {
+        bail!(tr("no host given"));
+    }
+    let destination = format!("ssh://{host}");
+    let path = path.trim_end_matches('/');
+
+    // the project file is looked for like in a local directory, unless the
+    // path names it
+    let names = tutel::Format::ALL
+        .iter()
+        .map(|format| format.file_name())
+        .collect::<Vec<_>>();
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) if names.contains(&name) => (shell_path(dir), name.to_string()),
+        _ if names.contains(&path) => (shell_path(""), path.to_string()),
+        _ => {
+            let dir = shell_path(path);
+            let find = format!(
+                "cd {dir} || exit 1; \
+                for f in {}; do if [ -f \"$f\" ]; then echo \"$f\"; exit; fi; done; exit 1",
+                names.join(" ")
+            );
+            let found = ssh(&destination, &find, None).context(tutel::Error::NoProject)?;
+            (dir, String::from_utf8_lossy(&found).trim().to_string())
+        }
+    };
+
+    let local = tempfile::tempdir()?;
+    let mut files = Vec::new();
+    for name in [name.as_str(), tutel::ARCHIVE_FILE_NAME] {
+        let content = ssh(
+            &destination,
+            &format!("cat {dir}/{} 2>/dev/null", quote(name)),
+            None,
+        )
+        .ok();
+        if let Some(content) = &content {
+            fs::write(local.path().join(name), content)?;
+        }
+        files.push((name.to_string(), content));
+    }
+    if files[0].1.is_none() {
+        bail!(tutel::Error::NoProject);
+    }
+
+    Ok(Remote {
+        location: String::new(),
+        destination: Some(destination),
+        dir,
+        files,
+        local,
+    })
+}
+
+/// Downloads the project file, or takes it from the cache if it was
+/// downloaded less than a minute ago
+fn fetch_http(url: &str) -> Result<Remote> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let format = tutel::Format::of(Path::new(path));
+    if !format.is_text() {
+        bail!(tr("only text projects can be fetched over http"));
+    }
+
+    let content = cache::cached("http", url, HTTP_MAX_AGE, || {
+        download(url)
+            .map_err(|e| eprintln!("[tutel] {url}: {e}"))
+            .ok()
+    })
+    .context(tr("download failed, it is tried again after a minute"))?;
+
+    let local = tempfile::tempdir()?;
+    let name = format.file_name();
+    fs::write(local.path().join(name), &content)?;
+    Ok(Remote {
+        location: String::new(),
+        destination: None,
+        dir: String::new(),
+        files: vec![
+            (name.to_string(), Some(content.into_bytes())),
+            (tutel::ARCHIVE_FILE_NAME.to_string(), None),
+        ],
+        local,
+    })
+}
+
+fn download(url: &str) -> Result<String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", "10", url])
+        .output()
+        .context(tr("curl not found"))?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn ssh(destination: &str, command: &str, input: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut child = Command::new("ssh")
+        .arg(destination)
+        .arg(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .spawn()
+        .context(tr("ssh not found"))?;
+
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(tr!("ssh {destination} failed", destination));
+    }
+    Ok(output.stdout)
+}
+
+/// The path on the remote machine, ready for the shell. Absolute unless it
+/// starts with `~`, for the home directory.
+fn shell_path(path: &str) -> String {
+    if path == "~" {
+        return path.to_string();
+    }
+    path.strip_prefix("~/").map_or_else(
+        || format!("/{}", quote(path)),
+        |rest| format!("~/{}", quote(rest)),
+    )
+}
+
+/// Quotes the text for a posix shell
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[test]
+fn shell_quoting() {
+    assert_eq!(quote("my list"), "'my list'");
+    assert_eq!(quote("it's"), r"'it'\''s'");
+    assert_eq!(shell_path("srv/my list"), "/'srv/my list'");
+    assert_eq!(shell_path("~/todo"), "~/'todo'");
+    assert_eq!(shell_path("~"), "~");
+}
@@ -0,0 +1,137 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{date, theme::Style, tr, Priority, Task};
+
+/// Something a task has to fulfill for a rule to apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Pending,
+    Done,
+    Waiting,
+    /// Added more than this many seconds ago
+    OlderThan(u64),
+    /// Added less than this many seconds ago
+    NewerThan(u64),
+    /// Still open after the due date
+    Overdue,
+    /// Still open and due in less than this many seconds, or overdue
+    DueWithin(u64),
+    Priority(Priority),
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let age = |duration: &str| {
+            date::parse_duration(duration)
+                .with_context(|| tr!("invalid duration: {duration}. use e.g. 3d or 2w", duration))
+        };
+
+        Ok(match words.as_slice() {
+            ["pending"] => Self::Pending,
+            ["done"] => Self::Done,
+            ["waiting"] => Self::Waiting,
+            ["age", ">", duration] => Self::OlderThan(age(duration)?),
+            ["age", "<", duration] => Self::NewerThan(age(duration)?),
+            ["overdue"] => Self::Overdue,
+            ["due", "<", duration] => Self::DueWithin(age(duration)?),
+            ["priority", "=", priority] => Self::Priority(priority.parse()?),
+            _ => bail!(tr!("unknown condition: {text}. available are pending, done, waiting, overdue, age > DURATION, age < DURATION, due < DURATION and priority = PRIORITY", text)),
+        })
+    }
+
+    fn matches(&self, task: &Task, now: u64) -> bool {
+        let age = task.created.map(|created| now.saturating_sub(created));
+        match self {
+            Self::Pending => !task.is_completed(),
+            Self::Done => task.is_completed(),
+            Self::Waiting => task.is_waiting(),
+            Self::OlderThan(secs) => age.is_some_and(|age| age > *secs),
+            Self::NewerThan(secs) => age.is_some_and(|age| age < *secs),
+            Self::Overdue => task.is_overdue(now),
+            Self::DueWithin(secs) => {
+                !task.is_completed() && task.due.is_some_and(|due| due < now.saturating_add(*secs))
+            }
+            Self::Priority(priority) => task.priority == Some(*priority),
+        }
+    }
+}
+
+/// Styles the description of every task matching all of its conditions,
+/// e.g. `when = "pending and age > 2w"`, `style = "yellow"`
+#[derive(Debug, Clone)]
+pub struct Rule {
+    conditions: Vec<Condition>,
+    pub style: Style,
+}
+
+/// The rules used unless the config has its own
+const DEFAULTS: &[(&str, &str)] = &[
+    ("overdue", "red"),
+    ("due < 2d", "yellow"),
+    ("priority = high", "bold"),
+    ("waiting", "dimmed"),
+];
+
+impl Rule {
+    /// Overdue tasks in red, those due within two days in yellow, high
+    /// priority ones in bold and waiting ones dimmed
+    pub fn defaults() -> Vec<Self> {
+        DEFAULTS
+            .iter()
+            .filter_map(|(when, style)| Self::parse(when, style).ok())
+            .collect()
+    }
+
+    /// Parses a rule from its condition and style
+    ///
+    /// # Errors
+    /// This function will return an error if the condition or style is invalid
+    pub fn parse(when: &str, style: &str) -> Result<Self> {
+        let conditions = when
+            .split(" and ")
+            .map(Condition::parse)
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            conditions,
+            style: Style::parse(style)?,
+        })
+    }
+
+    /// Whether the rule applies to the task at the given time
+    pub fn matches(&self, task: &Task, now: u64) -> bool {
+        self.conditions.iter().all(|c| c.matches(task, now))
+    }
+}
+
+#[test]
+fn parse_rules() {
+    let rule = Rule::parse("pending and age > 2w", "yellow").unwrap();
+    assert_eq!(
+        rule.conditions,
+        [Condition::Pending, Condition::OlderThan(14 * 24 * 60 * 60)]
+    );
+
+    assert!(Rule::parse("age > soon", "yellow").is_err());
+    assert!(Rule::parse("due > 2d", "red").is_err());
+    assert!(Rule::parse("priority = urgent", "bold").is_err());
+    assert_eq!(Rule::defaults().len(), DEFAULTS.len());
+}
+
+#[test]
+fn match_due_dates_and_priorities() {
+    let now = 1_700_000_000;
+    let mut task = Task::new("", false, 0);
+    task.due = Some(now + 60 * 60);
+    task.priority = Some(Priority::High);
+
+    let rule = |when| Rule::parse(when, "red").unwrap();
+    assert!(!rule("overdue").matches(&task, now));
+    assert!(rule("due < 2d").matches(&task, now));
+    assert!(rule("overdue").matches(&task, now + 2 * 60 * 60));
+    assert!(rule("pending and priority = high").matches(&task, now));
+
+    task.set_status(crate::Status::Done);
+    assert!(!rule("due < 2d").matches(&task, now));
+}
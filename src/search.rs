@@ -0,0 +1,420 @@
+use anyhow::{bail, Result};
+use std::{
+    collections::BTreeMap,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{
+    data::{edit_distance, fnv, words},
+    tr, Project, Task, ARCHIVE_FILE_NAME, DEFAULT_LIST,
+};
+
+/// A task with all the words searched for in its description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub index: usize,
+    pub id: String,
+    pub desc: String,
+    /// The list the task is on, none if it is archived
+    pub list: Option<String>,
+    /// How far the words of the description are from the ones searched
+    /// for, lower is closer. Always 0 unless the search is fuzzy.
+    pub score: usize,
+    /// The parts of the description that matched, in order
+    pub spans: Vec<Range<usize>>,
+}
+
+impl Hit {
+    /// The part of the description to show in `width` characters, starting
+    /// a little before the first match if it doesn't fit
+    pub fn context(&self, width: usize) -> Range<usize> {
+        let boundaries: Vec<usize> = self
+            .desc
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(self.desc.len()))
+            .collect();
+        let chars = boundaries.len() - 1;
+        if chars <= width {
+            return 0..self.desc.len();
+        }
+
+        let first = self
+            .spans
+            .first()
+            .map_or(0, |span| boundaries.partition_point(|&b| b < span.start));
+        let start = first.saturating_sub(width / 3).min(chars - width);
+        boundaries[start]..boundaries[start + width]
+    }
+}
+
+/// The archived tasks of a project by the words of their descriptions, so
+/// a search doesn't have to read the whole archive
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Index {
+    /// The archive file as it was when the index was built, see [`stamp`]
+    stamp: String,
+    /// Index, id and description of every archived task
+    tasks: Vec<(usize, String, String)>,
+    /// Every word, with the positions in `tasks` it is found at
+    words: BTreeMap<String, Vec<usize>>,
+}
+
+impl Index {
+    fn build(stamp: String, archived: &[Task]) -> Self {
+        let mut index = Self {
+            stamp,
+            ..Self::default()
+        };
+        for (n, task) in archived.iter().enumerate() {
+            // the index is line based
+            let desc = task.desc.replace(['\t', '\n'], " ");
+            for word in words(&desc) {
+                let positions = index.words.entry(word).or_default();
+                if positions.last() != Some(&n) {
+                    positions.push(n);
+                }
+            }
+            index.tasks.push((task.index, task.id.clone(), desc));
+        }
+        index
+    }
+
+    /// The positions in `tasks` of those with a word matching each of the
+    /// terms, with their score like [`score`] gives it
+    fn lookup(&self, terms: &[String], fuzzy: bool) -> BTreeMap<usize, usize> {
+        let mut found: Option<BTreeMap<usize, usize>> = None;
+        for term in terms {
+            let mut scores = BTreeMap::new();
+            let mut add = |positions: &[usize], score: usize| {
+                for position in positions {
+                    let best = scores.entry(*position).or_insert(score);
+                    *best = score.min(*best);
+                }
+            };
+            if fuzzy {
+                // every word needs a look, a typo can be anywhere
+                for (word, positions) in &self.words {
+                    if let Some(score) = word_score(term, word, true) {
+                        add(positions, score);
+                    }
+                }
+            } else {
+                self.words
+                    .range(term.clone()..)
+                    .take_while(|(word, _)| word.starts_with(term.as_str()))
+                    .for_each(|(_, positions)| add(positions, 0));
+            }
+            found = Some(match found {
+                Some(found) => found
+                    .into_iter()
+                    .filter_map(|(n, total)| Some((n, total + scores.get(&n)?)))
+                    .collect(),
+                None => scores,
+            });
+        }
+        found.unwrap_or_default()
+    }
+
+    fn render(&self) -> String {
+        let mut out = format!("{}\n", self.stamp);
+        for (index, id, desc) in &self.tasks {
+            out.push_str(&format!("t\t{index}\t{id}\t{desc}\n"));
+        }
+        for (word, positions) in &self.words {
+            let positions: Vec<String> = positions.iter().map(usize::to_string).collect();
+            out.push_str(&format!("w\t{word}\t{}\n", positions.join(" ")));
+        }
+        out
+    }
+
+    fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        let mut index = Self {
+            stamp: lines.next()?.to_string(),
+            ..Self::default()
+        };
+        for line in lines {
+            match line.split('\t').collect::<Vec<_>>().as_slice() {
+                ["t", task, id, desc] => {
+                    index
+                        .tasks
+                        .push((task.parse().ok()?, (*id).to_string(), (*desc).to_string()));
+                }
+                ["w", word, positions] => {
+                    let positions = positions
+                        .split(' ')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .ok()?;
+                    index.words.insert((*word).to_string(), positions);
+                }
+                _ => return None,
+            }
+        }
+        Some(index)
+    }
+}
+
+/// Where the index of the project's archive is kept, named after the
+/// project file like its undo history
+fn index_file(project_file: &Path) -> Option<PathBuf> {
+    let path = crate::original_path(project_file).canonicalize().ok()?;
+    let key = format!("{:016x}", fnv(&path.to_string_lossy()));
+    Some(crate::data_dir()?.join("search").join(key))
+}
+
+/// The size and modification time of the file, so an index of an archive
+/// that was changed by hand or by `undo` isn't trusted
+fn stamp(file: &Path) -> String {
+    fs::metadata(file)
+        .and_then(|m| Ok((m.len(), m.modified()?)))
+        .ok()
+        .and_then(|(len, modified)| {
+            let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+            Some(format!("{len} {nanos}"))
+        })
+        .unwrap_or_else(|| "missing".to_string())
+}
+
+/// Writes the index of the archive next to the project file, after it was
+/// written with these tasks.
+///
+/// # Errors
+/// This function will return an error if the index can't be written.
+pub fn update_index(project_file: &Path, archived: &[Task]) -> Result<()> {
+    let Some(file) = index_file(project_file) else {
+        return Ok(());
+    };
+    let archive = project_file.with_file_name(ARCHIVE_FILE_NAME);
+    let index = Index::build(stamp(&archive), archived);
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(file, index.render())?;
+    Ok(())
+}
+
+/// The index of the project's archive, if there is one that is up to date
+fn load_index(project_file: &Path) -> Option<Index> {
+    let index = Index::parse(&fs::read_to_string(index_file(project_file)?).ok()?)?;
+    let archive = project_file.with_file_name(ARCHIVE_FILE_NAME);
+    (index.stamp == stamp(&archive)).then_some(index)
+}
+
+/// How far the word is from the term, `None` if too far. Words starting
+/// with the term score 0. Fuzzy matching accepts a typo per four letters of
+/// the term and the letters of the term in order, like `cfg` for `config`,
+/// scoring one per typo and one per two skipped letters.
+fn word_score(term: &str, word: &str, fuzzy: bool) -> Option<usize> {
+    if word.starts_with(term) {
+        return Some(0);
+    }
+    if !fuzzy {
+        return None;
+    }
+
+    let length = term.chars().count();
+    let prefix: String = word.chars().take(length).collect();
+    let typos = edit_distance(term, word).min(edit_distance(term, &prefix));
+    let typos = (typos <= length / 4).then_some(typos);
+
+    let mut letters = word.chars();
+    let skipped = (length >= 3 && term.chars().all(|c| letters.any(|l| l == c)))
+        .then(|| 1 + (word.chars().count() - length) / 2);
+
+    match (typos, skipped) {
+        (Some(typos), Some(skipped)) => Some(typos.min(skipped)),
+        (typos, skipped) => typos.or(skipped),
+    }
+}
+
+/// The score of the description if each of the terms matches one of its
+/// words, summed up over the terms
+fn score(terms: &[String], desc: &str, fuzzy: bool) -> Option<usize> {
+    let words = words(desc);
+    terms
+        .iter()
+        .map(|term| {
+            words
+                .iter()
+                .filter_map(|word| word_score(term, word, fuzzy))
+                .min()
+        })
+        .sum()
+}
+
+/// The words of the description the terms match. Only the matched start of
+/// a word counts, or all of it for a fuzzy match.
+fn spans(terms: &[String], desc: &str, fuzzy: bool) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    // the same words as `words` finds, with where they are
+    for (i, c) in desc
+        .char_indices()
+        .chain(std::iter::once((desc.len(), ' ')))
+    {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(begin)) => {
+                start = None;
+                let word = &desc[begin..i];
+                let lower = word.to_lowercase();
+                let end = terms
+                    .iter()
+                    .filter(|term| word_score(term, &lower, fuzzy).is_some())
+                    .map(|term| {
+                        if lower.starts_with(term.as_str()) {
+                            word.char_indices()
+                                .nth(term.chars().count())
+                                .map_or(word.len(), |(end, _)| end)
+                        } else {
+                            word.len()
+                        }
+                    })
+                    .max();
+                if let Some(end) = end {
+                    spans.push(begin..begin + end);
+                }
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// Finds the tasks of all lists of the project and its archive that have a
+/// word starting with each of the words of `text`, ignoring case.
+///
+/// With `fuzzy`, words with typos or with the letters of a search word in
+/// order match as well, and the closest matches come first. With
+/// `use_index` the archive is looked up in its index, which is built first
+/// if it is missing or out of date.
+///
+/// # Errors
+/// This function will return an error if there is nothing to search for or
+/// the archive can't be read.
+pub fn search(project: &Project, text: &str, fuzzy: bool, use_index: bool) -> Result<Vec<Hit>> {
+    let terms = &words(text);
+    if terms.is_empty() {
+        bail!(tr("nothing to search for"));
+    }
+
+    let current = project.list.as_deref().unwrap_or(DEFAULT_LIST);
+    let lists = std::iter::once((current, &project.data.tasks)).chain(
+        project
+            .data
+            .lists
+            .iter()
+            .map(|(name, tasks)| (name.as_str(), tasks)),
+    );
+    let mut hits: Vec<Hit> = lists
+        .flat_map(|(list, tasks)| {
+            tasks.iter().filter_map(move |t| {
+                Some(Hit {
+                    index: t.index,
+                    id: t.id.clone(),
+                    desc: t.desc.clone(),
+                    list: Some(list.to_string()),
+                    score: score(terms, &t.desc, fuzzy)?,
+                    spans: Vec::new(),
+                })
+            })
+        })
+        .collect();
+
+    let index = if use_index {
+        load_index(&project.path)
+    } else {
+        None
+    };
+    let archived = match index {
+        Some(index) => index,
+        None => {
+            let archived = project.read_archive()?.map(|a| a.tasks).unwrap_or_default();
+            if use_index {
+                // searching works without it, just slower
+                let _ = update_index(&project.path, &archived);
+            }
+            Index::build(String::new(), &archived)
+        }
+    };
+    hits.extend(archived.lookup(terms, fuzzy).into_iter().map(|(n, score)| {
+        let (index, id, desc) = &archived.tasks[n];
+        Hit {
+            index: *index,
+            id: id.clone(),
+            desc: desc.clone(),
+            list: None,
+            score,
+            spans: Vec::new(),
+        }
+    }));
+    for hit in &mut hits {
+        hit.spans = spans(terms, &hit.desc, fuzzy);
+    }
+    // stable, so equally close tasks stay in the order of their lists
+    hits.sort_by_key(|hit| hit.score);
+
+    Ok(hits)
+}
+
+#[test]
+fn index_lookup() {
+    let archived = [
+        Task::new("Fix the flaky test", true, 0),
+        Task::new("fix CI, again", true, 1),
+        Task::new("release\tv2", true, 2),
+    ];
+    let index = Index::build("stamp".to_string(), &archived);
+    assert_eq!(Index::parse(&index.render()), Some(index));
+
+    let index = Index::parse(&Index::build(String::new(), &archived).render()).unwrap();
+    let terms = |text: &str| {
+        index
+            .lookup(&words(text), false)
+            .into_keys()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(terms("fix"), [0, 1]);
+    assert_eq!(terms("FIX fla"), [0]);
+    assert_eq!(terms("v2"), [2]);
+    assert!(terms("fix release").is_empty());
+
+    let fuzzy = |text: &str| {
+        index
+            .lookup(&words(text), true)
+            .into_iter()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(fuzzy("flakey tset"), []);
+    assert_eq!(fuzzy("flakey test"), [(0, 1)]);
+    assert_eq!(fuzzy("test flaky"), [(0, 0)]);
+    assert_eq!(fuzzy("agn"), [(1, 2)]);
+}
+
+#[test]
+fn match_context() {
+    let terms = words("fix fla");
+    let desc = "Fix the flaky test, fixing it for good";
+    assert_eq!(spans(&terms, desc, false), [0..3, 8..11, 20..23]);
+    assert_eq!(
+        spans(&words("flakey fix"), desc, true),
+        [0..3, 8..13, 20..23]
+    );
+
+    let hit = Hit {
+        index: 0,
+        id: String::new(),
+        desc: "a long description before the flaky test".to_string(),
+        list: None,
+        score: 0,
+        spans: spans(&terms, "a long description before the flaky test", false),
+    };
+    assert_eq!(&hit.desc[hit.context(100)], hit.desc);
+    assert_eq!(&hit.desc[hit.context(12)], "the flaky te");
+    assert_eq!(&hit.desc[hit.context(3)], " fl");
+}
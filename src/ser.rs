@@ -1,15 +1,24 @@
 use serde::{ser::SerializeStruct, Serialize};
 
-use super::{data::ProjectData, Task};
+use super::{
+    data::{Interval, Priority, ProjectData, Status},
+    Task,
+};
 
 impl Serialize for ProjectData {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("ProjectData", 2)?;
+        let len = if self.lists.is_empty() { 2 } else { 3 };
+        let mut state = serializer.serialize_struct("ProjectData", len)?;
         state.serialize_field("name", &self.name)?;
         state.serialize_field("tasks", &self.tasks)?;
+        if self.lists.is_empty() {
+            state.skip_field("lists")?;
+        } else {
+            state.serialize_field("lists", &self.lists)?;
+        }
         state.end()
     }
 }
@@ -19,10 +28,144 @@ impl Serialize for Task {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("Task", 3)?;
+        // formats like MessagePack write the number of fields up front, so it
+        // has to leave out the skipped ones
+        let present = [
+            self.closed.is_some(),
+            self.note.is_some(),
+            self.created.is_some(),
+            self.estimate.is_some(),
+            self.pomodoros != 0,
+            self.snoozed.is_some(),
+            self.waiting.is_some(),
+            self.assignee.is_some(),
+            self.priority.is_some(),
+            self.due.is_some(),
+            self.pinned,
+            self.hidden,
+            !self.depends.is_empty(),
+            !self.external.is_empty(),
+            !self.attachments.is_empty(),
+            self.alias.is_some(),
+            !self.intervals.is_empty(),
+        ];
+        let len = 4 + present.iter().filter(|p| **p).count();
+
+        let mut state = serializer.serialize_struct("Task", len)?;
         state.serialize_field("desc", &self.desc)?;
-        state.serialize_field("completed", &self.completed)?;
+        state.serialize_field("status", &self.status)?;
+        match &self.closed {
+            Some(closed) => state.serialize_field("closed", closed)?,
+            None => state.skip_field("closed")?,
+        }
+        match &self.note {
+            Some(note) => state.serialize_field("note", note)?,
+            None => state.skip_field("note")?,
+        }
         state.serialize_field("index", &self.index)?;
+        state.serialize_field("id", &self.id)?;
+        match &self.created {
+            Some(created) => state.serialize_field("created", created)?,
+            None => state.skip_field("created")?,
+        }
+        match &self.estimate {
+            Some(estimate) => state.serialize_field("estimate", estimate)?,
+            None => state.skip_field("estimate")?,
+        }
+        if self.pomodoros == 0 {
+            state.skip_field("pomodoros")?;
+        } else {
+            state.serialize_field("pomodoros", &self.pomodoros)?;
+        }
+        match &self.snoozed {
+            Some(snoozed) => state.serialize_field("snoozed", snoozed)?,
+            None => state.skip_field("snoozed")?,
+        }
+        match &self.waiting {
+            Some(waiting) => state.serialize_field("waiting", waiting)?,
+            None => state.skip_field("waiting")?,
+        }
+        match &self.assignee {
+            Some(assignee) => state.serialize_field("assignee", assignee)?,
+            None => state.skip_field("assignee")?,
+        }
+        match &self.priority {
+            Some(priority) => state.serialize_field("priority", priority)?,
+            None => state.skip_field("priority")?,
+        }
+        match &self.due {
+            Some(due) => state.serialize_field("due", due)?,
+            None => state.skip_field("due")?,
+        }
+        if self.pinned {
+            state.serialize_field("pinned", &self.pinned)?;
+        } else {
+            state.skip_field("pinned")?;
+        }
+        if self.hidden {
+            state.serialize_field("hidden", &self.hidden)?;
+        } else {
+            state.skip_field("hidden")?;
+        }
+        if self.depends.is_empty() {
+            state.skip_field("depends")?;
+        } else {
+            state.serialize_field("depends", &self.depends)?;
+        }
+        if self.external.is_empty() {
+            state.skip_field("external")?;
+        } else {
+            state.serialize_field("external", &self.external)?;
+        }
+        match &self.alias {
+            Some(alias) => state.serialize_field("alias", alias)?,
+            None => state.skip_field("alias")?,
+        }
+        if self.attachments.is_empty() {
+            state.skip_field("attachments")?;
+        } else {
+            state.serialize_field("attachments", &self.attachments)?;
+        }
+        if self.intervals.is_empty() {
+            state.skip_field("intervals")?;
+        } else {
+            state.serialize_field("intervals", &self.intervals)?;
+        }
+        state.end()
+    }
+}
+
+impl Serialize for Status {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl Serialize for Priority {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let len = 1 + usize::from(self.end.is_some());
+        let mut state = serializer.serialize_struct("Interval", len)?;
+        state.serialize_field("start", &self.start)?;
+        match &self.end {
+            Some(end) => state.serialize_field("end", end)?,
+            // the interval is still running
+            None => state.skip_field("end")?,
+        }
         state.end()
     }
 }
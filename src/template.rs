@@ -0,0 +1,89 @@
+use anyhow::{bail, Result};
+
+use crate::tr;
+
+/// A named list of tasks that are added together, e.g. the steps of a
+/// release. Descriptions may contain placeholders like `{version}`, which
+/// are filled in when the template is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    pub tasks: Vec<String>,
+}
+
+impl Template {
+    /// The names of the placeholders in all of the tasks, in the order they
+    /// first appear
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for task in &self.tasks {
+            let mut rest = task.as_str();
+            while let Some((name, after)) = next_placeholder(rest) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                rest = after;
+            }
+        }
+        names
+    }
+
+    /// The descriptions of the tasks with every placeholder replaced by its
+    /// value.
+    ///
+    /// # Errors
+    /// This function will return an error if no value is given for one of
+    /// the placeholders.
+    pub fn expand(&self, values: &[(&str, String)]) -> Result<Vec<String>> {
+        let mut expanded = Vec::with_capacity(self.tasks.len());
+        for task in &self.tasks {
+            let mut desc = String::new();
+            let mut rest = task.as_str();
+            while let Some((name, after)) = next_placeholder(rest) {
+                let Some((_, value)) = values.iter().find(|(n, _)| *n == name) else {
+                    bail!(tr!(
+                        "no value for {placeholder}",
+                        placeholder = format!("{{{name}}}")
+                    ));
+                };
+                desc.push_str(&rest[..rest.len() - after.len() - name.len() - 2]);
+                desc.push_str(value);
+                rest = after;
+            }
+            desc.push_str(rest);
+            expanded.push(desc);
+        }
+        Ok(expanded)
+    }
+}
+
+/// Finds the next `{name}` in the text, returning the name and the text after it
+fn next_placeholder(text: &str) -> Option<(&str, &str)> {
+    let start = text.find('{')?;
+    let len = text[start + 1..].find('}')?;
+    let name = &text[start + 1..start + 1 + len];
+    Some((name, &text[start + len + 2..]))
+}
+
+#[test]
+fn expand_placeholders() {
+    let template = Template {
+        name: "release".to_string(),
+        tasks: vec![
+            "bump the version to {version}".to_string(),
+            "tag v{version} on {branch}".to_string(),
+            "announce it".to_string(),
+        ],
+    };
+    assert_eq!(template.placeholders(), ["version", "branch"]);
+
+    let values = [
+        ("version", "1.2".to_string()),
+        ("branch", "main".to_string()),
+    ];
+    assert_eq!(
+        template.expand(&values).unwrap(),
+        ["bump the version to 1.2", "tag v1.2 on main", "announce it"]
+    );
+    assert!(template.expand(&values[..1]).is_err());
+}
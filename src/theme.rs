@@ -0,0 +1,311 @@
+use anyhow::{bail, Result};
+use colored::{Color, ColoredString, Colorize};
+
+use crate::tr;
+
+/// How a single element of the output is rendered
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// A style that leaves the text untouched
+    pub const fn plain() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    const fn fg(color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::plain()
+        }
+    }
+
+    pub(crate) const fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    pub(crate) const fn italic(self) -> Self {
+        Self {
+            italic: true,
+            ..self
+        }
+    }
+
+    pub(crate) const fn underline(self) -> Self {
+        Self {
+            underline: true,
+            ..self
+        }
+    }
+
+    const fn dimmed(self) -> Self {
+        Self {
+            dimmed: true,
+            ..self
+        }
+    }
+
+    /// Parses a style from a whitespace separated list of attributes,
+    /// e.g. `"bold red on_black"` or `"#ff8800 italic"`.
+    ///
+    /// # Errors
+    /// This function will return an error if one of the attributes is neither
+    /// a known color nor a known text attribute.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut style = Self::plain();
+
+        for word in spec.split_whitespace() {
+            match word {
+                "bold" => style.bold = true,
+                "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                _ => {
+                    if let Some(bg) = word.strip_prefix("on_") {
+                        style.bg = Some(parse_color(bg)?);
+                    } else {
+                        style.fg = Some(parse_color(word)?);
+                    }
+                }
+            }
+        }
+
+        Ok(style)
+    }
+
+    /// Applies this style to the given text
+    pub fn paint(&self, text: &str) -> ColoredString {
+        let mut s = text.normal();
+        if let Some(fg) = self.fg {
+            s = s.color(fg);
+        }
+        if let Some(bg) = self.bg {
+            s = s.on_color(bg);
+        }
+        if self.bold {
+            s = s.bold();
+        }
+        if self.dimmed {
+            s = s.dimmed();
+        }
+        if self.italic {
+            s = s.italic();
+        }
+        if self.underline {
+            s = s.underline();
+        }
+        s
+    }
+}
+
+impl Style {
+    /// Applies this style using tmux's `#[...]` format markup instead of
+    /// escape sequences
+    pub fn paint_tmux(&self, text: &str) -> String {
+        let mut attrs = Vec::new();
+        if let Some(fg) = self.fg {
+            attrs.push(format!("fg={}", tmux_color(fg)));
+        }
+        if let Some(bg) = self.bg {
+            attrs.push(format!("bg={}", tmux_color(bg)));
+        }
+        for (set, attr) in [
+            (self.bold, "bold"),
+            (self.dimmed, "dim"),
+            (self.italic, "italics"),
+            (self.underline, "underscore"),
+        ] {
+            if set {
+                attrs.push(attr.to_string());
+            }
+        }
+
+        if attrs.is_empty() {
+            text.to_string()
+        } else {
+            format!("#[{}]{text}#[default]", attrs.join(","))
+        }
+    }
+}
+
+fn tmux_color(color: Color) -> String {
+    match color {
+        Color::Black => "black".to_string(),
+        Color::Red => "red".to_string(),
+        Color::Green => "green".to_string(),
+        Color::Yellow => "yellow".to_string(),
+        Color::Blue => "blue".to_string(),
+        Color::Magenta => "magenta".to_string(),
+        Color::Cyan => "cyan".to_string(),
+        Color::White => "white".to_string(),
+        Color::BrightBlack => "brightblack".to_string(),
+        Color::BrightRed => "brightred".to_string(),
+        Color::BrightGreen => "brightgreen".to_string(),
+        Color::BrightYellow => "brightyellow".to_string(),
+        Color::BrightBlue => "brightblue".to_string(),
+        Color::BrightMagenta => "brightmagenta".to_string(),
+        Color::BrightCyan => "brightcyan".to_string(),
+        Color::BrightWhite => "brightwhite".to_string(),
+        Color::TrueColor { r, g, b } => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+/// Parses a color name(`red`, `bright_blue`) or a hex color(`#rrggbb`)
+fn parse_color(name: &str) -> Result<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() == 6 {
+            if let Ok(rgb) = u32::from_str_radix(hex, 16) {
+                return Ok(Color::TrueColor {
+                    r: (rgb >> 16) as u8,
+                    g: (rgb >> 8) as u8,
+                    b: rgb as u8,
+                });
+            }
+        }
+        bail!(tr!("invalid hex color: {name}", name));
+    }
+
+    match name.replace(['_', '-'], " ").parse() {
+        Ok(color) => Ok(color),
+        Err(_) => bail!(tr!("unknown color or attribute: {name}", name)),
+    }
+}
+
+#[test]
+fn parse_style() {
+    let style = Style::parse("bold bright_red on_#102030").unwrap();
+    assert_eq!(
+        style,
+        Style {
+            fg: Some(Color::BrightRed),
+            bg: Some(Color::TrueColor {
+                r: 0x10,
+                g: 0x20,
+                b: 0x30
+            }),
+            bold: true,
+            ..Style::plain()
+        }
+    );
+    assert!(Style::parse("blod").is_err());
+}
+
+/// The styles used for every element of the output
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: Style,
+    /// How many recursive steps were needed to find the project
+    pub steps: Style,
+    pub index: Style,
+    pub separator: Style,
+    pub completed: Style,
+    pub pending: Style,
+    pub in_progress: Style,
+    pub blocked: Style,
+    pub cancelled: Style,
+    pub description: Style,
+    /// The column names of the table layout
+    pub header: Style,
+    pub age: Style,
+    /// Marks the task that is being worked on
+    pub active: Style,
+    /// The marker of tasks waiting on something else
+    pub waiting: Style,
+    /// The marker of pinned tasks
+    pub pinned: Style,
+    /// The age of tasks that are open for too long
+    pub stale: Style,
+    /// `code` in descriptions
+    pub code: Style,
+    /// The words a search found
+    pub matched: Style,
+}
+
+impl Theme {
+    /// Returns the builtin theme with the given name, if there is one
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            _ => None,
+        }
+    }
+
+    /// Overrides the styles of this theme with those set in the given table.
+    /// Keys that are not set keep their current style.
+    ///
+    /// # Errors
+    /// This function will return an error if the table contains an unknown key
+    /// or a style that cannot be parsed.
+    pub fn apply(&mut self, table: &toml::value::Table) -> Result<()> {
+        for (key, value) in table {
+            let spec = match value.as_str() {
+                Some(spec) => spec,
+                None => bail!(tr!("style of '{key}' must be a string", key)),
+            };
+
+            let style = match key.as_str() {
+                "name" => &mut self.name,
+                "steps" => &mut self.steps,
+                "index" => &mut self.index,
+                "separator" => &mut self.separator,
+                "completed" => &mut self.completed,
+                "pending" => &mut self.pending,
+                "in-progress" => &mut self.in_progress,
+                "blocked" => &mut self.blocked,
+                "cancelled" => &mut self.cancelled,
+                "description" => &mut self.description,
+                "header" => &mut self.header,
+                "age" => &mut self.age,
+                "active" => &mut self.active,
+                "waiting" => &mut self.waiting,
+                "pinned" => &mut self.pinned,
+                "stale" => &mut self.stale,
+                "code" => &mut self.code,
+                "matched" => &mut self.matched,
+                _ => bail!(tr!("unknown theme key: {key}", key)),
+            };
+            *style = Style::parse(spec)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: Style::plain().bold(),
+            steps: Style::fg(Color::Blue),
+            index: Style::plain(),
+            separator: Style::plain().bold(),
+            completed: Style::fg(Color::Green),
+            pending: Style::fg(Color::Red),
+            in_progress: Style::fg(Color::Yellow),
+            blocked: Style::fg(Color::Magenta),
+            cancelled: Style::plain().dimmed(),
+            description: Style::plain(),
+            header: Style::plain().bold(),
+            age: Style::plain(),
+            active: Style::fg(Color::Yellow).bold(),
+            waiting: Style::fg(Color::Cyan),
+            pinned: Style::fg(Color::BrightBlue).bold(),
+            stale: Style::fg(Color::Yellow),
+            code: Style::fg(Color::Cyan),
+            matched: Style::plain().bold().underline(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{date, links, Project};
+
+/// Time spent on a task during a single day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The local day, e.g. `2024-03-01`
+    pub day: String,
+    pub index: usize,
+    pub desc: String,
+    pub secs: u64,
+}
+
+/// Sums up the tracked time of every task per day, between `since` and `now`.
+/// Intervals spanning midnight count towards both days. Entries are ordered
+/// by day, then by task.
+pub fn timesheet(project: &Project, since: u64, now: u64) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for task in &project.data.tasks {
+        for interval in &task.intervals {
+            let mut start = interval.start.max(since);
+            let end = interval.end.unwrap_or(now).min(now);
+
+            while start < end {
+                let split = date::next_midnight(start).map_or(end, |m| m.min(end));
+                let day = date::format_day(start);
+
+                match entries
+                    .iter_mut()
+                    .find(|e| e.day == day && e.index == task.index)
+                {
+                    Some(entry) => entry.secs += split - start,
+                    None => entries.push(Entry {
+                        day,
+                        index: task.index,
+                        desc: task.desc.clone(),
+                        secs: split - start,
+                    }),
+                }
+
+                start = split;
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.day.cmp(&b.day).then(a.index.cmp(&b.index)));
+    entries
+}
+
+/// Sums up the entries per `#tag` in the descriptions, ordered by tag. Time
+/// on a task with several tags counts towards each of them, time on tasks
+/// without any is summed up under `None` at the end.
+pub fn timesheet_by_tag(entries: &[Entry]) -> Vec<(Option<String>, u64)> {
+    let mut tags: Vec<(Option<String>, u64)> = Vec::new();
+    for entry in entries {
+        let found = links::find_tags(&entry.desc);
+        let names = if found.is_empty() {
+            vec![None]
+        } else {
+            found.into_iter().map(|tag| Some(tag.to_string())).collect()
+        };
+        for name in names {
+            match tags.iter_mut().find(|(tag, _)| *tag == name) {
+                Some((_, secs)) => *secs += entry.secs,
+                None => tags.push((name, entry.secs)),
+            }
+        }
+    }
+    // the untagged time comes last
+    tags.sort_by(|a, b| a.0.is_none().cmp(&b.0.is_none()).then(a.0.cmp(&b.0)));
+    tags
+}
+
+#[test]
+fn sum_up_by_tag() {
+    let entry = |day: &str, desc: &str, secs| Entry {
+        day: day.to_string(),
+        index: 0,
+        desc: desc.to_string(),
+        secs,
+    };
+    let entries = [
+        entry("2024-03-01", "fix the build #ci", 60),
+        entry("2024-03-01", "lunch", 30),
+        entry("2024-03-02", "release #ci #web", 120),
+    ];
+    assert_eq!(
+        timesheet_by_tag(&entries),
+        [
+            (Some("ci".to_string()), 180),
+            (Some("web".to_string()), 120),
+            (None, 30)
+        ]
+    );
+}
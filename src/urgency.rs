@@ -0,0 +1,190 @@
+use anyhow::{bail, Result};
+
+use crate::{tr, Priority, Status, Task};
+
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Coefficients of the urgency score, modeled after taskwarrior. Each factor
+/// of a task is scaled to `0.0..=1.0` and multiplied by its coefficient.
+#[derive(Debug, Clone)]
+pub struct Urgency {
+    /// Sort the list by urgency instead of by index
+    pub sort: bool,
+    /// Reached by high priority tasks, medium ones get 0.65 and low ones 0.3
+    pub priority: f64,
+    /// Reached a week after the due date, two weeks before it is 0.2
+    pub due: f64,
+    /// Reached once a task is a year old
+    pub age: f64,
+    /// Reached with three tags, one gets 0.8 and two 0.9
+    pub tags: f64,
+    pub in_progress: f64,
+    /// Usually negative, blocked tasks can't be worked on
+    pub blocked: f64,
+    pub pinned: f64,
+}
+
+impl Default for Urgency {
+    fn default() -> Self {
+        Self {
+            sort: false,
+            priority: 6.0,
+            due: 12.0,
+            age: 2.0,
+            tags: 1.0,
+            in_progress: 4.0,
+            blocked: -5.0,
+            pinned: 5.0,
+        }
+    }
+}
+
+impl Urgency {
+    /// The urgency of a task at the given time. Completed tasks and those
+    /// waiting on something else aren't urgent at all.
+    pub fn score(&self, task: &Task, now: u64) -> f64 {
+        if task.is_completed() || task.is_waiting() {
+            return 0.0;
+        }
+
+        let priority = match task.priority {
+            Some(Priority::High) => 1.0,
+            Some(Priority::Medium) => 0.65,
+            Some(Priority::Low) => 0.3,
+            None => 0.0,
+        };
+        let due = task.due.map_or(0.0, |due| {
+            let overdue = (now as f64 - due as f64) / DAY;
+            if overdue >= 7.0 {
+                1.0
+            } else if overdue >= -14.0 {
+                (overdue + 14.0) * 0.8 / 21.0 + 0.2
+            } else {
+                0.2
+            }
+        });
+        let age = task
+            .created
+            .map_or(0.0, |created| now.saturating_sub(created) as f64 / DAY);
+        let tags = match task.tags().len() {
+            0 => 0.0,
+            1 => 0.8,
+            2 => 0.9,
+            _ => 1.0,
+        };
+        let flag = |set: bool| if set { 1.0 } else { 0.0 };
+
+        [
+            (self.priority, priority),
+            (self.due, due),
+            (self.age, (age / 365.0).min(1.0)),
+            (self.tags, tags),
+            (self.in_progress, flag(task.status == Status::InProgress)),
+            (self.blocked, flag(task.status == Status::Blocked)),
+            (self.pinned, flag(task.pinned)),
+        ]
+        .iter()
+        .map(|(coefficient, factor)| coefficient * factor)
+        .sum()
+    }
+
+    /// Orders tasks by descending urgency, completed ones last. Tasks that
+    /// are equally urgent keep their order.
+    pub fn sort(&self, tasks: &mut [&Task], now: u64) {
+        tasks.sort_by(|a, b| {
+            a.is_completed()
+                .cmp(&b.is_completed())
+                .then_with(|| self.score(b, now).total_cmp(&self.score(a, now)))
+        });
+    }
+
+    /// Overrides coefficients with the values of an `[urgency]` table
+    pub(crate) fn apply(&mut self, table: &toml::value::Table) -> Result<()> {
+        for (key, value) in table {
+            if key == "sort" {
+                match value {
+                    toml::Value::Boolean(sort) => self.sort = *sort,
+                    _ => bail!(tr("'sort' must be true or false")),
+                }
+                continue;
+            }
+            let coefficient = match key.as_str() {
+                "priority" => &mut self.priority,
+                "due" => &mut self.due,
+                "age" => &mut self.age,
+                "tags" => &mut self.tags,
+                "in-progress" => &mut self.in_progress,
+                "blocked" => &mut self.blocked,
+                "pinned" => &mut self.pinned,
+                _ => bail!(tr!("unknown urgency key: {key}", key)),
+            };
+            *coefficient = match value {
+                toml::Value::Float(c) => *c,
+                toml::Value::Integer(c) => *c as f64,
+                _ => bail!(tr!("coefficient '{key}' must be a number", key)),
+            };
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn sort_by_urgency() {
+    let task = |index, completed, created| Task {
+        created,
+        ..Task::new("", completed, index)
+    };
+    let now = 400 * DAY as u64;
+    let tasks = [
+        task(0, true, Some(0)),
+        task(1, false, Some(now - 10 * DAY as u64)),
+        task(2, false, None),
+        task(3, false, Some(0)),
+    ];
+
+    let mut sorted: Vec<&Task> = tasks.iter().collect();
+    Urgency::default().sort(&mut sorted, now);
+
+    let order: Vec<usize> = sorted.iter().map(|t| t.index).collect();
+    assert_eq!(order, [3, 1, 2, 0]);
+}
+
+#[test]
+fn more_than_age_counts() {
+    let now = 400 * DAY as u64;
+    let task = |index, desc: &str| Task {
+        created: Some(now - 300 * DAY as u64),
+        ..Task::new(desc, false, index)
+    };
+    let mut tasks = [
+        task(0, "oldest"),
+        task(1, "tagged #release"),
+        task(2, "important"),
+        task(3, "due"),
+        task(4, "blocked"),
+    ];
+    tasks[0].created = Some(0);
+    tasks[2].priority = Some(Priority::High);
+    tasks[3].due = Some(now - DAY as u64);
+    tasks[4].set_status(Status::Blocked);
+
+    let mut sorted: Vec<&Task> = tasks.iter().collect();
+    let urgency = Urgency::default();
+    urgency.sort(&mut sorted, now);
+    let order: Vec<usize> = sorted.iter().map(|t| t.index).collect();
+    assert_eq!(order, [3, 2, 1, 0, 4]);
+
+    // only the age is left to tell them apart
+    let mut table = toml::value::Table::new();
+    for key in ["priority", "due", "tags", "blocked"] {
+        table.insert(key.to_string(), toml::Value::Integer(0));
+    }
+    let mut by_age = urgency;
+    by_age.apply(&table).unwrap();
+    by_age.sort(&mut sorted, now);
+    assert_eq!(sorted[0].index, 0);
+
+    table.insert("tags".to_string(), toml::Value::Boolean(true));
+    assert!(by_age.apply(&table).is_err());
+}
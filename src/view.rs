@@ -0,0 +1,97 @@
+use anyhow::{bail, Context, Result};
+
+use crate::display::{Column, GroupBy};
+
+use crate::tr;
+
+/// A named combination of `show` options from the config, like
+/// `work = "tag:work assignee:me sort:urgency"`. Options given on the
+/// command line win over the ones of the view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct View {
+    pub name: String,
+    pub tag: Option<String>,
+    pub assignee: Option<String>,
+    pub mentions: Option<String>,
+    pub group_by: Option<GroupBy>,
+    pub limit: Option<usize>,
+    pub columns: Option<Vec<Column>>,
+    /// Sort by urgency, or by index if false
+    pub urgency: Option<bool>,
+    pub stale: bool,
+    pub waiting: bool,
+    pub hidden: bool,
+    pub snoozed: bool,
+}
+
+impl View {
+    /// Reads the view from its terms, separated by whitespace: `key:value`
+    /// for the options with a value, like `tag:work`, `limit:5` or
+    /// `columns:index,description`, and just the name for switches like
+    /// `stale` or `table`.
+    ///
+    /// # Errors
+    /// This function will return an error if a term is unknown or its value
+    /// is invalid.
+    pub fn parse(name: &str, query: &str) -> Result<Self> {
+        let mut view = Self {
+            name: name.to_string(),
+            ..Self::default()
+        };
+
+        for term in query.split_whitespace() {
+            let (key, value) = term.split_once(':').unwrap_or((term, ""));
+            match (key, value) {
+                ("tag", tag) if !tag.is_empty() => {
+                    view.tag = Some(tag.trim_start_matches('#').to_string());
+                }
+                ("assignee", name) if !name.is_empty() => view.assignee = Some(name.to_string()),
+                ("mentions", name) if !name.is_empty() => {
+                    view.mentions = Some(name.trim_start_matches('@').to_string());
+                }
+                ("group" | "group-by", group_by) => view.group_by = Some(group_by.parse()?),
+                ("limit", limit) => {
+                    view.limit = Some(limit.parse().context(tr("limit must be a number"))?);
+                }
+                ("columns", columns) => {
+                    view.columns = Some(columns.split(',').map(str::parse).collect::<Result<_>>()?);
+                }
+                ("table", "") => view.columns = Some(Column::ALL.to_vec()),
+                ("sort", "urgency") => view.urgency = Some(true),
+                ("sort", "index") => view.urgency = Some(false),
+                ("sort", by) => bail!(tr!(
+                    "can't sort by {by}. available are urgency and index",
+                    by
+                )),
+                ("stale", "") => view.stale = true,
+                ("waiting", "") => view.waiting = true,
+                ("hidden", "") => view.hidden = true,
+                ("snoozed", "") => view.snoozed = true,
+                _ => bail!(tr!("unknown term in view {name}: {term}", name, term)),
+            }
+        }
+
+        Ok(view)
+    }
+}
+
+#[test]
+fn parse_views() {
+    let view = View::parse("work", "tag:#work sort:urgency group:status limit:5 stale").unwrap();
+    assert_eq!(
+        view,
+        View {
+            name: "work".to_string(),
+            tag: Some("work".to_string()),
+            group_by: Some(GroupBy::Status),
+            limit: Some(5),
+            urgency: Some(true),
+            stale: true,
+            ..View::default()
+        }
+    );
+
+    assert!(View::parse("due", "sort:due").is_err());
+    assert!(View::parse("typo", "stael").is_err());
+    assert!(View::parse("empty", "tag:").is_err());
+}